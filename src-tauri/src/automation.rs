@@ -0,0 +1,770 @@
+use super::*;
+
+// ============================================
+// AUTOMATION TYPES
+// ============================================
+
+/// Payload of "shutdown-backup-complete"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownBackupEvent {
+    pub success: bool,
+    pub filename: Option<String>,
+    pub message: String,
+}
+
+/// A one-shot backup registered with schedule_backup_at
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledBackup {
+    pub id: String,
+    pub run_at: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledBackupComplete {
+    pub id: String,
+    pub result: Option<BackupResult>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutoBackupStatus {
+    pub enabled: bool,
+    pub paused: bool,
+    /// Configured daily time, HH:MM
+    pub backup_time: String,
+    /// Next occurrence of backup_time; None while disabled or paused
+    pub next_run_at: Option<String>,
+}
+
+/// Serializes access to the persisted schedule file
+#[derive(Default)]
+pub(crate) struct ScheduleState {
+    pub(crate) lock: Mutex<()>,
+}
+
+// Local automation endpoint; the token is required and never reported back
+pub(crate) const AUTOMATION_API_ENABLED_KEY: &str = "automation_api_enabled";
+pub(crate) const AUTOMATION_API_TOKEN_KEY: &str = "automation_api_token";
+pub(crate) const AUTOMATION_API_PORT_KEY: &str = "automation_api_port";
+pub(crate) const DEFAULT_AUTOMATION_API_PORT: u16 = 8765;
+pub(crate) const AUTOMATION_MAX_HEAD_BYTES: usize = 8 * 1024;
+
+// Closing the app waits at most this long for the exit backup
+pub(crate) const SHUTDOWN_BACKUP_TIMEOUT: Duration = Duration::from_secs(20);
+
+// Webhook notified after backups and restores; the secret signs the body
+pub(crate) const WEBHOOK_URL_KEY: &str = "webhook_url";
+pub(crate) const WEBHOOK_SECRET_KEY: &str = "webhook_secret";
+pub(crate) const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+pub(crate) const WEBHOOK_ATTEMPTS: u32 = 3;
+pub(crate) const WEBHOOK_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+// ============================================
+// SCHEDULED AND AUTOMATIC BACKUPS
+// ============================================
+
+pub(crate) fn scheduled_backups_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_app_dir(app)?.join(SCHEDULED_BACKUPS_FILE))
+}
+
+pub(crate) fn load_scheduled_backups(app: &AppHandle) -> Vec<ScheduledBackup> {
+    scheduled_backups_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn save_scheduled_backups(
+    app: &AppHandle,
+    schedules: &[ScheduledBackup],
+) -> Result<(), String> {
+    let path = scheduled_backups_path(app)?;
+    let json = serde_json::to_string_pretty(schedules).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("Failed to save scheduled backups: {}", e))
+}
+
+/// Parses an RFC 3339 timestamp, or a local "YYYY-MM-DDTHH:MM[:SS]" time
+pub(crate) fn parse_local_datetime(value: &str) -> Result<chrono::DateTime<Local>, String> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&Local));
+    }
+
+    [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| chrono::NaiveDateTime::parse_from_str(value, format).ok())
+    .and_then(|naive| naive.and_local_timezone(Local).earliest())
+    .ok_or_else(|| format!("Invalid date/time: {}", value))
+}
+
+/// Waits for a scheduled backup's time on a background thread, then runs it.
+/// Exits quietly if the schedule is cancelled in the meantime.
+pub(crate) fn spawn_scheduled_backup(app: AppHandle, schedule: ScheduledBackup) {
+    std::thread::spawn(move || {
+        let Ok(run_at) = parse_local_datetime(&schedule.run_at) else {
+            return;
+        };
+
+        loop {
+            let still_scheduled = {
+                let state = app.state::<ScheduleState>();
+                let _guard = state.lock.lock();
+                load_scheduled_backups(&app)
+                    .iter()
+                    .any(|s| s.id == schedule.id)
+            };
+            if !still_scheduled {
+                return;
+            }
+
+            let remaining = run_at - Local::now();
+            if remaining <= chrono::Duration::zero() {
+                break;
+            }
+            let wait = remaining.to_std().unwrap_or_default();
+            std::thread::sleep(wait.min(Duration::from_secs(1)));
+        }
+
+        // Remove the schedule before running so a crash mid-backup doesn't re-fire it forever
+        {
+            let state = app.state::<ScheduleState>();
+            let _guard = state.lock.lock();
+            let remaining: Vec<ScheduledBackup> = load_scheduled_backups(&app)
+                .into_iter()
+                .filter(|s| s.id != schedule.id)
+                .collect();
+            if let Err(e) = save_scheduled_backups(&app, &remaining) {
+                log::warn!("{}", e);
+            }
+        }
+
+        let event = match run_guarded_backup(&app, Some(true), None) {
+            Ok(result) => {
+                log_operation(
+                    &app,
+                    OperationRecord::new("scheduled_backup", true, "Scheduled backup completed")
+                        .with_filename(&result.filename),
+                );
+                ScheduledBackupComplete {
+                    id: schedule.id,
+                    result: Some(result),
+                    error: None,
+                }
+            }
+            Err(e) => {
+                log_operation(
+                    &app,
+                    OperationRecord::new("scheduled_backup", false, e.clone()),
+                );
+                ScheduledBackupComplete {
+                    id: schedule.id,
+                    result: None,
+                    error: Some(e),
+                }
+            }
+        };
+
+        let _ = app.emit("scheduled-backup-complete", event);
+    });
+}
+
+/// Re-arms persisted schedules at startup; any that were missed fire immediately
+pub(crate) fn resume_scheduled_backups(app: &AppHandle) {
+    for schedule in load_scheduled_backups(app) {
+        spawn_scheduled_backup(app.clone(), schedule);
+    }
+}
+
+/// Schedules a one-time backup at a local date/time (RFC 3339 or YYYY-MM-DDTHH:MM).
+/// Emits "scheduled-backup-complete" when it runs.
+#[tauri::command]
+pub(crate) fn schedule_backup_at(
+    app: AppHandle,
+    state: State<'_, ScheduleState>,
+    iso_datetime: String,
+) -> Result<ScheduledBackup, String> {
+    let run_at = parse_local_datetime(iso_datetime.trim())?;
+    if run_at <= Local::now() {
+        return Err("Scheduled time must be in the future".to_string());
+    }
+
+    let schedule = ScheduledBackup {
+        id: format!("scheduled_{}", Local::now().timestamp_millis()),
+        run_at: run_at.to_rfc3339(),
+        created_at: Local::now().to_rfc3339(),
+    };
+
+    {
+        let _guard = state
+            .lock
+            .lock()
+            .map_err(|_| "Schedule state is unavailable".to_string())?;
+        let mut schedules = load_scheduled_backups(&app);
+        if schedules.iter().any(|s| s.id == schedule.id) {
+            return Err("A backup was just scheduled. Please try again.".to_string());
+        }
+        schedules.push(schedule.clone());
+        save_scheduled_backups(&app, &schedules)?;
+    }
+
+    spawn_scheduled_backup(app, schedule.clone());
+    Ok(schedule)
+}
+
+/// Lists pending one-time backups, soonest first
+#[tauri::command]
+pub(crate) fn list_scheduled_backups(
+    app: AppHandle,
+    state: State<'_, ScheduleState>,
+) -> Result<Vec<ScheduledBackup>, String> {
+    let _guard = state
+        .lock
+        .lock()
+        .map_err(|_| "Schedule state is unavailable".to_string())?;
+    let mut schedules = load_scheduled_backups(&app);
+    schedules.sort_by(|a, b| a.run_at.cmp(&b.run_at));
+    Ok(schedules)
+}
+
+/// Cancels a pending one-time backup
+#[tauri::command]
+pub(crate) fn cancel_scheduled_backup(
+    app: AppHandle,
+    state: State<'_, ScheduleState>,
+    id: String,
+) -> Result<(), String> {
+    let _guard = state
+        .lock
+        .lock()
+        .map_err(|_| "Schedule state is unavailable".to_string())?;
+    let schedules = load_scheduled_backups(&app);
+    if !schedules.iter().any(|s| s.id == id) {
+        return Err(format!("Scheduled backup not found: {}", id));
+    }
+
+    let remaining: Vec<ScheduledBackup> = schedules.into_iter().filter(|s| s.id != id).collect();
+    save_scheduled_backups(&app, &remaining)
+}
+
+/// Pauses automatic backups without touching their enabled flag or time
+#[tauri::command]
+pub(crate) fn pause_auto_backup(app: AppHandle) -> Result<AutoBackupStatus, String> {
+    set_setting(&app, AUTO_BACKUP_PAUSED_KEY, "1")?;
+    log_operation(
+        &app,
+        OperationRecord::new("auto_backup", true, "Auto-backup paused"),
+    );
+    get_auto_backup_status(app)
+}
+
+/// Whether a backup is taken when the app's window is closed
+#[tauri::command]
+pub(crate) fn get_backup_on_exit(app: AppHandle) -> bool {
+    get_setting(&app, BACKUP_ON_EXIT_KEY).is_some_and(|v| setting_is_true(&v))
+}
+
+/// Turns the backup taken when the app's window is closed on or off
+#[tauri::command]
+pub(crate) fn set_backup_on_exit(app: AppHandle, enabled: bool) -> Result<(), String> {
+    set_setting(&app, BACKUP_ON_EXIT_KEY, if enabled { "1" } else { "0" })
+}
+
+/// Window close hook: with backup_on_exit enabled, holds the close, takes a
+/// VACUUM INTO backup (giving up after SHUTDOWN_BACKUP_TIMEOUT), emits
+/// "shutdown-backup-complete" and then closes the window for real
+pub(crate) fn handle_close_for_backup(window: &tauri::Window, api: &tauri::CloseRequestApi) {
+    let app = window.app_handle().clone();
+    if !is_primary_instance(app.clone()) || !get_backup_on_exit(app.clone()) {
+        return;
+    }
+    if app
+        .state::<BackupState>()
+        .shutdown_backup_started
+        .swap(true, Ordering::SeqCst)
+    {
+        return;
+    }
+    api.prevent_close();
+
+    let window = window.clone();
+    std::thread::spawn(move || {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let worker_app = app.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(run_shutdown_backup(&worker_app));
+        });
+        // A timed-out backup is abandoned; it only ever writes a .partial file,
+        // which remove_abandoned_partials clears on the next start
+        let result = receiver
+            .recv_timeout(SHUTDOWN_BACKUP_TIMEOUT)
+            .unwrap_or_else(|_| {
+                Err(format!(
+                    "Backup did not finish within {} seconds",
+                    SHUTDOWN_BACKUP_TIMEOUT.as_secs()
+                ))
+            });
+
+        let event = match &result {
+            Ok(Some(filename)) => ShutdownBackupEvent {
+                success: true,
+                filename: Some(filename.clone()),
+                message: format!("Backup created on exit: {}", filename),
+            },
+            Ok(None) => ShutdownBackupEvent {
+                success: true,
+                filename: None,
+                message: "Nothing to back up yet".to_string(),
+            },
+            Err(e) => ShutdownBackupEvent {
+                success: false,
+                filename: None,
+                message: e.clone(),
+            },
+        };
+        let record = OperationRecord::new("shutdown_backup", event.success, event.message.clone());
+        log_operation(
+            &app,
+            match &event.filename {
+                Some(filename) => record.with_filename(filename),
+                None => record,
+            },
+        );
+        let _ = app.emit("shutdown-backup-complete", event);
+        let _ = window.destroy();
+    });
+}
+
+/// VACUUM INTO a .partial file, renamed into place once complete.
+/// Ok(None) when the database is still empty.
+pub(crate) fn run_shutdown_backup(app: &AppHandle) -> Result<Option<String>, String> {
+    let db_path = get_db_path(app)?;
+    if !db_path.exists() || describe_empty_database(&db_path).is_some() {
+        return Ok(None);
+    }
+    let backups_dir = get_backups_dir(app)?;
+    let captured_at = Local::now();
+    let backup_filename = format!(
+        "motormods_backup_{}.db",
+        captured_at.format("%Y-%m-%d_%H-%M-%S")
+    );
+    let backup_path = backups_dir.join(&backup_filename);
+    let partial_path = backups_dir.join(format!("{}.partial", backup_filename));
+    let _ = fs::remove_file(&partial_path);
+
+    let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    conn.execute(
+        "VACUUM INTO ?1",
+        params![partial_path.to_string_lossy().to_string()],
+    )
+    .map_err(|e| {
+        let _ = fs::remove_file(&partial_path);
+        format!("Exit backup failed: {}", e)
+    })?;
+    drop(conn);
+    fs::rename(&partial_path, &backup_path).map_err(|e| {
+        let _ = fs::remove_file(&partial_path);
+        format!("Failed to finish exit backup: {}", e)
+    })?;
+
+    let checksum = compute_file_sha256(&backup_path)?;
+    write_checksum_sidecar(&backup_path, &checksum)?;
+    let manifest = BackupManifest {
+        captured_at: Some(captured_at.to_rfc3339()),
+        ..Default::default()
+    };
+    write_backup_manifest(&backup_path, &manifest)?;
+    Ok(Some(backup_filename))
+}
+
+/// Removes .partial files an interrupted backup left in the backups folders,
+/// such as an exit backup abandoned at SHUTDOWN_BACKUP_TIMEOUT when the app
+/// then quit under it. Called by the primary instance at startup, before
+/// anything can be writing one.
+pub(crate) fn remove_abandoned_partials(app: &AppHandle) {
+    let Ok(dirs) = backup_search_dirs(app) else {
+        return;
+    };
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if !path.is_file() || !path.to_string_lossy().ends_with(".partial") {
+                continue;
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => log::info!("Removed abandoned {}", path.display()),
+                Err(e) => log::warn!("Failed to remove {}: {}", path.display(), e),
+            }
+        }
+    }
+}
+
+/// Resumes automatic backups on their existing schedule
+#[tauri::command]
+pub(crate) fn resume_auto_backup(app: AppHandle) -> Result<AutoBackupStatus, String> {
+    set_setting(&app, AUTO_BACKUP_PAUSED_KEY, "0")?;
+    log_operation(
+        &app,
+        OperationRecord::new("auto_backup", true, "Auto-backup resumed"),
+    );
+    get_auto_backup_status(app)
+}
+
+#[tauri::command]
+pub(crate) fn get_auto_backup_status(app: AppHandle) -> Result<AutoBackupStatus, String> {
+    let enabled = get_setting(&app, AUTO_BACKUP_ENABLED_KEY)
+        .map(|v| setting_is_true(&v))
+        .unwrap_or(true);
+    let paused = get_setting(&app, AUTO_BACKUP_PAUSED_KEY)
+        .map(|v| setting_is_true(&v))
+        .unwrap_or(false);
+    let backup_time = get_setting(&app, AUTO_BACKUP_TIME_KEY)
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_AUTO_BACKUP_TIME.to_string());
+
+    let next_run_at = if enabled && !paused {
+        chrono::NaiveTime::parse_from_str(backup_time.trim(), "%H:%M")
+            .ok()
+            .and_then(|time| {
+                let now = Local::now();
+                let today = now.date_naive().and_time(time);
+                let next = if today > now.naive_local() {
+                    today
+                } else {
+                    today + chrono::Duration::days(1)
+                };
+                next.and_local_timezone(Local).earliest()
+            })
+            .map(|dt| dt.to_rfc3339())
+    } else {
+        None
+    };
+
+    Ok(AutoBackupStatus {
+        enabled,
+        paused,
+        backup_time,
+        next_run_at,
+    })
+}
+
+// ============================================
+// AUTOMATION API (local HTTP, off by default)
+// ============================================
+
+/// Starts the automation endpoint when enabled in settings. It only ever binds
+/// to 127.0.0.1 and refuses to start without a token.
+pub(crate) fn start_automation_api(app: &AppHandle) {
+    let enabled = get_setting(app, AUTOMATION_API_ENABLED_KEY)
+        .map(|v| setting_is_true(&v))
+        .unwrap_or(false);
+    if !enabled {
+        return;
+    }
+
+    let Some(token) = get_setting(app, AUTOMATION_API_TOKEN_KEY)
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+    else {
+        log::warn!("Automation API enabled but no token is set; not starting it");
+        return;
+    };
+    let port = get_setting(app, AUTOMATION_API_PORT_KEY)
+        .and_then(|v| v.trim().parse::<u16>().ok())
+        .unwrap_or(DEFAULT_AUTOMATION_API_PORT);
+
+    let listener = match std::net::TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::warn!("Failed to start automation API on port {}: {}", port, e);
+            return;
+        }
+    };
+    log::info!("Automation API listening on 127.0.0.1:{}", port);
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let app = app.clone();
+            let token = token.clone();
+            std::thread::spawn(move || handle_automation_request(&app, stream, &token));
+        }
+    });
+}
+
+pub(crate) fn handle_automation_request(app: &AppHandle, mut stream: TcpStream, token: &str) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(5)));
+
+    let (status, body) = match read_http_head(&mut stream) {
+        Err(e) => (400, serde_json::json!({ "error": e })),
+        Ok((method, path, headers)) => {
+            let authorized = headers
+                .get("authorization")
+                .and_then(|v| v.strip_prefix("Bearer "))
+                .is_some_and(|given| constant_time_eq(given.trim().as_bytes(), token.as_bytes()));
+            if !authorized {
+                (
+                    401,
+                    serde_json::json!({ "error": "Missing or invalid token" }),
+                )
+            } else {
+                route_automation_request(app, &method, &path)
+            }
+        }
+    };
+
+    let body = body.to_string();
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes());
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+pub(crate) fn route_automation_request(
+    app: &AppHandle,
+    method: &str,
+    path: &str,
+) -> (u16, serde_json::Value) {
+    let to_json = |result: Result<serde_json::Value, String>| match result {
+        Ok(value) => (200, value),
+        Err(e) => (500, serde_json::json!({ "error": e })),
+    };
+
+    match (method, path.split('?').next().unwrap_or(path)) {
+        ("POST", "/backup") => {
+            let result = run_guarded_backup(app, None, None)
+                .and_then(|r| serde_json::to_value(r).map_err(|e| e.to_string()));
+            to_json(result)
+        }
+        ("GET", "/backups") => {
+            let result = list_backups(app.clone(), None)
+                .and_then(|r| serde_json::to_value(r).map_err(|e| e.to_string()));
+            to_json(result)
+        }
+        (_, "/backup") | (_, "/backups") => {
+            (405, serde_json::json!({ "error": "Method not allowed" }))
+        }
+        _ => (404, serde_json::json!({ "error": "Not found" })),
+    }
+}
+
+/// Reads the request line and headers (lowercased names). Bodies are ignored;
+/// none of the endpoints take one.
+pub(crate) fn read_http_head(
+    stream: &mut impl Read,
+) -> Result<(String, String, HashMap<String, String>), String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > AUTOMATION_MAX_HEAD_BYTES {
+            return Err("Request headers too large".to_string());
+        }
+        let read = stream
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read request: {}", e))?;
+        if read == 0 {
+            return Err("Incomplete request".to_string());
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+    if method.is_empty() || path.is_empty() {
+        return Err("Malformed request line".to_string());
+    }
+
+    let headers = lines
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    Ok((method, path, headers))
+}
+
+/// Compares secrets without exiting early on the first differing byte
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ============================================
+// WEBHOOKS (backup/restore notifications)
+// ============================================
+
+/// JSON body POSTed to the configured webhook URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    /// "backup.completed", "backup.failed", "restore.completed", "restore.failed" or "test"
+    pub event: String,
+    pub filename: Option<String>,
+    pub size: Option<u64>,
+    pub checksum: Option<String>,
+    pub success: bool,
+    pub message: Option<String>,
+    pub timestamp: String,
+}
+
+impl WebhookPayload {
+    pub(crate) fn new(event: &str, success: bool) -> Self {
+        Self {
+            event: event.to_string(),
+            filename: None,
+            size: None,
+            checksum: None,
+            success,
+            message: None,
+            timestamp: Local::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Configured webhook URL and optional signing secret; None when unset
+pub(crate) fn webhook_config(app: &AppHandle) -> Option<(String, Option<String>)> {
+    let url = get_setting(app, WEBHOOK_URL_KEY)
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())?;
+    let secret = get_setting(app, WEBHOOK_SECRET_KEY).filter(|v| !v.is_empty());
+    Some((url, secret))
+}
+
+/// POSTs the payload, retrying a couple of times. With a secret, the body's
+/// HMAC-SHA256 is sent as "X-Motormods-Signature: sha256=<hex>".
+pub(crate) async fn deliver_webhook(
+    url: &str,
+    secret: Option<&str>,
+    payload: &WebhookPayload,
+) -> Result<(), String> {
+    use hmac::Mac;
+
+    let body = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+    let signature = match secret {
+        Some(secret) => {
+            let mut mac = <hmac::Hmac<sha2::Sha256> as Mac>::new_from_slice(secret.as_bytes())
+                .map_err(|e| format!("Invalid webhook secret: {}", e))?;
+            mac.update(&body);
+            Some(format!("sha256={:x}", mac.finalize().into_bytes()))
+        }
+        None => None,
+    };
+
+    let client = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to set up webhook client: {}", e))?;
+
+    let mut last_error = String::new();
+    for attempt in 0..WEBHOOK_ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::sleep(WEBHOOK_RETRY_DELAY * attempt).await;
+        }
+        let mut request = client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .body(body.clone());
+        if let Some(signature) = &signature {
+            request = request.header("X-Motormods-Signature", signature);
+        }
+        match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => last_error = format!("HTTP {}", response.status()),
+            Err(e) => last_error = e.to_string(),
+        }
+    }
+    Err(format!(
+        "Webhook delivery to {} failed after {} attempts: {}",
+        url, WEBHOOK_ATTEMPTS, last_error
+    ))
+}
+
+/// Sends a webhook in the background if one is configured. Delivery problems
+/// are logged to the operation history and never reach the caller.
+pub(crate) fn fire_webhook(app: &AppHandle, payload: WebhookPayload) {
+    let Some((url, secret)) = webhook_config(app) else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = deliver_webhook(&url, secret.as_deref(), &payload).await {
+            log::warn!("{}", e);
+            log_operation(
+                &app,
+                OperationRecord::new("webhook", false, format!("{}: {}", payload.event, e)),
+            );
+        }
+    });
+}
+
+/// Sends a "test" event to the configured webhook and reports whether it arrived
+#[tauri::command]
+pub(crate) async fn test_webhook(app: AppHandle) -> Result<String, String> {
+    let (url, secret) =
+        webhook_config(&app).ok_or_else(|| "No webhook URL configured".to_string())?;
+    let mut payload = WebhookPayload::new("test", true);
+    payload.message = Some("Test event from MotorMods".to_string());
+    deliver_webhook(&url, secret.as_deref(), &payload).await?;
+    Ok(format!("Webhook delivered to {}", url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_head_yields_method_path_and_lowercased_headers() {
+        let mut request: &[u8] =
+            b"POST /backup?compress=1 HTTP/1.1\r\nHost: localhost\r\nAuthorization:  Bearer abc \r\n\r\nignored body";
+
+        let (method, path, headers) = read_http_head(&mut request).unwrap();
+
+        assert_eq!(method, "POST");
+        assert_eq!(path, "/backup?compress=1");
+        assert_eq!(headers["authorization"], "Bearer abc");
+        assert_eq!(headers["host"], "localhost");
+    }
+
+    #[test]
+    fn http_head_rejects_incomplete_malformed_and_oversized_requests() {
+        let mut incomplete: &[u8] = b"GET /status HTTP/1.1\r\nHost: localhost\r\n";
+        assert_eq!(
+            read_http_head(&mut incomplete).unwrap_err(),
+            "Incomplete request"
+        );
+
+        let mut malformed: &[u8] = b"GET\r\n\r\n";
+        assert_eq!(
+            read_http_head(&mut malformed).unwrap_err(),
+            "Malformed request line"
+        );
+
+        let mut oversized = b"GET / HTTP/1.1\r\nX-Padding: ".to_vec();
+        oversized.resize(AUTOMATION_MAX_HEAD_BYTES + 4096, b'a');
+        assert_eq!(
+            read_http_head(&mut oversized.as_slice()).unwrap_err(),
+            "Request headers too large"
+        );
+    }
+}
@@ -0,0 +1,3907 @@
+use super::*;
+
+// ============================================
+// BACKUP TYPES
+// ============================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupResult {
+    pub filename: String,
+    pub path: String,
+    pub file_size: u64,
+    pub created_at: String,
+    /// Directory the backup was actually written to (may be a rotation drive)
+    pub backup_dir: String,
+    /// Set when today's rotation drive was unavailable and the default directory was used
+    pub warning: Option<String>,
+    /// SHA-256 of the backup file, also stored in the <filename>.sha256 sidecar
+    pub checksum: String,
+    /// Time spent copying the database pages
+    pub duration_ms: u64,
+    /// Copy speed in megabytes (10^6 bytes) per second
+    pub throughput_mbps: f64,
+    /// Set when backup_database skipped a new backup inside the cooldown;
+    /// the other fields then describe the earlier backup
+    pub skipped_reason: Option<String>,
+    /// Set with skipped_reason when the database has no tables or data yet;
+    /// no backup file was written and the other fields are empty
+    #[serde(default)]
+    pub nothing_to_back_up: bool,
+    /// The new file had a SQLite header and passed integrity_check;
+    /// false for backups that were skipped or written without the check
+    #[serde(default)]
+    pub verified: bool,
+    /// Why verification failed. backup_database deletes such a file and
+    /// returns this as its error, so successful results leave it empty.
+    #[serde(default)]
+    pub integrity_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupFileInfo {
+    pub filename: String,
+    pub path: String,
+    pub file_size: u64,
+    /// RFC 3339 for display, or "Unknown" when the file time can't be read
+    pub modified_at: String,
+    /// Milliseconds since the Unix epoch; used for sorting, None when unknown
+    pub modified_epoch_ms: Option<i64>,
+    /// Protected backups are never removed by automatic pruning
+    pub protected: bool,
+    /// PII was scrubbed from this copy; it is not a real restore point
+    pub redacted: bool,
+    /// When the data was captured, from the manifest; None for backups without one
+    pub captured_at: Option<String>,
+}
+
+/// Metadata kept next to a backup as <backup>.json
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupManifest {
+    pub protected: bool,
+    pub redacted: bool,
+    /// When the data was captured; unlike the file mtime it survives copies and moves
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub captured_at: Option<String>,
+    /// Tables copied without their rows by create_backup_excluding
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub excluded_tables: Vec<String>,
+    /// Set by create_verified_backup once integrity and checksum checks passed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<String>,
+    /// Safety backups only: the operation they were taken before, e.g. "restore"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safety_for: Option<String>,
+    /// Safety backups only: the backup or file that operation applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safety_target: Option<String>,
+}
+
+/// A safety backup with the operation it protects against, for list_safety_backups
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SafetyBackupInfo {
+    #[serde(flatten)]
+    pub info: BackupFileInfo,
+    pub operation: Option<String>,
+    pub target: Option<String>,
+    /// e.g. "Created before restoring motormods_backup_2024-03-01.db"
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableDelta {
+    pub table: String,
+    pub backup_count: i64,
+    pub live_count: i64,
+    /// backup_count - live_count; negative when the backup has fewer rows
+    pub delta: i64,
+}
+
+/// A backup compared with the live database, for list_backups_with_delta
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupWithDelta {
+    #[serde(flatten)]
+    pub info: BackupFileInfo,
+    pub tables: Vec<TableDelta>,
+    /// Latest created_at/updated_at found in the backup's key tables
+    pub last_change_at: Option<String>,
+    /// "newer", "older" or "same" relative to the live data; "unknown" when undecidable
+    pub relation: String,
+    /// Set when the backup couldn't be opened; tables is empty then
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionColumn {
+    pub table: String,
+    pub column: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    /// Columns to scrub; defaults to customer contact details and user credentials
+    pub columns: Vec<RedactionColumn>,
+    /// Replace values with a short SHA-256 rather than a fixed placeholder,
+    /// so equal values still match each other in the copy
+    pub hash_values: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupProgress {
+    pub copied_bytes: u64,
+    pub total_bytes: u64,
+    pub throughput_mbps: f64,
+    /// Configured cap, if any
+    pub rate_limit_mbps: Option<f64>,
+    /// The copy was paused after this batch to stay under the cap
+    pub throttled: bool,
+}
+
+/// A backup held in memory by backup_to_bytes
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupBytes {
+    pub data: Vec<u8>,
+    /// SHA-256 of `data`, hex
+    pub checksum: String,
+    pub size: u64,
+}
+
+/// Filters for list_backups_filtered; every field is optional
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupQuery {
+    /// Case-insensitive substring of the filename
+    pub text: Option<String>,
+    /// Inclusive bounds on modified time: YYYY-MM-DD or RFC 3339
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    /// Include pre_restore/pre_import/... safety backups (default true)
+    pub include_safety: Option<bool>,
+    /// "modified" (default), "size" or "filename"
+    pub sort_by: Option<String>,
+    /// Defaults to true (newest / largest / Z first)
+    pub descending: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VolumeSpace {
+    /// Directory the next backup would be written to
+    pub path: String,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    /// Space the current user can actually write (may exclude reserved blocks)
+    pub available_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionResult {
+    pub retention_days: u32,
+    pub deleted: Vec<String>,
+    /// Kept despite its age because it was the last backup passing validation
+    pub preserved_last_good: Option<String>,
+    /// Also sent as the "retention-warning" event
+    pub warning: Option<String>,
+    /// Rotation folders that could not be read and were left untouched
+    pub unavailable_folders: Vec<String>,
+}
+
+/// Per-file outcome of delete_backups
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupDeleteOutcome {
+    pub filename: String,
+    pub deleted: bool,
+    pub bytes_freed: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchDeleteResult {
+    pub results: Vec<BackupDeleteOutcome>,
+    /// Total across all deleted files, sidecars included
+    pub bytes_freed: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupSizeEnforcement {
+    pub removed: Vec<String>,
+    /// Size of everything in the backups directory afterwards
+    pub total_bytes: u64,
+    pub max_bytes: u64,
+    /// False when only protected/safety backups remain and the cap still isn't met
+    pub under_cap: bool,
+    pub message: String,
+}
+
+/// Outcome of writing one copy in backup_to_destinations
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DestinationBackupResult {
+    pub destination: String,
+    pub success: bool,
+    pub path: Option<String>,
+    pub file_size: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// A backup entry tagged with the storage backend it lives on
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SourcedBackupInfo {
+    pub source: String,
+    #[serde(flatten)]
+    pub info: BackupFileInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyProgress {
+    pub current: usize,
+    pub total: usize,
+    pub filename: String,
+    pub passed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkProgress {
+    pub current: usize,
+    pub total: usize,
+    /// e.g. "gzip backup"
+    pub step: String,
+}
+
+/// Timings for one backup format in benchmark_backup_formats
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupFormatBenchmark {
+    /// "copy", "vacuum_into" or "gzip"
+    pub format: String,
+    pub size_bytes: u64,
+    pub backup_ms: u64,
+    pub restore_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupBenchmarkResult {
+    pub database_size: u64,
+    pub formats: Vec<BackupFormatBenchmark>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyFailure {
+    pub filename: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct VerifySummary {
+    pub total: usize,
+    pub passed: Vec<String>,
+    pub failed: Vec<VerifyFailure>,
+    /// Backups that have no checksum sidecar; only their SQLite header was checked
+    pub unverified: Vec<String>,
+    pub cancelled: bool,
+}
+
+/// Row counts and last change per backup for list_backups_with_delta,
+/// keyed by path and reused while the file's size and mtime are unchanged
+#[derive(Default)]
+pub(crate) struct BackupDeltaCache {
+    pub(crate) entries: Mutex<HashMap<String, CachedDelta>>,
+}
+
+pub(crate) struct CachedDelta {
+    pub(crate) file_size: u64,
+    pub(crate) modified_epoch_ms: Option<i64>,
+    pub(crate) snapshot: DeltaSnapshot,
+}
+
+#[derive(Clone)]
+pub(crate) struct DeltaSnapshot {
+    pub(crate) counts: Vec<(String, i64)>,
+    pub(crate) last_change_at: Option<String>,
+}
+
+/// Tracks the batch verification job so it can be cancelled and not run twice
+#[derive(Default)]
+pub(crate) struct VerifyState {
+    pub(crate) running: AtomicBool,
+    pub(crate) cancel: AtomicBool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupFreshness {
+    pub last_backup_filename: Option<String>,
+    pub last_backup_at: Option<String>,
+    pub age_seconds: Option<i64>,
+    pub is_stale: bool,
+    pub stale_after_hours: u64,
+    /// Rotation folders that could not be read, so backups there were not counted
+    pub unavailable_folders: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OperationCount {
+    pub operation: String,
+    pub total: usize,
+    pub failed: usize,
+}
+
+/// Backup activity summary for a date range, from generate_backup_report
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupReport {
+    pub from: String,
+    pub to: String,
+    pub generated_at: String,
+    /// Backup-creating operations from the history (backup, auto_backup, ...)
+    pub backup_runs: usize,
+    pub failed_runs: usize,
+    pub operations: Vec<OperationCount>,
+    /// Failed operations of any kind, newest first
+    pub failures: Vec<OperationRecord>,
+    /// Backup files on disk modified within the range
+    pub backup_count: usize,
+    pub total_size: u64,
+    pub oldest: Option<BackupFileInfo>,
+    pub newest: Option<BackupFileInfo>,
+    /// Backups in the range that create_verified_backup checked
+    pub verified_count: usize,
+    /// Backups in the range without a checksum sidecar
+    pub without_checksum: Vec<String>,
+    /// The same report rendered as Markdown, readable as plain text too
+    pub markdown: String,
+}
+
+// Encrypted backup container (.enc):
+// magic (8) | PBKDF2 iterations, u32 LE (4) | salt (16) | stream nonce prefix (7) | chunks
+// Each chunk is ENCRYPTION_CHUNK_SIZE bytes of plaintext sealed with AES-256-GCM (STREAM, BE32)
+pub(crate) const ENCRYPTION_MAGIC: &[u8; 8] = b"MMENC01\0";
+pub(crate) const ENCRYPTION_SALT_LEN: usize = 16;
+pub(crate) const ENCRYPTION_NONCE_PREFIX_LEN: usize = 7;
+pub(crate) const ENCRYPTION_CHUNK_SIZE: usize = 64 * 1024;
+pub(crate) const ENCRYPTION_TAG_LEN: usize = 16;
+pub(crate) const ENCRYPTION_MAX_ITERATIONS: u32 = 10_000_000;
+
+// An uncompressed database sealed in the container above
+pub(crate) const ENCRYPTED_BACKUP_SUFFIX: &str = ".db.enc";
+
+// Archival backups are a snapshot gzipped, then sealed in the container above
+pub(crate) const ARCHIVAL_BACKUP_SUFFIX: &str = ".db.gz.enc";
+pub(crate) const ARCHIVAL_KDF_ITERATIONS: u32 = 600_000;
+pub(crate) const ARCHIVAL_MIN_PASSPHRASE_LEN: usize = 8;
+
+// Source tag for backups stored in the local backups directory
+pub(crate) const LOCAL_BACKUP_SOURCE: &str = "local";
+
+// Columns scrubbed by create_redacted_backup when the config doesn't list any
+pub(crate) const DEFAULT_REDACTED_COLUMNS: &[(&str, &str)] = &[
+    ("invoices", "customer_name"),
+    ("invoices", "customer_phone"),
+    ("users", "name"),
+    ("users", "password_hash"),
+];
+pub(crate) const REDACTION_PLACEHOLDER: &str = "[REDACTED]";
+
+// ============================================
+// ENCRYPTED BACKUP CONTAINER
+// ============================================
+
+/// Header of an encrypted backup container
+pub(crate) struct EncryptionHeader {
+    pub(crate) iterations: u32,
+    pub(crate) salt: [u8; ENCRYPTION_SALT_LEN],
+    pub(crate) nonce_prefix: [u8; ENCRYPTION_NONCE_PREFIX_LEN],
+}
+
+pub(crate) fn read_encryption_header(reader: &mut impl Read) -> Result<EncryptionHeader, String> {
+    let mut magic = [0u8; 8];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|e| format!("Failed to read encrypted backup header: {}", e))?;
+    if &magic != ENCRYPTION_MAGIC {
+        return Err("Not an encrypted MotorMods backup".to_string());
+    }
+
+    let mut iterations = [0u8; 4];
+    let mut salt = [0u8; ENCRYPTION_SALT_LEN];
+    let mut nonce_prefix = [0u8; ENCRYPTION_NONCE_PREFIX_LEN];
+    reader
+        .read_exact(&mut iterations)
+        .and_then(|_| reader.read_exact(&mut salt))
+        .and_then(|_| reader.read_exact(&mut nonce_prefix))
+        .map_err(|e| format!("Failed to read encrypted backup header: {}", e))?;
+
+    let iterations = u32::from_le_bytes(iterations);
+    if iterations == 0 || iterations > ENCRYPTION_MAX_ITERATIONS {
+        return Err("Encrypted backup header is corrupt".to_string());
+    }
+
+    Ok(EncryptionHeader {
+        iterations,
+        salt,
+        nonce_prefix,
+    })
+}
+
+pub(crate) fn derive_encryption_key(passphrase: &str, header: &EncryptionHeader) -> Key<Aes256Gcm> {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(
+        passphrase.as_bytes(),
+        &header.salt,
+        header.iterations,
+        &mut key,
+    );
+    key.into()
+}
+
+// ============================================
+// BACKUP COMMANDS
+// ============================================
+
+/// Creates a backup of the database and returns detailed information
+/// Uses SQLite's backup API to ensure a consistent backup even with WAL mode.
+/// `compress` gzips the verified backup into <name>.db.gz before the size cap
+/// is enforced, so the cap counts the file that is actually kept.
+pub(crate) fn run_database_backup(app: &AppHandle, compress: bool) -> Result<BackupResult, String> {
+    let destination = resolve_backup_destination(app)?;
+    let result = write_database_backup(app, &destination.dir, compress)?;
+
+    if let Some(max_bytes) = get_backup_size_cap(app) {
+        match enforce_backup_size_cap(&destination.dir, max_bytes, Some(&result.filename)) {
+            Ok(outcome) if !outcome.removed.is_empty() || !outcome.under_cap => log_operation(
+                app,
+                OperationRecord::new(
+                    "enforce_max_backup_size",
+                    outcome.under_cap,
+                    outcome.message,
+                ),
+            ),
+            Ok(_) => {}
+            Err(e) => log::warn!("{}", e),
+        }
+    }
+
+    Ok(BackupResult {
+        warning: destination.warning,
+        ..result
+    })
+}
+
+/// The backup pipeline behind run_database_backup, writing into `backups_dir`:
+/// a stepped backup-API snapshot, verified and copied into place with its
+/// checksum and manifest sidecars, then optionally compressed
+pub(crate) fn write_database_backup(
+    app: &AppHandle,
+    backups_dir: &Path,
+    compress: bool,
+) -> Result<BackupResult, String> {
+    let db_path = get_db_path(app)?;
+
+    // Verify source database exists
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let operation = begin_operation(app, "backup", true);
+
+    // Generate backup filename with timestamp
+    let captured_at = Local::now();
+    let timestamp = captured_at.format("%Y-%m-%d_%H-%M-%S").to_string();
+    let backup_filename =
+        unused_backup_filename(backups_dir, &format!("motormods_backup_{}", timestamp));
+    let backup_path = backups_dir.join(&backup_filename);
+    // The backup API writes the pages itself, so it fills a snapshot that is
+    // then copied into place and hashed on the way
+    let snapshot = TempFileGuard(backups_dir.join(format!("{}.snapshot", backup_filename)));
+
+    // Use SQLite's backup API for a proper backup that handles WAL mode
+    // This ensures all data (including WAL) is included in the backup
+    let source_conn = open_database(&db_path, OpenFlags::default())?;
+    
+    let mut backup_conn = Connection::open(&snapshot.0)
+        .map_err(|e| format!("Failed to create backup database: {}", e))?;
+
+    // Use SQLite's backup API
+    let backup = rusqlite::backup::Backup::new(&source_conn, &mut backup_conn)
+        .map_err(|e| format!("Failed to initialize backup: {}", e))?;
+    
+    // Copy in steps so an optional rate limit can pace the I/O
+    let copy_started = Instant::now();
+    let page_size: i64 = source_conn
+        .query_row("PRAGMA page_size", [], |row| row.get(0))
+        .unwrap_or(4096);
+    let copied = run_backup_stepped(
+        app,
+        &backup,
+        page_size as u64,
+        get_backup_rate_limit(app),
+        &operation,
+    );
+
+    // Close the backup so the snapshot is complete on disk before copying it.
+    // A half-written (or cancelled) snapshot is removed by the guard.
+    drop(backup);
+    drop(backup_conn);
+    copied?;
+    let copy_elapsed = copy_started.elapsed();
+
+    let checksum = match finish_backup_copy(&snapshot.0, &backup_path) {
+        Ok(checksum) => checksum,
+        Err(message) => {
+            log_operation(
+                app,
+                OperationRecord::new("backup", false, message.clone())
+                    .with_filename(&backup_filename),
+            );
+            return Err(message);
+        }
+    };
+    drop(snapshot);
+
+    // Get file size
+    let metadata =
+        fs::metadata(&backup_path).map_err(|e| format!("Failed to get backup metadata: {}", e))?;
+
+    let duration_ms = copy_elapsed.as_millis() as u64;
+    let throughput_mbps = throughput_mbps(metadata.len(), copy_elapsed);
+    log_operation(
+        app,
+        OperationRecord::new(
+            "backup",
+            true,
+            format!(
+                "Backup created in {} ms ({:.2} MB/s) at {}",
+                duration_ms,
+                throughput_mbps,
+                backups_dir.display()
+            ),
+        )
+        .with_filename(&backup_filename)
+        .with_throughput(duration_ms, throughput_mbps)
+        .with_database_size(fs::metadata(&db_path).ok().map(|m| m.len())),
+    );
+    check_database_growth(app);
+
+    if let Err(e) = write_checksum_sidecar(&backup_path, &checksum) {
+        log::warn!("{}", e);
+    }
+    let manifest = BackupManifest {
+        captured_at: Some(captured_at.to_rfc3339()),
+        ..Default::default()
+    };
+    if let Err(e) = write_backup_manifest(&backup_path, &manifest) {
+        log::warn!("{}", e);
+    }
+
+    let (backup_filename, backup_path, file_size, checksum) = if compress {
+        let (path, size, checksum) = compress_backup_file(&backup_path)?;
+        (format!("{}.gz", backup_filename), path, size, checksum)
+    } else {
+        (backup_filename, backup_path, metadata.len(), checksum)
+    };
+
+    Ok(BackupResult {
+        filename: backup_filename,
+        path: backup_path.to_string_lossy().to_string(),
+        file_size,
+        created_at: Local::now().to_rfc3339(),
+        backup_dir: backups_dir.to_string_lossy().to_string(),
+        warning: None,
+        checksum,
+        duration_ms,
+        throughput_mbps,
+        skipped_reason: None,
+        nothing_to_back_up: false,
+        verified: true,
+        integrity_error: None,
+    })
+}
+
+/// `<stem>.db`, or `<stem>_2.db` and so on when that name, its compressed form
+/// or its snapshot is already taken in `dir`
+pub(crate) fn unused_backup_filename(dir: &Path, stem: &str) -> String {
+    (1..)
+        .map(|n| match n {
+            1 => format!("{}.db", stem),
+            n => format!("{}_{}.db", stem, n),
+        })
+        .find(|name| {
+            [
+                name.clone(),
+                format!("{}.gz", name),
+                format!("{}.snapshot", name),
+            ]
+            .iter()
+            .all(|taken| !dir.join(taken).exists())
+        })
+        .unwrap()
+}
+
+/// Verifies a backup snapshot and copies it to `backup_path`, returning the
+/// SHA-256 of the bytes written. A source that was corrupt or changed under
+/// the copy gives a snapshot that can't be restored, so nothing is kept then.
+pub(crate) fn finish_backup_copy(snapshot: &Path, backup_path: &Path) -> Result<String, String> {
+    let verified = verify_backup_copy(snapshot);
+    // Reopening a WAL-mode copy read-only can leave -wal/-shm files behind
+    for sidecar in sqlite_sidecar_paths(snapshot) {
+        let _ = fs::remove_file(sidecar);
+    }
+    verified.map_err(|e| format!("Backup failed verification and was deleted: {}", e))?;
+    let (_, checksum) = copy_with_sha256(snapshot, backup_path)
+        .map_err(|e| describe_copy_error("Failed to write backup", &e, snapshot, backup_path))?;
+    Ok(checksum)
+}
+
+/// Checks that a freshly written backup starts with the SQLite header and
+/// passes integrity_check when reopened read-only
+pub(crate) fn verify_backup_copy(backup_path: &Path) -> Result<(), String> {
+    if !has_sqlite_header(backup_path)? {
+        return Err("File does not start with the SQLite header".to_string());
+    }
+    let conn = open_database(backup_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to reopen backup: {}", e))?;
+    let check = run_integrity_check(&conn);
+    if check != "ok" {
+        return Err(format!("Integrity check failed: {}", check));
+    }
+    Ok(())
+}
+
+/// Backs up the database unless a backup already succeeded within the
+/// configured cooldown, in which case that earlier backup is returned with
+/// `skipped_reason` set. A database with nothing in it yet is not backed up
+/// either (`nothing_to_back_up`). `force` bypasses both checks. `compress`
+/// gzips the verified backup into motormods_backup_<timestamp>.db.gz.
+/// Runs on a blocking thread, as a backup rate limit sleeps between copy steps.
+#[tauri::command]
+pub(crate) async fn backup_database(
+    app: AppHandle,
+    force: Option<bool>,
+    compress: Option<bool>,
+) -> Result<BackupResult, String> {
+    tauri::async_runtime::spawn_blocking(move || run_guarded_backup(&app, force, compress))
+        .await
+        .map_err(|e| format!("Backup task failed: {}", e))?
+}
+
+/// The earlier backup with `skipped_reason` set when it succeeded less than
+/// `cooldown` ago; None when a new backup should be taken
+pub(crate) fn backup_within_cooldown(
+    last_success: Option<&(Instant, BackupResult)>,
+    cooldown: Duration,
+) -> Option<BackupResult> {
+    let (taken_at, previous) = last_success?;
+    let ago = taken_at.elapsed();
+    if cooldown.is_zero() || ago >= cooldown {
+        return None;
+    }
+    Some(BackupResult {
+        skipped_reason: Some(format!("Backup taken {} seconds ago", ago.as_secs())),
+        ..previous.clone()
+    })
+}
+
+/// backup_database for callers that are already off the main thread
+pub(crate) fn run_guarded_backup(
+    app: &AppHandle,
+    force: Option<bool>,
+    compress: Option<bool>,
+) -> Result<BackupResult, String> {
+    let state = app.state::<BackupState>();
+    // Held for the whole backup so repeated presses queue up and then see the cooldown
+    let mut last_success = state
+        .last_success
+        .lock()
+        .map_err(|_| "Backup state is unavailable".to_string())?;
+
+    if !force.unwrap_or(false) {
+        if let Some(skipped) =
+            backup_within_cooldown(last_success.as_ref(), get_backup_cooldown(app))
+        {
+            return Ok(skipped);
+        }
+    }
+
+    if !force.unwrap_or(false) {
+        if let Some(reason) = describe_empty_database(&get_db_path(app)?) {
+            return Ok(BackupResult {
+                filename: String::new(),
+                path: String::new(),
+                file_size: 0,
+                created_at: Local::now().to_rfc3339(),
+                backup_dir: String::new(),
+                warning: None,
+                checksum: String::new(),
+                duration_ms: 0,
+                throughput_mbps: 0.0,
+                skipped_reason: Some(format!("Nothing to back up yet: {}", reason)),
+                nothing_to_back_up: true,
+                verified: false,
+                integrity_error: None,
+            });
+        }
+    }
+
+    let result = run_database_backup(app, compress.unwrap_or(false));
+    let payload = match &result {
+        Ok(backup) => WebhookPayload {
+            filename: Some(backup.filename.clone()),
+            size: Some(backup.file_size),
+            checksum: Some(backup.checksum.clone()),
+            message: backup.warning.clone(),
+            ..WebhookPayload::new("backup.completed", true)
+        },
+        Err(e) => WebhookPayload {
+            message: Some(e.clone()),
+            ..WebhookPayload::new("backup.failed", false)
+        },
+    };
+    fire_webhook(app, payload);
+
+    let result = result?;
+    if let Some(keep_count) = get_backup_keep_count(app) {
+        if let Err(e) = prune_backups(app.clone(), keep_count, None, None) {
+            log::warn!("{}", e);
+        }
+    }
+    *last_success = Some((Instant::now(), result.clone()));
+    Ok(result)
+}
+
+/// Why a database isn't worth backing up yet (freshly created, no tables), or None.
+/// Missing files are left for the backup itself to report.
+pub(crate) fn describe_empty_database(db_path: &Path) -> Option<String> {
+    let size = fs::metadata(db_path).ok()?.len();
+    // The smallest real database is one 512-byte page
+    if size < 512 {
+        return Some(format!("the database file is only {} bytes", size));
+    }
+    let conn = open_database(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY).ok()?;
+    let user_tables: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+            [],
+            |row| row.get(0),
+        )
+        .ok()?;
+    (user_tables == 0).then(|| "the database has no tables".to_string())
+}
+
+/// Returns a compacted copy of the database as bytes, for callers that embed
+/// it in their own sync payload. VACUUM INTO writes a temp file that is
+/// removed before returning; databases over 64 MiB are refused.
+#[tauri::command]
+pub(crate) fn backup_to_bytes(app: AppHandle) -> Result<BackupBytes, String> {
+    use sha2::Digest;
+
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let too_large = |size: u64| {
+        format!(
+            "Database is {:.1} MB, over the {} MB in-memory limit. Use backup_database for a file backup instead.",
+            size as f64 / 1_048_576.0,
+            BACKUP_TO_BYTES_MAX / 1_048_576
+        )
+    };
+
+    let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    // Live pages minus free pages approximates the VACUUM INTO output
+    let used_bytes: i64 = conn
+        .query_row(
+            "SELECT (page_count - freelist_count) * page_size FROM pragma_page_count, pragma_freelist_count, pragma_page_size",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to read database size: {}", e))?;
+    if used_bytes.max(0) as u64 > BACKUP_TO_BYTES_MAX {
+        return Err(too_large(used_bytes as u64));
+    }
+
+    let temp_path = unique_temp_path("backup_bytes", "db");
+    let data = conn
+        .execute(
+            "VACUUM INTO ?1",
+            params![temp_path.to_string_lossy().to_string()],
+        )
+        .map_err(|e| format!("Failed to create backup: {}", e))
+        .and_then(|_| fs::read(&temp_path).map_err(|e| format!("Failed to read backup: {}", e)));
+    let _ = fs::remove_file(&temp_path);
+    let data = data?;
+
+    let size = data.len() as u64;
+    if size > BACKUP_TO_BYTES_MAX {
+        return Err(too_large(size));
+    }
+    let checksum = format!("{:x}", sha2::Sha256::digest(&data));
+    Ok(BackupBytes {
+        data,
+        checksum,
+        size,
+    })
+}
+
+/// Creates a backup and only returns Ok once it has been reopened read-only,
+/// passed integrity_check and re-hashed to the recorded checksum.
+/// A backup that fails any check is deleted.
+#[tauri::command]
+pub(crate) async fn create_verified_backup(app: AppHandle) -> Result<BackupResult, String> {
+    tauri::async_runtime::spawn_blocking(move || run_verified_backup(&app))
+        .await
+        .map_err(|e| format!("Backup task failed: {}", e))?
+}
+
+pub(crate) fn run_verified_backup(app: &AppHandle) -> Result<BackupResult, String> {
+    let result = run_guarded_backup(app, Some(true), None)?;
+    let backup_path = PathBuf::from(&result.path);
+
+    let verified = verify_new_backup(&backup_path, &result.checksum).and_then(|_| {
+        let manifest = BackupManifest {
+            verified_at: Some(Local::now().to_rfc3339()),
+            ..read_backup_manifest(&backup_path)
+        };
+        write_backup_manifest(&backup_path, &manifest)
+    });
+
+    if let Err(e) = verified {
+        let _ = remove_backup_files(&backup_path);
+        let message = format!("Backup failed verification and was deleted: {}", e);
+        log_operation(
+            app,
+            OperationRecord::new("verified_backup", false, message.clone())
+                .with_filename(&result.filename),
+        );
+        return Err(message);
+    }
+
+    log_operation(
+        app,
+        OperationRecord::new("verified_backup", true, "Backup created and verified")
+            .with_filename(&result.filename),
+    );
+    Ok(result)
+}
+
+pub(crate) fn verify_new_backup(backup_path: &Path, expected_checksum: &str) -> Result<(), String> {
+    verify_backup_copy(backup_path)?;
+
+    if read_checksum_sidecar(backup_path).as_deref() != Some(expected_checksum) {
+        return Err("Checksum file is missing or does not match".to_string());
+    }
+    let actual = compute_file_sha256(backup_path)?;
+    if actual != expected_checksum {
+        return Err(format!(
+            "Checksum mismatch on re-read: expected {}, got {}",
+            expected_checksum, actual
+        ));
+    }
+
+    Ok(())
+}
+
+/// Reports disk space on the volume holding the backups directory in use today
+/// (the weekday rotation drive when one is configured and available)
+#[tauri::command]
+pub(crate) fn get_backups_volume_space(app: AppHandle) -> Result<VolumeSpace, String> {
+    let dir = resolve_backup_destination(&app)?.dir;
+    let context =
+        |e: std::io::Error| format!("Failed to read disk space for {}: {}", dir.display(), e);
+
+    let total_bytes = fs2::total_space(&dir).map_err(context)?;
+    let free_bytes = fs2::free_space(&dir).map_err(context)?;
+    let available_bytes = fs2::available_space(&dir).map_err(context)?;
+
+    Ok(VolumeSpace {
+        path: dir.to_string_lossy().to_string(),
+        total_bytes,
+        used_bytes: total_bytes.saturating_sub(free_bytes),
+        available_bytes,
+    })
+}
+
+/// Backs the database up into every destination folder through the same
+/// pipeline as backup_database: a verified snapshot per destination with its
+/// checksum and manifest. Each destination succeeds or fails on its own; all
+/// outcomes are returned. Runs on a blocking thread like backup_database.
+#[tauri::command]
+pub(crate) async fn backup_to_destinations(
+    app: AppHandle,
+    destinations: Vec<String>,
+) -> Result<Vec<DestinationBackupResult>, String> {
+    tauri::async_runtime::spawn_blocking(move || run_backup_to_destinations(&app, destinations))
+        .await
+        .map_err(|e| format!("Backup task failed: {}", e))?
+}
+
+fn run_backup_to_destinations(
+    app: &AppHandle,
+    destinations: Vec<String>,
+) -> Result<Vec<DestinationBackupResult>, String> {
+    if destinations.is_empty() {
+        return Err("No backup destinations given".to_string());
+    }
+
+    if !get_db_path(app)?.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let results: Vec<DestinationBackupResult> = destinations
+        .into_iter()
+        .map(|destination| {
+            let written = normalize_user_path(app, &destination)
+                .and_then(|dir| {
+                    fs::create_dir_all(&dir)
+                        .map_err(|e| format!("Failed to create destination folder: {}", e))?;
+                    Ok(dir)
+                })
+                .and_then(|dir| write_database_backup(app, &dir, false));
+
+            match written {
+                Ok(backup) => DestinationBackupResult {
+                    destination,
+                    success: true,
+                    path: Some(backup.path),
+                    file_size: Some(backup.file_size),
+                    error: None,
+                },
+                Err(error) => DestinationBackupResult {
+                    destination,
+                    success: false,
+                    path: None,
+                    file_size: None,
+                    error: Some(error),
+                },
+            }
+        })
+        .collect();
+
+    let succeeded = results.iter().filter(|r| r.success).count();
+    log_operation(
+        app,
+        OperationRecord::new(
+            "backup_to_destinations",
+            succeeded == results.len(),
+            format!("Wrote {} of {} destination(s)", succeeded, results.len()),
+        ),
+    );
+
+    Ok(results)
+}
+
+/// Writes a backup whose bytes depend only on the data, for CI comparisons.
+/// VACUUM INTO normalizes the page layout, the caller chooses the filename and
+/// the file's modification time is pinned to `fixed_timestamp`. No manifest is
+/// written; only the checksum sidecar, which is itself deterministic.
+#[tauri::command]
+pub(crate) fn create_backup_deterministic(
+    app: AppHandle,
+    fixed_timestamp: String,
+    fixed_name: String,
+) -> Result<BackupResult, String> {
+    let timestamp = parse_local_datetime(fixed_timestamp.trim())?;
+    let backup_filename = format!("{}.db", validate_db_name(&fixed_name)?);
+    let db_path = get_db_path(&app)?;
+    let backups_dir = get_backups_dir(&app)?;
+
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let backup_path = backups_dir.join(&backup_filename);
+    let started = Instant::now();
+    write_deterministic_backup(&db_path, &backup_path, timestamp.into())?;
+    let elapsed = started.elapsed();
+
+    let metadata =
+        fs::metadata(&backup_path).map_err(|e| format!("Failed to get backup metadata: {}", e))?;
+    let checksum = compute_file_sha256(&backup_path)?;
+    write_checksum_sidecar(&backup_path, &checksum)?;
+
+    Ok(BackupResult {
+        filename: backup_filename,
+        path: backup_path.to_string_lossy().to_string(),
+        file_size: metadata.len(),
+        created_at: timestamp.to_rfc3339(),
+        backup_dir: backups_dir.to_string_lossy().to_string(),
+        warning: None,
+        checksum,
+        duration_ms: elapsed.as_millis() as u64,
+        throughput_mbps: throughput_mbps(metadata.len(), elapsed),
+        skipped_reason: None,
+        nothing_to_back_up: false,
+        verified: false,
+        integrity_error: None,
+    })
+}
+
+/// VACUUM INTO `backup_path`, replacing an earlier run's output, then pins the
+/// file's modification time to `modified`
+pub(crate) fn write_deterministic_backup(
+    db_path: &Path,
+    backup_path: &Path,
+    modified: std::time::SystemTime,
+) -> Result<(), String> {
+    // Re-runs replace the previous output; VACUUM INTO refuses an existing file
+    if backup_path.exists() {
+        remove_backup_files(backup_path)
+            .map_err(|e| format!("Failed to replace existing backup: {}", e))?;
+    }
+
+    let conn = open_database(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    conn.execute(
+        "VACUUM INTO ?1",
+        params![backup_path.to_string_lossy().to_string()],
+    )
+    .map_err(|e| {
+        let _ = fs::remove_file(backup_path);
+        format!("Failed to write deterministic backup: {}", e)
+    })?;
+    drop(conn);
+
+    fs::File::options()
+        .write(true)
+        .open(backup_path)
+        .and_then(|file| file.set_modified(modified))
+        .map_err(|e| format!("Failed to set backup timestamp: {}", e))
+}
+
+/// Creates a copy of the database with PII columns overwritten, for sharing
+/// outside the shop. The copy is flagged redacted in its manifest.
+#[tauri::command]
+pub(crate) fn create_redacted_backup(
+    app: AppHandle,
+    config: Option<RedactionConfig>,
+) -> Result<BackupResult, String> {
+    let config = config.unwrap_or_default();
+    let db_path = get_db_path(&app)?;
+    let backups_dir = get_backups_dir(&app)?;
+
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    // Explicitly requested columns must exist; missing default ones are just skipped
+    let explicit = !config.columns.is_empty();
+    let columns: Vec<RedactionColumn> = if explicit {
+        config.columns
+    } else {
+        DEFAULT_REDACTED_COLUMNS
+            .iter()
+            .map(|(table, column)| RedactionColumn {
+                table: table.to_string(),
+                column: column.to_string(),
+            })
+            .collect()
+    };
+
+    let started = Instant::now();
+    let timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
+    let backup_filename = format!("motormods_redacted_{}.db", timestamp);
+    let backup_path = backups_dir.join(&backup_filename);
+
+    let manifest = BackupManifest {
+        redacted: true,
+        captured_at: Some(Local::now().to_rfc3339()),
+        ..Default::default()
+    };
+    // Redacted under a temporary name, so the copy only shows up as a backup
+    // once its manifest marks it as redacted
+    let partial = TempFileGuard(backups_dir.join(format!("{}.partial", backup_filename)));
+    let _ = fs::remove_file(&partial.0);
+    let redacted = snapshot_database(&db_path, &partial.0)
+        .and_then(|_| redact_database(&partial.0, &columns, explicit, config.hash_values))
+        .and_then(|_| write_backup_manifest(&backup_path, &manifest))
+        .and_then(|_| {
+            fs::rename(&partial.0, &backup_path).map_err(|e| {
+                let _ = fs::remove_file(manifest_sidecar_path(&backup_path));
+                format!("Failed to save redacted backup: {}", e)
+            })
+        });
+    if let Err(e) = redacted {
+        log_operation(
+            &app,
+            OperationRecord::new("redacted_backup", false, e.clone()),
+        );
+        return Err(e);
+    }
+
+    let metadata =
+        fs::metadata(&backup_path).map_err(|e| format!("Failed to get backup metadata: {}", e))?;
+    let checksum = compute_file_sha256(&backup_path)?;
+    if let Err(e) = write_checksum_sidecar(&backup_path, &checksum) {
+        log::warn!("{}", e);
+    }
+
+    let elapsed = started.elapsed();
+    log_operation(
+        &app,
+        OperationRecord::new(
+            "redacted_backup",
+            true,
+            format!("Redacted {} column(s)", columns.len()),
+        )
+        .with_filename(&backup_filename),
+    );
+
+    Ok(BackupResult {
+        filename: backup_filename,
+        path: backup_path.to_string_lossy().to_string(),
+        file_size: metadata.len(),
+        created_at: Local::now().to_rfc3339(),
+        backup_dir: backups_dir.to_string_lossy().to_string(),
+        warning: None,
+        checksum,
+        duration_ms: elapsed.as_millis() as u64,
+        throughput_mbps: throughput_mbps(metadata.len(), elapsed),
+        skipped_reason: None,
+        nothing_to_back_up: false,
+        verified: false,
+        integrity_error: None,
+    })
+}
+
+/// Backs up the full schema but leaves the rows of `tables` out (e.g. a huge
+/// audit_log). Rows are copied table by table into a fresh file rather than
+/// copying the file and deleting, so no delete triggers fire. The manifest
+/// lists the excluded tables and restoring the backup warns about them.
+#[tauri::command]
+pub(crate) fn create_backup_excluding(
+    app: AppHandle,
+    tables: Vec<String>,
+) -> Result<BackupResult, String> {
+    let db_path = get_db_path(&app)?;
+    let backups_dir = get_backups_dir(&app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+    if tables.is_empty() {
+        return Err("Name at least one table to exclude".to_string());
+    }
+
+    let source = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let schema = read_live_schema(&source)?;
+    let all_tables: Vec<String> = schema.tables.iter().map(|t| t.name.clone()).collect();
+    if let Some(unknown) = tables.iter().find(|t| !all_tables.contains(t)) {
+        return Err(format!("Unknown table: {}", unknown));
+    }
+    let mut objects_stmt = source
+        .prepare(
+            "SELECT type, sql FROM sqlite_master
+             WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%'
+             ORDER BY rowid",
+        )
+        .map_err(|e| format!("Failed to read schema: {}", e))?;
+    let objects: Vec<(String, String)> = objects_stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| format!("Failed to read schema: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    let (user_version, application_id) = read_schema_ids(&source)?;
+    drop(objects_stmt);
+    drop(source);
+
+    let started = Instant::now();
+    let captured_at = Local::now();
+    let backup_filename = format!(
+        "motormods_backup_{}_excluding.db",
+        captured_at.format("%Y-%m-%d_%H-%M-%S")
+    );
+    let backup_path = backups_dir.join(&backup_filename);
+    let partial_path = backups_dir.join(format!("{}.partial", backup_filename));
+    let _ = fs::remove_file(&partial_path);
+    let quote = |name: &str| format!("\"{}\"", name.replace('"', "\"\""));
+
+    let copied = (|| -> Result<(), String> {
+        let mut conn = open_database(&partial_path, OpenFlags::default())?;
+        // Tables are filled one at a time and excluded ones stay empty, so
+        // references can't be enforced while copying
+        conn.execute_batch("PRAGMA foreign_keys = OFF")
+            .map_err(|e| format!("Failed to configure backup database: {}", e))?;
+        conn.execute(
+            "ATTACH DATABASE ?1 AS src",
+            params![db_path.to_string_lossy().to_string()],
+        )
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let run = |sql: &str| {
+            tx.execute(sql, [])
+                .map_err(|e| format!("Backup failed: {}", e))
+        };
+
+        // Tables first so triggers don't fire on the copied rows
+        for (_, sql) in objects.iter().filter(|(kind, _)| kind == "table") {
+            run(sql)?;
+        }
+        for name in all_tables.iter().filter(|t| !tables.contains(t)) {
+            run(&format!(
+                "INSERT INTO main.{0} SELECT * FROM src.{0}",
+                quote(name)
+            ))?;
+        }
+        for (_, sql) in objects.iter().filter(|(kind, _)| kind != "table") {
+            run(sql)?;
+        }
+        run(&format!("PRAGMA user_version = {}", user_version))?;
+        run(&format!("PRAGMA application_id = {}", application_id))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit backup: {}", e))?;
+        conn.execute_batch("DETACH DATABASE src")
+            .map_err(|e| format!("Failed to close database: {}", e))?;
+        drop(conn);
+        fs::rename(&partial_path, &backup_path)
+            .map_err(|e| format!("Failed to finish backup: {}", e))
+    })();
+    if let Err(e) = copied {
+        let _ = fs::remove_file(&partial_path);
+        log_operation(
+            &app,
+            OperationRecord::new("excluding_backup", false, e.clone()),
+        );
+        return Err(e);
+    }
+
+    let manifest = BackupManifest {
+        captured_at: Some(captured_at.to_rfc3339()),
+        excluded_tables: tables.clone(),
+        ..Default::default()
+    };
+    write_backup_manifest(&backup_path, &manifest)?;
+
+    let metadata =
+        fs::metadata(&backup_path).map_err(|e| format!("Failed to get backup metadata: {}", e))?;
+    let checksum = compute_file_sha256(&backup_path)?;
+    if let Err(e) = write_checksum_sidecar(&backup_path, &checksum) {
+        log::warn!("{}", e);
+    }
+
+    let elapsed = started.elapsed();
+    log_operation(
+        &app,
+        OperationRecord::new(
+            "excluding_backup",
+            true,
+            format!("Backup without rows of: {}", tables.join(", ")),
+        )
+        .with_filename(&backup_filename),
+    );
+
+    Ok(BackupResult {
+        filename: backup_filename,
+        path: backup_path.to_string_lossy().to_string(),
+        file_size: metadata.len(),
+        created_at: Local::now().to_rfc3339(),
+        backup_dir: backups_dir.to_string_lossy().to_string(),
+        warning: Some(format!(
+            "This backup has no rows for: {}",
+            tables.join(", ")
+        )),
+        checksum,
+        duration_ms: elapsed.as_millis() as u64,
+        throughput_mbps: throughput_mbps(metadata.len(), elapsed),
+        skipped_reason: None,
+        nothing_to_back_up: false,
+        verified: false,
+        integrity_error: None,
+    })
+}
+
+pub(crate) fn redact_database(
+    path: &Path,
+    columns: &[RedactionColumn],
+    require_all: bool,
+    hash_values: bool,
+) -> Result<(), String> {
+    use sha2::Digest;
+
+    let mut conn = open_database(path, OpenFlags::default())?;
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    for target in columns {
+        let has_column = table_exists(&tx, &target.table)
+            && tx
+                .prepare(&format!(
+                    "PRAGMA table_info(\"{}\")",
+                    target.table.replace('"', "\"\"")
+                ))
+                .and_then(|mut stmt| {
+                    stmt.query_map([], |row| row.get::<_, String>(1))?
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .map(|names| names.iter().any(|name| name == &target.column))
+                .unwrap_or(false);
+
+        if !has_column {
+            if require_all {
+                return Err(format!(
+                    "Column not found: {}.{}",
+                    target.table, target.column
+                ));
+            }
+            continue;
+        }
+
+        let table = format!("\"{}\"", target.table.replace('"', "\"\""));
+        let column = format!("\"{}\"", target.column.replace('"', "\"\""));
+        let context = |e: rusqlite::Error| {
+            format!("Failed to redact {}.{}: {}", target.table, target.column, e)
+        };
+
+        if hash_values {
+            let rows: Vec<(i64, String)> = tx
+                .prepare(&format!(
+                    "SELECT rowid, CAST({} AS TEXT) FROM {} WHERE {} IS NOT NULL",
+                    column, table, column
+                ))
+                .and_then(|mut stmt| {
+                    stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .map_err(context)?;
+
+            let mut update = tx
+                .prepare(&format!(
+                    "UPDATE {} SET {} = ?1 WHERE rowid = ?2",
+                    table, column
+                ))
+                .map_err(context)?;
+            for (rowid, value) in rows {
+                let digest = sha2::Sha256::digest(value.as_bytes());
+                let short_hash: String = format!("{:x}", digest).chars().take(16).collect();
+                update
+                    .execute(params![format!("redacted-{}", short_hash), rowid])
+                    .map_err(context)?;
+            }
+        } else {
+            tx.execute(
+                &format!(
+                    "UPDATE {} SET {} = ?1 WHERE {} IS NOT NULL",
+                    table, column, column
+                ),
+                params![REDACTION_PLACEHOLDER],
+            )
+            .map_err(context)?;
+        }
+    }
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit redaction: {}", e))?;
+
+    // Rewrite the file so the original values don't linger in free pages
+    conn.execute_batch("VACUUM")
+        .map_err(|e| format!("Failed to compact redacted copy: {}", e))
+}
+
+/// Optional backup copy speed cap in MB/s from settings; None means unlimited
+pub(crate) fn get_backup_rate_limit(app: &AppHandle) -> Option<f64> {
+    get_setting(app, BACKUP_RATE_LIMIT_KEY)
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|mbps| mbps.is_finite() && *mbps > 0.0)
+}
+
+/// Runs a backup in page batches, sleeping between them when `rate_limit_mbps`
+/// is set so the copy stays under that speed. Emits "backup-progress" and
+/// stops early once `operation` is cancelled, or with an error once the source
+/// has been busy or locked for longer than the busy timeout.
+pub(crate) fn run_backup_stepped(
+    app: &AppHandle,
+    backup: &rusqlite::backup::Backup,
+    page_size: u64,
+    rate_limit_mbps: Option<f64>,
+    operation: &OperationGuard,
+) -> Result<(), String> {
+    use rusqlite::backup::StepResult;
+
+    let started = Instant::now();
+    let mut last_event: Option<Instant> = None;
+    // (first retry, retries) while the source stays busy or locked
+    let mut retrying: Option<(Instant, u32)> = None;
+    loop {
+        let step = backup
+            .step(BACKUP_STEP_PAGES)
+            .map_err(|e| format!("Failed to complete backup: {}", e))?;
+        if matches!(step, StepResult::Done | StepResult::More) {
+            if let Some((since, retries)) = retrying.take() {
+                record_lock_retry("backup", retries, since);
+            }
+        }
+
+        let progress = backup.progress();
+        let total_bytes = progress.pagecount.max(0) as u64 * page_size;
+        let copied_bytes = (progress.pagecount - progress.remaining).max(0) as u64 * page_size;
+        let done = step == StepResult::Done;
+        if progress.pagecount > 0 {
+            operation.set_progress(copied_bytes as f64 * 100.0 / total_bytes as f64);
+        }
+        if !done && operation.is_cancelled() {
+            return Err("Backup cancelled".to_string());
+        }
+
+        let mut throttled = false;
+        if let (Some(limit), false) = (rate_limit_mbps, done) {
+            let target = Duration::from_secs_f64(copied_bytes as f64 / (limit * 1_000_000.0));
+            let elapsed = started.elapsed();
+            if target > elapsed {
+                std::thread::sleep(target - elapsed);
+                throttled = true;
+            }
+        }
+
+        if done || last_event.is_none_or(|t| t.elapsed() >= BACKUP_PROGRESS_INTERVAL) {
+            last_event = Some(Instant::now());
+            let _ = app.emit(
+                "backup-progress",
+                BackupProgress {
+                    copied_bytes,
+                    total_bytes,
+                    throughput_mbps: throughput_mbps(copied_bytes, started.elapsed()),
+                    rate_limit_mbps,
+                    throttled,
+                },
+            );
+        }
+
+        match step {
+            StepResult::Done => return Ok(()),
+            StepResult::More => {}
+            // Another connection is writing; give it a moment and retry, for
+            // as long as the busy timeout lets other commands wait for a lock
+            _ => {
+                let (since, retries) = retrying.get_or_insert((Instant::now(), 0));
+                *retries += 1;
+                let limit = Duration::from_millis(BUSY_TIMEOUT_MS.load(Ordering::SeqCst));
+                if since.elapsed() >= limit {
+                    record_lock_retry("backup", *retries, *since);
+                    return Err(format!(
+                        "Backup gave up: the database stayed locked for {} ms",
+                        limit.as_millis()
+                    ));
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+    }
+}
+
+/// Lists all backup files (.db and .db.gz) in the backups directory and the
+/// mounted rotation folders, newest first. A filename found in more than one
+/// folder is listed once, from the folder resolve_any_backup_file would pick.
+/// file_size is the size on disk, compressed for .db.gz. `sort` is "mtime"
+/// (default, file modification time) or "data_time", which orders by when the
+/// data was captured per the manifest and falls back to the mtime.
+#[tauri::command]
+pub(crate) fn list_backups(
+    app: AppHandle,
+    sort: Option<String>,
+) -> Result<Vec<BackupFileInfo>, String> {
+    let by_data_time = match sort.as_deref().map(str::trim) {
+        None | Some("") | Some("mtime") => false,
+        Some("data_time") => true,
+        Some(other) => return Err(format!("Unknown sort: {}. Use mtime or data_time", other)),
+    };
+    // Never offer the live database as a backup, however the folders are set up
+    let live_db = get_db_path(&app)
+        .ok()
+        .and_then(|p| fs::canonicalize(p).ok());
+
+    let mut backups: Vec<BackupFileInfo> = Vec::new();
+    let mut seen_names = std::collections::HashSet::new();
+
+    for backups_dir in backup_search_dirs(&app)? {
+        let Ok(entries) = fs::read_dir(&backups_dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if live_db.is_some() && fs::canonicalize(&path).ok() == live_db {
+                continue;
+            }
+            if is_listed_backup(&path) && seen_names.insert(entry.file_name()) {
+                if let Ok(metadata) = fs::metadata(&path) {
+                    let modified: Option<chrono::DateTime<Local>> =
+                        metadata.modified().ok().map(Into::into);
+                    let manifest = read_backup_manifest(&path);
+
+                    backups.push(BackupFileInfo {
+                        filename: path
+                            .file_name()
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_default(),
+                        path: path.to_string_lossy().to_string(),
+                        file_size: metadata.len(),
+                        modified_at: modified
+                            .map(|t| t.to_rfc3339())
+                            .unwrap_or_else(|| "Unknown".to_string()),
+                        modified_epoch_ms: modified.map(|t| t.timestamp_millis()),
+                        protected: manifest.protected,
+                        redacted: manifest.redacted,
+                        captured_at: manifest.captured_at,
+                    });
+                }
+            }
+        }
+    }
+
+    // Newest first
+    if by_data_time {
+        backups.sort_by(|a, b| {
+            compare_epochs(data_epoch_ms(a), data_epoch_ms(b), true)
+                .then_with(|| a.filename.cmp(&b.filename))
+        });
+    } else {
+        backups.sort_by(|a, b| compare_backup_times(a, b, true));
+    }
+
+    Ok(backups)
+}
+
+/// Safety backups, newest first, each with the operation it was taken before
+#[tauri::command]
+pub(crate) fn list_safety_backups(app: AppHandle) -> Result<Vec<SafetyBackupInfo>, String> {
+    Ok(list_backups(app, None)?
+        .into_iter()
+        .filter(|b| is_safety_backup(&b.filename))
+        .map(|info| {
+            let manifest = read_backup_manifest(Path::new(&info.path));
+            let description = describe_safety_origin(
+                &info.filename,
+                manifest.safety_for.as_deref(),
+                manifest.safety_target.as_deref(),
+            );
+            SafetyBackupInfo {
+                info,
+                operation: manifest.safety_for,
+                target: manifest.safety_target,
+                description,
+            }
+        })
+        .collect())
+}
+
+// Tables compared by list_backups_with_delta
+pub(crate) const DELTA_TABLES: &[&str] = &[
+    "products",
+    "invoices",
+    "invoice_items",
+    "stock_adjustments",
+    "sales_returns",
+    "return_items",
+];
+
+/// Exact row counts for DELTA_TABLES plus the latest created_at/updated_at among them
+pub(crate) fn read_delta_snapshot(conn: &Connection) -> Result<DeltaSnapshot, String> {
+    let mut counts = Vec::new();
+    let mut last_change_at: Option<String> = None;
+    for table in DELTA_TABLES {
+        if !table_exists(conn, table) {
+            continue;
+        }
+        let count: i64 = conn
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| format!("Failed to count rows in {}: {}", table, e))?;
+        counts.push((table.to_string(), count));
+
+        let mut stmt = conn
+            .prepare(&format!("PRAGMA table_info({})", table))
+            .map_err(|e| format!("Failed to get table info: {}", e))?;
+        let columns: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(1))
+            .map_err(|e| format!("Failed to query columns: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        for column in ["created_at", "updated_at"] {
+            if !columns.iter().any(|c| c == column) {
+                continue;
+            }
+            let latest: Option<String> = conn
+                .query_row(
+                    &format!("SELECT MAX({}) FROM {}", column, table),
+                    [],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to read {}.{}: {}", table, column, e))?;
+            // SQLite timestamps are ISO-8601 text, so they order as strings
+            if latest > last_change_at {
+                last_change_at = latest;
+            }
+        }
+    }
+    Ok(DeltaSnapshot {
+        counts,
+        last_change_at,
+    })
+}
+
+/// Like list_backups, but compares each backup with the live database: row-count
+/// deltas for the key tables and whether its data is newer or older, judged by the
+/// latest created_at/updated_at. Backup figures are cached until the file changes.
+#[tauri::command]
+pub(crate) fn list_backups_with_delta(app: AppHandle) -> Result<Vec<BackupWithDelta>, String> {
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+    let live = {
+        let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        read_delta_snapshot(&conn)?
+    };
+    let live_counts: HashMap<&str, i64> = live
+        .counts
+        .iter()
+        .map(|(table, count)| (table.as_str(), *count))
+        .collect();
+
+    let backups = list_backups(app.clone(), None)?;
+    let cache = app.state::<BackupDeltaCache>();
+    let mut entries = cache.entries.lock().unwrap();
+    // Forget backups that have been deleted since the last call
+    entries.retain(|path, _| backups.iter().any(|b| &b.path == path));
+
+    let mut results = Vec::with_capacity(backups.len());
+    for info in backups {
+        let cached = entries
+            .get(&info.path)
+            .filter(|c| {
+                c.file_size == info.file_size && c.modified_epoch_ms == info.modified_epoch_ms
+            })
+            .map(|c| c.snapshot.clone());
+        let snapshot = match cached {
+            Some(snapshot) => Ok(snapshot),
+            None => open_database(Path::new(&info.path), OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .and_then(|conn| read_delta_snapshot(&conn))
+                .inspect(|snapshot| {
+                    entries.insert(
+                        info.path.clone(),
+                        CachedDelta {
+                            file_size: info.file_size,
+                            modified_epoch_ms: info.modified_epoch_ms,
+                            snapshot: snapshot.clone(),
+                        },
+                    );
+                }),
+        };
+
+        let snapshot = match snapshot {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                results.push(BackupWithDelta {
+                    info,
+                    tables: Vec::new(),
+                    last_change_at: None,
+                    relation: "unknown".to_string(),
+                    error: Some(e),
+                });
+                continue;
+            }
+        };
+
+        let tables = snapshot
+            .counts
+            .iter()
+            .map(|(table, backup_count)| {
+                let live_count = live_counts.get(table.as_str()).copied().unwrap_or(0);
+                TableDelta {
+                    table: table.clone(),
+                    backup_count: *backup_count,
+                    live_count,
+                    delta: backup_count - live_count,
+                }
+            })
+            .collect();
+        let relation = match (&snapshot.last_change_at, &live.last_change_at) {
+            (Some(backup), Some(current)) if backup > current => "newer",
+            (Some(backup), Some(current)) if backup < current => "older",
+            (Some(_), Some(_)) | (None, None) => "same",
+            _ => "unknown",
+        };
+        results.push(BackupWithDelta {
+            info,
+            tables,
+            last_change_at: snapshot.last_change_at,
+            relation: relation.to_string(),
+            error: None,
+        });
+    }
+
+    Ok(results)
+}
+
+/// In-progress writes and sync-client scratch files, which never change the listing
+pub(crate) fn is_transient_backup_file(name: &str) -> bool {
+    [".partial", ".tmp", ".repair", "-journal", "-wal", "-shm"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+        || ["~", ".~", ".dropbox", ".sync"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+}
+
+/// Name, size and mtime of every non-transient file in the backups folder
+pub(crate) fn backups_dir_fingerprint(
+    app: &AppHandle,
+) -> Vec<(String, u64, Option<std::time::SystemTime>)> {
+    let Ok(dirs) = backup_search_dirs(app) else {
+        return Vec::new();
+    };
+    let mut fingerprint: Vec<_> = dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if is_transient_backup_file(&name) {
+                return None;
+            }
+            let metadata = entry.metadata().ok()?;
+            Some((name, metadata.len(), metadata.modified().ok()))
+        })
+        .collect();
+    fingerprint.sort();
+    fingerprint
+}
+
+/// Polls the backups folder and emits "backups-changed" with a fresh listing once
+/// changes have stopped for the debounce window, so a burst becomes one event
+pub(crate) fn start_backups_watch(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut last = backups_dir_fingerprint(&app);
+        let mut changed_at: Option<Instant> = None;
+        loop {
+            std::thread::sleep(BACKUPS_WATCH_POLL_INTERVAL);
+            let current = backups_dir_fingerprint(&app);
+            if current != last {
+                last = current;
+                changed_at = Some(Instant::now());
+                continue;
+            }
+            let debounce = Duration::from_millis(
+                get_setting(&app, BACKUPS_WATCH_DEBOUNCE_KEY)
+                    .and_then(|v| v.trim().parse().ok())
+                    .unwrap_or(DEFAULT_BACKUPS_WATCH_DEBOUNCE_MS),
+            );
+            if changed_at.is_some_and(|at| at.elapsed() >= debounce) {
+                changed_at = None;
+                match list_backups(app.clone(), None) {
+                    Ok(backups) => {
+                        let _ = app.emit("backups-changed", backups);
+                    }
+                    Err(e) => log::warn!("Failed to list backups: {}", e),
+                }
+            }
+        }
+    });
+}
+
+/// Sets the quiet period before "backups-changed" fires (0 emits after one poll)
+#[tauri::command]
+pub(crate) fn set_backups_watch_debounce(app: AppHandle, debounce_ms: u64) -> Result<(), String> {
+    if debounce_ms > 60_000 {
+        return Err("Debounce must be at most 60000 ms".to_string());
+    }
+    set_setting(&app, BACKUPS_WATCH_DEBOUNCE_KEY, &debounce_ms.to_string())
+}
+
+/// Orders backups by modification time; unknown times always sort last,
+/// with the filename as a tie-breaker so the order is stable
+pub(crate) fn compare_backup_times(
+    a: &BackupFileInfo,
+    b: &BackupFileInfo,
+    newest_first: bool,
+) -> std::cmp::Ordering {
+    compare_epochs(a.modified_epoch_ms, b.modified_epoch_ms, newest_first)
+        .then_with(|| a.filename.cmp(&b.filename))
+}
+
+pub(crate) fn compare_epochs(
+    a: Option<i64>,
+    b: Option<i64>,
+    newest_first: bool,
+) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    match (a, b) {
+        (Some(x), Some(y)) if newest_first => y.cmp(&x),
+        (Some(x), Some(y)) => x.cmp(&y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// When a backup's data was captured, falling back to its mtime
+pub(crate) fn data_epoch_ms(backup: &BackupFileInfo) -> Option<i64> {
+    backup
+        .captured_at
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.timestamp_millis())
+        .or(backup.modified_epoch_ms)
+}
+
+pub(crate) fn format_megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / 1_000_000.0)
+}
+
+/// Summarises backup activity between two dates (YYYY-MM-DD or RFC 3339):
+/// runs and failures from the operation history, and the backups on disk from
+/// that period with their verification state. Includes a Markdown rendering.
+#[tauri::command]
+pub(crate) fn generate_backup_report(
+    app: AppHandle,
+    from: String,
+    to: String,
+) -> Result<BackupReport, String> {
+    let records = read_operation_history(&app, None, Some(&from), Some(&to), None)?;
+    let from_bound = parse_query_bound(&from, false)?;
+    let to_bound = parse_query_bound(&to, true)?;
+
+    let mut operations: Vec<OperationCount> = Vec::new();
+    for record in &records {
+        match operations
+            .iter_mut()
+            .find(|c| c.operation == record.operation)
+        {
+            Some(count) => {
+                count.total += 1;
+                count.failed += usize::from(!record.success);
+            }
+            None => operations.push(OperationCount {
+                operation: record.operation.clone(),
+                total: 1,
+                failed: usize::from(!record.success),
+            }),
+        }
+    }
+    operations.sort_by(|a, b| a.operation.cmp(&b.operation));
+    let backup_runs = records
+        .iter()
+        .filter(|r| BACKUP_OPERATION_TYPES.contains(&r.operation.as_str()))
+        .count();
+    let failed_runs = records
+        .iter()
+        .filter(|r| BACKUP_OPERATION_TYPES.contains(&r.operation.as_str()) && !r.success)
+        .count();
+    let failures: Vec<OperationRecord> = records.into_iter().filter(|r| !r.success).collect();
+
+    let mut backups: Vec<BackupFileInfo> = list_backups(app.clone(), None)?
+        .into_iter()
+        .filter(|b| {
+            chrono::DateTime::parse_from_rfc3339(&b.modified_at)
+                .is_ok_and(|modified| modified >= from_bound && modified <= to_bound)
+        })
+        .collect();
+    backups.sort_by(|a, b| compare_backup_times(a, b, false));
+
+    let total_size = backups.iter().map(|b| b.file_size).sum();
+    let mut verified_count = 0;
+    let mut without_checksum = Vec::new();
+    for backup in &backups {
+        let path = Path::new(&backup.path);
+        if read_backup_manifest(path).verified_at.is_some() {
+            verified_count += 1;
+        }
+        if read_checksum_sidecar(path).is_none() {
+            without_checksum.push(backup.filename.clone());
+        }
+    }
+    let backup_count = backups.len();
+    let oldest = backups.first().cloned();
+    let newest = backups.last().cloned();
+
+    let mut report = BackupReport {
+        from,
+        to,
+        generated_at: Local::now().to_rfc3339(),
+        backup_runs,
+        failed_runs,
+        operations,
+        failures,
+        backup_count,
+        total_size,
+        oldest,
+        newest,
+        verified_count,
+        without_checksum,
+        markdown: String::new(),
+    };
+    report.markdown = render_backup_report(&report);
+    Ok(report)
+}
+
+pub(crate) fn render_backup_report(report: &BackupReport) -> String {
+    let mut out = format!("# Backup report: {} to {}\n\n", report.from, report.to);
+    out.push_str("## Summary\n\n");
+    out.push_str(&format!(
+        "- Backup runs: {} ({} failed)\n",
+        report.backup_runs, report.failed_runs
+    ));
+    out.push_str(&format!(
+        "- Backups on disk from this period: {} ({})\n",
+        report.backup_count,
+        format_megabytes(report.total_size)
+    ));
+    if let Some(oldest) = &report.oldest {
+        out.push_str(&format!(
+            "- Oldest: {} ({})\n",
+            oldest.filename, oldest.modified_at
+        ));
+    }
+    if let Some(newest) = &report.newest {
+        out.push_str(&format!(
+            "- Newest: {} ({})\n",
+            newest.filename, newest.modified_at
+        ));
+    }
+    out.push_str(&format!(
+        "- Verified backups: {} of {}\n",
+        report.verified_count, report.backup_count
+    ));
+    if !report.without_checksum.is_empty() {
+        out.push_str(&format!(
+            "- Without checksum: {}\n",
+            report.without_checksum.join(", ")
+        ));
+    }
+
+    if !report.operations.is_empty() {
+        out.push_str("\n## Operations\n\n| Operation | Total | Failed |\n|---|---|---|\n");
+        for count in &report.operations {
+            out.push_str(&format!(
+                "| {} | {} | {} |\n",
+                count.operation, count.total, count.failed
+            ));
+        }
+    }
+
+    out.push_str("\n## Failures\n\n");
+    if report.failures.is_empty() {
+        out.push_str("None.\n");
+    }
+    for failure in &report.failures {
+        out.push_str(&format!(
+            "- {} {}: {}\n",
+            failure.timestamp, failure.operation, failure.details
+        ));
+    }
+
+    out.push_str(&format!("\n_Generated {}_\n", report.generated_at));
+    out
+}
+
+/// Lists local backups matching a query, sorted as requested
+#[tauri::command]
+pub(crate) fn list_backups_filtered(
+    app: AppHandle,
+    query: BackupQuery,
+) -> Result<Vec<BackupFileInfo>, String> {
+    let from = query
+        .from
+        .as_deref()
+        .map(|v| parse_query_bound(v, false))
+        .transpose()?;
+    let to = query
+        .to
+        .as_deref()
+        .map(|v| parse_query_bound(v, true))
+        .transpose()?;
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err("The 'from' date is after the 'to' date".to_string());
+        }
+    }
+
+    let text = query.text.as_deref().map(|t| t.trim().to_lowercase());
+    let include_safety = query.include_safety.unwrap_or(true);
+
+    let mut backups: Vec<BackupFileInfo> = list_backups(app, None)?
+        .into_iter()
+        .filter(|b| include_safety || !is_safety_backup(&b.filename))
+        .filter(|b| {
+            text.as_deref()
+                .is_none_or(|t| b.filename.to_lowercase().contains(t))
+        })
+        .filter(|b| query.min_size.is_none_or(|min| b.file_size >= min))
+        .filter(|b| query.max_size.is_none_or(|max| b.file_size <= max))
+        .filter(|b| {
+            if from.is_none() && to.is_none() {
+                return true;
+            }
+            // Entries without a readable modified time can't satisfy a date range
+            chrono::DateTime::parse_from_rfc3339(&b.modified_at).is_ok_and(|modified| {
+                from.is_none_or(|f| modified >= f) && to.is_none_or(|t| modified <= t)
+            })
+        })
+        .collect();
+
+    let descending = query.descending.unwrap_or(true);
+    match query.sort_by.as_deref().unwrap_or("modified") {
+        // Handles direction itself so unknown times stay at the end either way
+        "modified" => backups.sort_by(|a, b| compare_backup_times(a, b, descending)),
+        "size" => {
+            backups.sort_by_key(|b| b.file_size);
+            if descending {
+                backups.reverse();
+            }
+        }
+        "filename" => {
+            backups.sort_by(|a, b| a.filename.cmp(&b.filename));
+            if descending {
+                backups.reverse();
+            }
+        }
+        other => {
+            return Err(format!(
+                "Invalid sort field: {}. Use modified, size or filename",
+                other
+            ))
+        }
+    }
+
+    Ok(backups)
+}
+
+/// Lists the local backups (the backups folder and the rotation folders), tagged
+/// with their source. Backups behind a URL can't be listed; restore one with
+/// restore_from_any and source "url".
+#[tauri::command]
+pub(crate) fn list_all_backups(app: AppHandle) -> Result<Vec<SourcedBackupInfo>, String> {
+    let backups = list_backups(app, None)?
+        .into_iter()
+        .map(|info| SourcedBackupInfo {
+            source: LOCAL_BACKUP_SOURCE.to_string(),
+            info,
+        })
+        .collect();
+
+    Ok(backups)
+}
+
+/// Reports when the last regular (non-safety) backup was taken and whether it is stale.
+/// Backups in the weekday rotation folders count; unreadable ones are listed.
+#[tauri::command]
+pub(crate) fn get_backup_freshness(app: AppHandle) -> Result<BackupFreshness, String> {
+    let stale_after_hours = get_setting(&app, BACKUP_STALE_HOURS_KEY)
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|hours| *hours > 0)
+        .unwrap_or(DEFAULT_BACKUP_STALE_HOURS);
+
+    let unavailable_folders = unavailable_rotation_dirs(&app);
+    // list_backups covers the rotation folders too and is sorted newest first
+    let latest = list_backups(app, None)?.into_iter().find_map(|backup| {
+        if is_safety_backup(&backup.filename) {
+            return None;
+        }
+        chrono::DateTime::parse_from_rfc3339(&backup.modified_at)
+            .ok()
+            .map(|modified| (backup, modified))
+    });
+
+    let Some((backup, modified)) = latest else {
+        return Ok(BackupFreshness {
+            last_backup_filename: None,
+            last_backup_at: None,
+            age_seconds: None,
+            is_stale: true,
+            stale_after_hours,
+            unavailable_folders,
+        });
+    };
+
+    let age_seconds = (Local::now().fixed_offset() - modified).num_seconds().max(0);
+
+    Ok(BackupFreshness {
+        last_backup_filename: Some(backup.filename),
+        last_backup_at: Some(backup.modified_at),
+        age_seconds: Some(age_seconds),
+        is_stale: age_seconds as u64 > stale_after_hours * 3600,
+        stale_after_hours,
+        unavailable_folders,
+    })
+}
+
+/// Deletes a specific backup file
+#[tauri::command]
+pub(crate) fn delete_backup(app: AppHandle, backup_filename: String) -> Result<String, String> {
+    // Only .db and .db.gz files directly inside the backups folder can be deleted
+    let backup_path = resolve_any_backup_file(&app, &backup_filename)?;
+
+    remove_backup_files(&backup_path).map_err(|e| format!("Failed to delete backup: {}", e))?;
+
+    Ok(format!("Backup deleted: {}", backup_filename))
+}
+
+/// Deletes several backups, reporting each file separately so one failure
+/// doesn't hide the rest. Protected and safety backups need `force`.
+#[tauri::command]
+pub(crate) fn delete_backups(
+    app: AppHandle,
+    filenames: Vec<String>,
+    force: Option<bool>,
+) -> Result<BatchDeleteResult, String> {
+    let force = force.unwrap_or(false);
+    let mut results = Vec::with_capacity(filenames.len());
+    let mut bytes_freed = 0u64;
+
+    for filename in filenames {
+        let outcome = resolve_any_backup_file(&app, &filename).and_then(|path| {
+            if !force && is_safety_backup(&filename) {
+                return Err("Safety backup; pass force to delete".to_string());
+            }
+            if !force && read_backup_manifest(&path).protected {
+                return Err("Backup is protected; pass force to delete".to_string());
+            }
+            let size = backup_files_size(&path);
+            remove_backup_files(&path).map_err(|e| format!("Failed to delete backup: {}", e))?;
+            Ok(size)
+        });
+
+        results.push(match outcome {
+            Ok(size) => {
+                bytes_freed += size;
+                BackupDeleteOutcome {
+                    filename,
+                    deleted: true,
+                    bytes_freed: size,
+                    error: None,
+                }
+            }
+            Err(e) => BackupDeleteOutcome {
+                filename,
+                deleted: false,
+                bytes_freed: 0,
+                error: Some(e),
+            },
+        });
+    }
+
+    let deleted = results.iter().filter(|r| r.deleted).count();
+    log_operation(
+        &app,
+        OperationRecord::new(
+            "delete_backups",
+            deleted == results.len(),
+            format!("Deleted {} of {} backups", deleted, results.len()),
+        ),
+    );
+
+    Ok(BatchDeleteResult {
+        results,
+        bytes_freed,
+    })
+}
+
+/// Removes .sha256 / .json sidecars whose .db or .db.gz backup no longer
+/// exists, in the backups folder and every available rotation folder,
+/// returning the names of the files removed
+#[tauri::command]
+pub(crate) fn cleanup_orphan_sidecars(app: AppHandle) -> Result<Vec<String>, String> {
+    let mut removed = Vec::new();
+    for backups_dir in backup_search_dirs(&app)? {
+        let entries = fs::read_dir(&backups_dir)
+            .map_err(|e| format!("Failed to read {}: {}", backups_dir.display(), e))?;
+
+        for entry in entries.flatten() {
+            let filename = entry.file_name().to_string_lossy().to_string();
+            let backup_name = [CHECKSUM_SIDECAR_EXT, MANIFEST_SIDECAR_EXT]
+                .iter()
+                .find_map(|ext| filename.strip_suffix(&format!(".{}", ext)))
+                .filter(|name| is_listed_backup(Path::new(name)));
+            let Some(backup_name) = backup_name else {
+                continue;
+            };
+            if backups_dir.join(backup_name).exists() || !entry.path().is_file() {
+                continue;
+            }
+
+            match fs::remove_file(entry.path()) {
+                Ok(()) => removed.push(filename),
+                Err(e) => log::warn!("Failed to remove orphan sidecar {}: {}", filename, e),
+            }
+        }
+    }
+    removed.sort();
+
+    if !removed.is_empty() {
+        log_operation(
+            &app,
+            OperationRecord::new(
+                "cleanup_orphan_sidecars",
+                true,
+                format!("Removed {} orphan sidecar file(s)", removed.len()),
+            ),
+        );
+    }
+
+    Ok(removed)
+}
+
+/// True when a backup opens cleanly, passes integrity_check and (if it has a
+/// checksum sidecar) still matches it
+pub(crate) fn backup_passes_validation(backup: &BackupFileInfo) -> bool {
+    let path = Path::new(&backup.path);
+    if check_backup_restorable(path, &backup.filename).is_err() {
+        return false;
+    }
+    match read_checksum_sidecar(path) {
+        Some(expected) => compute_file_sha256(path).is_ok_and(|actual| actual == expected),
+        None => true,
+    }
+}
+
+/// Deletes backups older than the retention window (setting backup_retention_days
+/// unless given). Protected and safety backups are never deleted. Unless `force`
+/// is set, the newest backup that passes validation is kept even when it is past
+/// the window, so retention can't leave only corrupt backups behind. Rotation
+/// folders are included; one that can't be read is reported and left alone.
+#[tauri::command]
+pub(crate) fn apply_retention_policy(
+    app: AppHandle,
+    retention_days: Option<u32>,
+    force: Option<bool>,
+) -> Result<RetentionResult, String> {
+    let retention_days = retention_days
+        .or_else(|| {
+            get_setting(&app, BACKUP_RETENTION_DAYS_KEY).and_then(|v| v.trim().parse().ok())
+        })
+        .unwrap_or(DEFAULT_BACKUP_RETENTION_DAYS);
+    let cutoff_ms =
+        (Local::now() - chrono::Duration::days(retention_days as i64)).timestamp_millis();
+
+    let unavailable_folders = unavailable_rotation_dirs(&app);
+    let RetentionPlan {
+        expired,
+        preserved_last_good,
+        warning,
+    } = plan_retention(
+        list_backups(app.clone(), None)?,
+        cutoff_ms,
+        retention_days,
+        force.unwrap_or(false),
+    );
+
+    let mut deleted = Vec::new();
+    for backup in expired {
+        match remove_backup_files(Path::new(&backup.path)) {
+            Ok(()) => deleted.push(backup.filename),
+            Err(e) => log::warn!("Failed to delete backup {}: {}", backup.filename, e),
+        }
+    }
+
+    let result = RetentionResult {
+        retention_days,
+        deleted,
+        preserved_last_good,
+        warning,
+        unavailable_folders,
+    };
+    if let Some(message) = &result.warning {
+        log::warn!("{}", message);
+        let _ = app.emit("retention-warning", result.clone());
+    }
+    if !result.deleted.is_empty() || result.warning.is_some() {
+        log_operation(
+            &app,
+            OperationRecord::new(
+                "retention",
+                true,
+                format!(
+                    "Deleted {} backup(s) older than {} days{}",
+                    result.deleted.len(),
+                    retention_days,
+                    result
+                        .preserved_last_good
+                        .as_deref()
+                        .map(|f| format!("; kept last good backup {}", f))
+                        .unwrap_or_default()
+                ),
+            ),
+        );
+    }
+
+    Ok(result)
+}
+
+/// What apply_retention_policy deletes, and the last good backup it keeps
+pub(crate) struct RetentionPlan {
+    pub(crate) expired: Vec<BackupFileInfo>,
+    pub(crate) preserved_last_good: Option<String>,
+    pub(crate) warning: Option<String>,
+}
+
+/// Picks the unprotected, non-safety backups modified before `cutoff_ms` from
+/// `backups` (newest first). Unless `force` is set, the newest of them that
+/// passes validation is kept when no backup inside the window does.
+pub(crate) fn plan_retention(
+    backups: Vec<BackupFileInfo>,
+    cutoff_ms: i64,
+    retention_days: u32,
+    force: bool,
+) -> RetentionPlan {
+    let (mut expired, kept): (Vec<BackupFileInfo>, Vec<BackupFileInfo>) = backups
+        .into_iter()
+        .filter(|b| !b.protected && !is_safety_backup(&b.filename))
+        .partition(|b| b.modified_epoch_ms.is_some_and(|ms| ms < cutoff_ms));
+
+    let mut preserved_last_good = None;
+    let mut warning = None;
+    let has_good_survivor = kept
+        .iter()
+        .filter(|b| !b.redacted)
+        .any(backup_passes_validation);
+    if !has_good_survivor && !force && !expired.is_empty() {
+        // Newest first, so this is the most recent good backup
+        let last_good = expired
+            .iter()
+            .position(|b| !b.redacted && backup_passes_validation(b));
+        warning = Some(match last_good {
+            Some(index) => {
+                let backup = expired.remove(index);
+                let message = format!(
+                    "No backup inside the {}-day retention window passes validation; kept {} as the last good backup",
+                    retention_days, backup.filename
+                );
+                preserved_last_good = Some(backup.filename);
+                message
+            }
+            None => {
+                "No backup passes validation. Check the backups before relying on them.".to_string()
+            }
+        });
+    }
+
+    RetentionPlan {
+        expired,
+        preserved_last_good,
+        warning,
+    }
+}
+
+/// Backup keep count from settings; None when not configured
+pub(crate) fn get_backup_keep_count(app: &AppHandle) -> Option<usize> {
+    get_setting(app, BACKUP_KEEP_COUNT_KEY)
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|count| *count > 0)
+}
+
+/// Deletes the oldest backups beyond the newest `keep_count`, and with
+/// `keep_days` any older than that too, returning the deleted filenames.
+/// Safety backups only count against their own limit (`safety_keep_count`,
+/// else setting safety_backup_keep_count, else 5); age alone never removes one.
+/// Protected backups and the newest normal backup are never deleted.
+#[tauri::command]
+pub(crate) fn prune_backups(
+    app: AppHandle,
+    keep_count: usize,
+    keep_days: Option<u32>,
+    safety_keep_count: Option<usize>,
+) -> Result<Vec<String>, String> {
+    if keep_count == 0 {
+        return Err("keep_count must be at least 1".to_string());
+    }
+    let safety_keep_count = safety_keep_count
+        .or_else(|| {
+            get_setting(&app, SAFETY_BACKUP_KEEP_COUNT_KEY).and_then(|v| v.trim().parse().ok())
+        })
+        .unwrap_or(DEFAULT_SAFETY_BACKUP_KEEP_COUNT)
+        .max(1);
+    let cutoff_ms = keep_days
+        .map(|days| (Local::now() - chrono::Duration::days(days as i64)).timestamp_millis());
+
+    let prunable = select_prunable_backups(
+        list_backups(app.clone(), None)?,
+        keep_count,
+        cutoff_ms,
+        safety_keep_count,
+    );
+
+    let mut deleted = Vec::new();
+    for backup in prunable {
+        match remove_backup_files(Path::new(&backup.path)) {
+            Ok(()) => deleted.push(backup.filename),
+            Err(e) => log::warn!("Failed to delete backup {}: {}", backup.filename, e),
+        }
+    }
+
+    if !deleted.is_empty() {
+        log_operation(
+            &app,
+            OperationRecord::new(
+                "prune_backups",
+                true,
+                format!(
+                    "Deleted {} backup(s), keeping {}{} and {} safety backup(s)",
+                    deleted.len(),
+                    keep_count,
+                    keep_days
+                        .map(|days| format!(" from the last {} days", days))
+                        .unwrap_or_default(),
+                    safety_keep_count
+                ),
+            ),
+        );
+    }
+
+    Ok(deleted)
+}
+
+/// The backups prune_backups deletes from `backups` (newest first): normal ones
+/// past the newest `keep_count` or modified before `cutoff_ms`, and safety ones
+/// past the newest `safety_keep_count`. Protected backups and the newest normal
+/// backup are always kept.
+pub(crate) fn select_prunable_backups(
+    backups: Vec<BackupFileInfo>,
+    keep_count: usize,
+    cutoff_ms: Option<i64>,
+    safety_keep_count: usize,
+) -> Vec<BackupFileInfo> {
+    // Newest first, so the index is the backup's rank by age
+    let (safety, normal): (Vec<BackupFileInfo>, Vec<BackupFileInfo>) = backups
+        .into_iter()
+        .filter(|b| !b.protected)
+        .partition(|b| is_safety_backup(&b.filename));
+    let expired_normal = normal.into_iter().enumerate().filter(|(index, b)| {
+        let too_old =
+            cutoff_ms.is_some_and(|cutoff| b.modified_epoch_ms.is_some_and(|ms| ms < cutoff));
+        *index >= keep_count || (*index > 0 && too_old)
+    });
+    let expired_safety = safety.into_iter().skip(safety_keep_count);
+    expired_normal
+        .map(|(_, b)| b)
+        .chain(expired_safety)
+        .collect()
+}
+
+/// Backups directory size cap from settings; None when not configured
+pub(crate) fn get_backup_size_cap(app: &AppHandle) -> Option<u64> {
+    get_setting(app, BACKUP_MAX_DIR_BYTES_KEY)
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|bytes| *bytes > 0)
+}
+
+/// Deletes the oldest deletable backups until everything in `backups_dir`
+/// fits in `max_bytes`. Protected and safety backups, and `keep`, are never removed.
+pub(crate) fn enforce_backup_size_cap(
+    backups_dir: &Path,
+    max_bytes: u64,
+    keep: Option<&str>,
+) -> Result<BackupSizeEnforcement, String> {
+    let entries =
+        fs::read_dir(backups_dir).map_err(|e| format!("Failed to read backups folder: {}", e))?;
+
+    let mut total_bytes = 0u64;
+    let mut candidates: Vec<(std::time::SystemTime, PathBuf, String)> = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        total_bytes += metadata.len();
+
+        let filename = entry.file_name().to_string_lossy().to_string();
+        let deletable = is_listed_backup(&path)
+            && !is_safety_backup(&filename)
+            && keep != Some(filename.as_str())
+            && !read_backup_manifest(&path).protected;
+        if deletable {
+            let modified = metadata.modified().unwrap_or(std::time::UNIX_EPOCH);
+            candidates.push((modified, path, filename));
+        }
+    }
+    candidates.sort_by_key(|(modified, _, _)| *modified);
+
+    let mut removed = Vec::new();
+    for (_, path, filename) in candidates {
+        if total_bytes <= max_bytes {
+            break;
+        }
+        let size = backup_files_size(&path);
+        remove_backup_files(&path).map_err(|e| format!("Failed to delete {}: {}", filename, e))?;
+        total_bytes = total_bytes.saturating_sub(size);
+        removed.push(filename);
+    }
+
+    let under_cap = total_bytes <= max_bytes;
+    let message = if under_cap {
+        format!(
+            "Removed {} backup(s); backups now use {} of {} bytes",
+            removed.len(),
+            total_bytes,
+            max_bytes
+        )
+    } else {
+        format!(
+            "Backups still use {} of {} bytes after removing {} backup(s); \
+             the rest are protected or safety backups",
+            total_bytes,
+            max_bytes,
+            removed.len()
+        )
+    };
+
+    Ok(BackupSizeEnforcement {
+        removed,
+        total_bytes,
+        max_bytes,
+        under_cap,
+        message,
+    })
+}
+
+/// Deletes the oldest non-protected, non-safety backups until the backups
+/// directory is no larger than `max_bytes`
+#[tauri::command]
+pub(crate) fn enforce_max_backup_size(
+    app: AppHandle,
+    max_bytes: u64,
+) -> Result<BackupSizeEnforcement, String> {
+    let backups_dir = get_backups_dir(&app)?;
+    let outcome = enforce_backup_size_cap(&backups_dir, max_bytes, None)?;
+    log_operation(
+        &app,
+        OperationRecord::new(
+            "enforce_max_backup_size",
+            outcome.under_cap,
+            outcome.message.clone(),
+        ),
+    );
+    Ok(outcome)
+}
+
+/// Deletes the app's leftover temp files (receipt spool files, inspection copies)
+/// and returns their names. Files still in use by this session are kept unless
+/// `include_current` is set.
+#[tauri::command]
+pub(crate) fn cleanup_temp_files(include_current: Option<bool>) -> Vec<String> {
+    remove_stale_temp_files(include_current.unwrap_or(false))
+}
+
+/// Opens a read-only temporary copy of a backup in the external SQLite tool
+/// from settings, or the OS default handler for .db files
+#[tauri::command]
+pub(crate) fn open_backup_external(
+    app: AppHandle,
+    backup_filename: String,
+) -> Result<String, String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let backup_path = resolve_backup_file(&app, &backup_filename)?;
+
+    let copy_path = unique_temp_path(
+        &format!("inspect_{}", backup_filename.trim_end_matches(".db")),
+        "db",
+    );
+
+    fs::copy(&backup_path, &copy_path)
+        .map_err(|e| format!("Failed to copy backup for inspection: {}", e))?;
+    let mut permissions = fs::metadata(&copy_path)
+        .map_err(|e| format!("Failed to read temporary copy: {}", e))?
+        .permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(&copy_path, permissions)
+        .map_err(|e| format!("Failed to make temporary copy read-only: {}", e))?;
+
+    let tool = get_setting(&app, EXTERNAL_SQLITE_TOOL_KEY).filter(|t| !t.trim().is_empty());
+    let copy = copy_path.to_string_lossy().to_string();
+    app.opener()
+        .open_path(copy.clone(), tool.as_deref())
+        .map_err(|e| match &tool {
+            Some(tool) => format!("Failed to open backup with {}: {}", tool, e),
+            None => format!(
+                "No application is registered to open .db files. Install a SQLite browser \
+                 or set {} in settings. ({})",
+                EXTERNAL_SQLITE_TOOL_KEY, e
+            ),
+        })?;
+
+    Ok(copy)
+}
+
+/// Marks a backup as protected (or not). Automatic pruning skips protected backups.
+#[tauri::command]
+pub(crate) fn set_backup_protected(
+    app: AppHandle,
+    backup_filename: String,
+    protected: bool,
+) -> Result<(), String> {
+    let backup_path = resolve_any_backup_file(&app, &backup_filename)?;
+
+    let mut manifest = read_backup_manifest(&backup_path);
+    manifest.protected = protected;
+    write_backup_manifest(&backup_path, &manifest)?;
+
+    log_operation(
+        &app,
+        OperationRecord::new(
+            "set_backup_protected",
+            true,
+            if protected {
+                "Backup protected"
+            } else {
+                "Backup unprotected"
+            },
+        )
+        .with_filename(&backup_filename),
+    );
+
+    Ok(())
+}
+
+/// Gets the backups directory path for the file picker
+#[tauri::command]
+pub(crate) fn get_backups_path(app: AppHandle) -> Result<String, String> {
+    let backups_dir = get_backups_dir(&app)?;
+    Ok(backups_dir.to_string_lossy().to_string())
+}
+
+/// Sets the folder backups are written to and listed from. None or an empty
+/// path goes back to the default <app dir>/backups. Returns the folder in use.
+#[tauri::command]
+pub(crate) fn set_backups_directory(
+    app: AppHandle,
+    path: Option<String>,
+) -> Result<String, String> {
+    let mut path = path.map(|p| p.trim().to_string()).unwrap_or_default();
+    if !path.is_empty() {
+        let dir = normalize_user_path(&app, &path)?;
+        path = dir.to_string_lossy().to_string();
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create backups folder {}: {}", path, e))?;
+        check_backups_dir_location(&app, &dir)?;
+    }
+
+    set_setting(&app, BACKUPS_DIR_KEY, &path)?;
+    get_backups_path(app)
+}
+
+/// Gets the full path to a specific backup file
+#[tauri::command]
+pub(crate) fn get_backup_file_path(
+    app: AppHandle,
+    backup_filename: String,
+) -> Result<String, String> {
+    let backup_path = resolve_any_backup_file(&app, &backup_filename)?;
+
+    Ok(backup_path.to_string_lossy().to_string())
+}
+
+/// Creates a safety backup of the current database before import operations
+#[tauri::command]
+pub(crate) fn create_safety_backup(app: AppHandle) -> Result<String, String> {
+    let db_path = get_db_path(&app)?;
+    let backups_dir = get_backups_dir(&app)?;
+
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let safety_filename = safety_backup_filename("import");
+    let safety_path = backups_dir.join(&safety_filename);
+
+    snapshot_database(&db_path, &safety_path)
+        .map_err(|e| format!("Failed to create safety backup: {}", e))?;
+    record_safety_origin(&safety_path, "import", None);
+
+    Ok(safety_filename)
+}
+
+/// Gets the weekday -> backup directory rotation map
+#[tauri::command]
+pub(crate) fn get_backup_rotation(app: AppHandle) -> Result<HashMap<String, String>, String> {
+    Ok(get_backup_rotation_map(&app))
+}
+
+/// Saves the weekday -> backup directory rotation map
+/// Keys must be weekday names (e.g. "monday"); empty paths remove that day
+#[tauri::command]
+pub(crate) fn set_backup_rotation(
+    app: AppHandle,
+    rotation: HashMap<String, String>,
+) -> Result<(), String> {
+    let mut cleaned: HashMap<String, String> = HashMap::new();
+
+    for (day, path) in rotation {
+        let day = day.trim().to_lowercase();
+        if !WEEKDAYS.contains(&day.as_str()) {
+            return Err(format!("Invalid weekday: {}", day));
+        }
+
+        if !path.trim().is_empty() {
+            let dir = normalize_user_path(&app, &path)
+                .and_then(|dir| check_backups_dir_location(&app, &dir).map(|_| dir))
+                .map_err(|e| format!("Invalid folder for {}: {}", day, e))?;
+            cleaned.insert(day, dir.to_string_lossy().to_string());
+        }
+    }
+
+    let value = serde_json::to_string(&cleaned).map_err(|e| e.to_string())?;
+    set_setting(&app, BACKUP_ROTATION_KEY, &value)
+}
+
+/// Checks a passphrase against an encrypted backup without restoring it.
+/// Only the first chunk is decrypted and its AES-GCM tag validated.
+/// Returns Ok(false) for a wrong passphrase (or tampered data) and Err for I/O problems.
+#[tauri::command]
+pub(crate) fn verify_encrypted_backup(
+    app: AppHandle,
+    backup_filename: String,
+    passphrase: String,
+) -> Result<bool, String> {
+    let backup_path = backup_file_in_dir(&app, &backup_filename)?;
+
+    verify_encrypted_file(&backup_path, &passphrase)
+}
+
+pub(crate) fn verify_encrypted_file(path: &Path, passphrase: &str) -> Result<bool, String> {
+    let mut file =
+        fs::File::open(path).map_err(|e| format!("Failed to open encrypted backup: {}", e))?;
+    let header = read_encryption_header(&mut file)?;
+
+    // Read one extra byte to know whether the first chunk is also the last one
+    let mut chunk = vec![0u8; ENCRYPTION_CHUNK_SIZE + ENCRYPTION_TAG_LEN + 1];
+    let read = read_full(&mut file, &mut chunk)
+        .map_err(|e| format!("Failed to read encrypted backup: {}", e))?;
+    if read < ENCRYPTION_TAG_LEN {
+        return Err("Encrypted backup is truncated".to_string());
+    }
+
+    let key = derive_encryption_key(passphrase, &header);
+    let decryptor = DecryptorBE32::from_aead(Aes256Gcm::new(&key), (&header.nonce_prefix).into());
+
+    let plaintext = if read > ENCRYPTION_CHUNK_SIZE + ENCRYPTION_TAG_LEN {
+        let mut decryptor = decryptor;
+        decryptor.decrypt_next(&chunk[..ENCRYPTION_CHUNK_SIZE + ENCRYPTION_TAG_LEN])
+    } else {
+        decryptor.decrypt_last(&chunk[..read])
+    };
+
+    let Ok(plaintext) = plaintext else {
+        return Ok(false);
+    };
+
+    // The decrypted payload must be a database or a gzip-compressed database
+    if plaintext.starts_with(SQLITE_MAGIC) || plaintext.starts_with(GZIP_MAGIC) {
+        Ok(true)
+    } else {
+        Err("Backup decrypted but does not contain a database".to_string())
+    }
+}
+
+/// Whether a file starts with the encrypted backup container's magic
+pub(crate) fn is_encrypted_backup(path: &Path) -> Result<bool, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut magic = [0u8; 8];
+    let read =
+        read_full(&mut file, &mut magic).map_err(|e| format!("Failed to read file: {}", e))?;
+    Ok(read == magic.len() && &magic == ENCRYPTION_MAGIC)
+}
+
+/// Decrypts a whole encrypted backup container into `out`, chunk by chunk.
+/// Every chunk's tag is checked, so a wrong passphrase or a damaged file fails
+/// before anything after the bad chunk is written.
+pub(crate) fn decrypt_backup_file(
+    path: &Path,
+    passphrase: &str,
+    out: &mut impl Write,
+) -> Result<(), String> {
+    let mut file =
+        fs::File::open(path).map_err(|e| format!("Failed to open encrypted backup: {}", e))?;
+    let header = read_encryption_header(&mut file)?;
+    let key = derive_encryption_key(passphrase, &header);
+    let mut decryptor =
+        DecryptorBE32::from_aead(Aes256Gcm::new(&key), (&header.nonce_prefix).into());
+
+    let io_err = |e: std::io::Error| format!("Failed to decrypt backup: {}", e);
+    let wrong_key = || "Wrong passphrase, or the encrypted backup is damaged".to_string();
+    let sealed_len = ENCRYPTION_CHUNK_SIZE + ENCRYPTION_TAG_LEN;
+    let mut current = vec![0u8; sealed_len];
+    let mut next = vec![0u8; sealed_len];
+    let mut current_len = read_full(&mut file, &mut current).map_err(io_err)?;
+    loop {
+        let next_len = read_full(&mut file, &mut next).map_err(io_err)?;
+        if next_len == 0 {
+            break;
+        }
+        if current_len != sealed_len {
+            return Err("Encrypted backup is truncated".to_string());
+        }
+        let plaintext = decryptor
+            .decrypt_next(current.as_slice())
+            .map_err(|_| wrong_key())?;
+        out.write_all(&plaintext).map_err(io_err)?;
+        std::mem::swap(&mut current, &mut next);
+        current_len = next_len;
+    }
+    let plaintext = decryptor
+        .decrypt_last(&current[..current_len])
+        .map_err(|_| wrong_key())?;
+    out.write_all(&plaintext).map_err(io_err)
+}
+
+/// Decrypts a .db.enc or .db.gz.enc backup into a temp file holding the plain
+/// database, gunzipping it when the sealed payload is compressed
+pub(crate) fn decrypt_backup_to_temp(
+    path: &Path,
+    passphrase: &str,
+) -> Result<TempFileGuard, String> {
+    let temp = TempFileGuard(unique_temp_path("decrypted", "db"));
+    let mut out =
+        fs::File::create(&temp.0).map_err(|e| format!("Failed to decrypt backup: {}", e))?;
+    decrypt_backup_file(path, passphrase, &mut out)?;
+    out.sync_all()
+        .map_err(|e| format!("Failed to decrypt backup: {}", e))?;
+    drop(out);
+
+    let mut magic = [0u8; 2];
+    let read = fs::File::open(&temp.0)
+        .and_then(|mut f| read_full(&mut f, &mut magic))
+        .map_err(|e| format!("Failed to read decrypted backup: {}", e))?;
+    if read == magic.len() && &magic == GZIP_MAGIC {
+        return decompress_backup_to_temp(&temp.0);
+    }
+    Ok(temp)
+}
+
+/// Verifies every backup against its checksum sidecar on a background thread.
+/// Emits "verify-progress" after each file; cancel with cancel_verify_backups.
+#[tauri::command]
+pub(crate) async fn verify_all_backups(
+    app: AppHandle,
+    state: State<'_, VerifyState>,
+) -> Result<VerifySummary, String> {
+    if state.running.swap(true, Ordering::SeqCst) {
+        return Err("Backup verification is already running".to_string());
+    }
+    state.cancel.store(false, Ordering::SeqCst);
+
+    let worker_app = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || run_backup_verification(&worker_app))
+        .await
+        .map_err(|e| format!("Verification task failed: {}", e))
+        .and_then(|r| r);
+
+    state.running.store(false, Ordering::SeqCst);
+    result
+}
+
+pub(crate) fn run_backup_verification(app: &AppHandle) -> Result<VerifySummary, String> {
+    let backups = list_backups(app.clone(), None)?;
+    let state = app.state::<VerifyState>();
+    let mut summary = VerifySummary {
+        total: backups.len(),
+        ..Default::default()
+    };
+
+    for (index, backup) in backups.into_iter().enumerate() {
+        if state.cancel.load(Ordering::SeqCst) {
+            summary.cancelled = true;
+            break;
+        }
+
+        let path = PathBuf::from(&backup.path);
+        let outcome = (|| {
+            // A .db.gz is checked through the database it decompresses to; its
+            // checksum sidecar covers the compressed file as stored
+            let decompressed = if is_compressed_backup(&backup.filename) {
+                Some(decompress_backup_to_temp(&path)?)
+            } else {
+                None
+            };
+            let database = decompressed
+                .as_ref()
+                .map_or(path.as_path(), |t| t.0.as_path());
+            if !has_sqlite_header(database)? {
+                return Err("Not a valid SQLite database".to_string());
+            }
+            let expected = read_checksum_sidecar(&path);
+            if let Some(expected) = &expected {
+                if compute_file_sha256(&path)? != *expected {
+                    return Err("Checksum mismatch".to_string());
+                }
+            }
+            let conn = open_database(database, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+            let integrity = run_integrity_check(&conn);
+            if integrity != "ok" {
+                return Err(format!("Integrity check failed: {}", integrity));
+            }
+            Ok(expected.is_some())
+        })();
+
+        let passed = outcome.is_ok();
+        match outcome {
+            Ok(true) => summary.passed.push(backup.filename.clone()),
+            Ok(false) => summary.unverified.push(backup.filename.clone()),
+            Err(reason) => summary.failed.push(VerifyFailure {
+                filename: backup.filename.clone(),
+                reason,
+            }),
+        }
+
+        let _ = app.emit(
+            "verify-progress",
+            VerifyProgress {
+                current: index + 1,
+                total: summary.total,
+                filename: backup.filename,
+                passed,
+            },
+        );
+    }
+
+    Ok(summary)
+}
+
+/// Requests cancellation of a running verify_all_backups
+#[tauri::command]
+pub(crate) fn cancel_verify_backups(state: State<'_, VerifyState>) {
+    state.cancel.store(true, Ordering::SeqCst);
+}
+
+/// Times backing up and restoring the live database as a plain file copy,
+/// VACUUM INTO and a gzip-compressed snapshot. Everything is written to a
+/// scratch folder that is removed afterwards. Emits "benchmark-progress".
+#[tauri::command]
+pub(crate) async fn benchmark_backup_formats(
+    app: AppHandle,
+) -> Result<BackupBenchmarkResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let scratch_dir = unique_temp_path("benchmark", "d");
+        fs::create_dir_all(&scratch_dir)
+            .map_err(|e| format!("Failed to create benchmark folder: {}", e))?;
+        let result = run_backup_benchmark(&app, &scratch_dir);
+        let _ = fs::remove_dir_all(&scratch_dir);
+        result
+    })
+    .await
+    .map_err(|e| format!("Benchmark task failed: {}", e))?
+}
+
+pub(crate) fn run_backup_benchmark(
+    app: &AppHandle,
+    scratch_dir: &Path,
+) -> Result<BackupBenchmarkResult, String> {
+    use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+
+    let db_path = get_db_path(app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+    let database_size = fs::metadata(&db_path)
+        .map_err(|e| format!("Failed to read database size: {}", e))?
+        .len();
+
+    let steps = ["copy", "vacuum_into", "gzip"];
+    let report = |current: usize, step: String| {
+        let _ = app.emit(
+            "benchmark-progress",
+            BenchmarkProgress {
+                current,
+                total: steps.len() * 2,
+                step,
+            },
+        );
+    };
+    let elapsed_ms = |started: Instant| started.elapsed().as_millis() as u64;
+
+    let mut formats = Vec::new();
+    for (index, format) in steps.iter().enumerate() {
+        let backup_path = scratch_dir.join(format!("{}.bak", format));
+        let restore_path = scratch_dir.join(format!("{}_restored.db", format));
+
+        report(index * 2 + 1, format!("{} backup", format));
+        let started = Instant::now();
+        match *format {
+            "copy" => {
+                copy_file_durable(&db_path, &backup_path)
+                    .map_err(|e| format!("Copy backup failed: {}", e))?;
+            }
+            "vacuum_into" => {
+                let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+                conn.execute("VACUUM INTO ?1", params![backup_path.to_string_lossy()])
+                    .map_err(|e| format!("VACUUM INTO backup failed: {}", e))?;
+            }
+            _ => {
+                let snapshot_path = scratch_dir.join("gzip_snapshot.db");
+                snapshot_database(&db_path, &snapshot_path)?;
+                let mut source = fs::File::open(&snapshot_path)
+                    .map_err(|e| format!("Gzip backup failed: {}", e))?;
+                let out = fs::File::create(&backup_path)
+                    .map_err(|e| format!("Gzip backup failed: {}", e))?;
+                let mut encoder = GzEncoder::new(out, Compression::default());
+                std::io::copy(&mut source, &mut encoder)
+                    .and_then(|_| encoder.finish())
+                    .and_then(|file| file.sync_all())
+                    .map_err(|e| format!("Gzip backup failed: {}", e))?;
+                let _ = fs::remove_file(&snapshot_path);
+            }
+        }
+        let backup_ms = elapsed_ms(started);
+        let size_bytes = fs::metadata(&backup_path)
+            .map_err(|e| format!("Failed to read {} backup size: {}", format, e))?
+            .len();
+
+        // Restores go to a scratch file; the live database is never touched
+        report(index * 2 + 2, format!("{} restore", format));
+        let started = Instant::now();
+        if *format == "gzip" {
+            let source =
+                fs::File::open(&backup_path).map_err(|e| format!("Gzip restore failed: {}", e))?;
+            let mut out = fs::File::create(&restore_path)
+                .map_err(|e| format!("Gzip restore failed: {}", e))?;
+            std::io::copy(&mut GzDecoder::new(source), &mut out)
+                .and_then(|_| out.sync_all())
+                .map_err(|e| format!("Gzip restore failed: {}", e))?;
+        } else {
+            copy_file_durable(&backup_path, &restore_path)
+                .map_err(|e| format!("{} restore failed: {}", format, e))?;
+        }
+        let restore_ms = elapsed_ms(started);
+        if !has_sqlite_header(&restore_path)? {
+            return Err(format!("{} round trip did not produce a database", format));
+        }
+
+        let _ = fs::remove_file(&backup_path);
+        let _ = fs::remove_file(&restore_path);
+        formats.push(BackupFormatBenchmark {
+            format: format.to_string(),
+            size_bytes,
+            backup_ms,
+            restore_ms,
+        });
+    }
+
+    Ok(BackupBenchmarkResult {
+        database_size,
+        formats,
+    })
+}
+
+// ============================================
+// ARCHIVAL BACKUPS (compressed + encrypted, one file)
+// ============================================
+
+/// Written next to an archival backup as <archive>.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivalManifest {
+    pub filename: String,
+    pub created_at: String,
+    /// PRAGMA user_version of the snapshot
+    pub schema_version: i64,
+    pub uncompressed_size: u64,
+    pub compressed_size: u64,
+    pub archive_size: u64,
+    /// SHA-256 of the whole archive file, checked before anything is decrypted
+    pub sha256: String,
+    pub compression: String,
+    pub cipher: String,
+    pub chunk_size: usize,
+    pub kdf: String,
+    pub kdf_iterations: u32,
+}
+
+/// Write half of the pipeline: seals ENCRYPTION_CHUNK_SIZE plaintext chunks as
+/// they fill and hashes every byte that reaches the file
+pub(crate) struct ArchiveWriter {
+    pub(crate) file: fs::File,
+    pub(crate) hasher: sha2::Sha256,
+    pub(crate) encryptor: Option<EncryptorBE32<Aes256Gcm>>,
+    pub(crate) pending: Vec<u8>,
+    pub(crate) plaintext_len: u64,
+}
+
+impl ArchiveWriter {
+    pub(crate) fn write_hashed(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        use sha2::Digest;
+        self.file.write_all(bytes)?;
+        self.hasher.update(bytes);
+        Ok(())
+    }
+
+    /// Seals the final chunk and syncs; returns (sha256, plaintext bytes)
+    pub(crate) fn finish(mut self) -> std::io::Result<(String, u64)> {
+        use sha2::Digest;
+        let encryptor = self.encryptor.take().expect("archive already finished");
+        let last = encryptor
+            .encrypt_last(self.pending.as_slice())
+            .map_err(|_| std::io::Error::other("Encryption failed"))?;
+        self.write_hashed(&last)?;
+        self.file.sync_all()?;
+        Ok((format!("{:x}", self.hasher.finalize()), self.plaintext_len))
+    }
+}
+
+impl Write for ArchiveWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // A full chunk is only sealed once more data arrives, so the final one
+        // always goes through encrypt_last
+        if self.pending.len() == ENCRYPTION_CHUNK_SIZE && !buf.is_empty() {
+            let sealed = self
+                .encryptor
+                .as_mut()
+                .expect("archive already finished")
+                .encrypt_next(self.pending.as_slice())
+                .map_err(|_| std::io::Error::other("Encryption failed"))?;
+            self.write_hashed(&sealed)?;
+            self.pending.clear();
+        }
+        let take = buf.len().min(ENCRYPTION_CHUNK_SIZE - self.pending.len());
+        self.pending.extend_from_slice(&buf[..take]);
+        self.plaintext_len += take as u64;
+        Ok(take)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Takes a WAL-safe snapshot and streams it through gzip and AES-256-GCM into
+/// one .db.gz.enc file in the backups folder, hashing the ciphertext as it is
+/// written. A manifest (<archive>.json) records the schema version, sizes and
+/// algorithm parameters; the hash also goes to the usual .sha256 sidecar.
+#[tauri::command]
+pub(crate) async fn create_archival_backup(
+    app: AppHandle,
+    passphrase: String,
+) -> Result<ArchivalManifest, String> {
+    if passphrase.chars().count() < ARCHIVAL_MIN_PASSPHRASE_LEN {
+        return Err(format!(
+            "Passphrase must be at least {} characters",
+            ARCHIVAL_MIN_PASSPHRASE_LEN
+        ));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let _operation = begin_operation(&app, "archival_backup", false);
+        let result = run_archival_backup(&app, &passphrase);
+        log_operation(
+            &app,
+            match &result {
+                Ok(manifest) => OperationRecord::new(
+                    "archival_backup",
+                    true,
+                    format!(
+                        "Archived {} bytes as {} bytes (schema {})",
+                        manifest.uncompressed_size, manifest.archive_size, manifest.schema_version
+                    ),
+                )
+                .with_filename(&manifest.filename),
+                Err(e) => OperationRecord::new("archival_backup", false, e.clone()),
+            },
+        );
+        result
+    })
+    .await
+    .map_err(|e| format!("Archival backup task failed: {}", e))?
+}
+
+pub(crate) fn run_archival_backup(
+    app: &AppHandle,
+    passphrase: &str,
+) -> Result<ArchivalManifest, String> {
+    let db_path = get_db_path(app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+    let backups_dir = get_backups_dir(app)?;
+    let filename = format!(
+        "motormods_archive_{}{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S"),
+        ARCHIVAL_BACKUP_SUFFIX
+    );
+    let archive_path = backups_dir.join(&filename);
+    let partial_path = backups_dir.join(format!("{}.partial", filename));
+    let snapshot_path = unique_temp_path("archive_snapshot", "db");
+
+    let result = (|| {
+        snapshot_database(&db_path, &snapshot_path)?;
+        let (schema_version, _) = read_schema_ids(&open_database(
+            &snapshot_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY,
+        )?)?;
+        let uncompressed_size = fs::metadata(&snapshot_path)
+            .map_err(|e| format!("Failed to read snapshot size: {}", e))?
+            .len();
+        let (sha256, compressed_size) = pack_archival_backup(
+            &snapshot_path,
+            passphrase,
+            ARCHIVAL_KDF_ITERATIONS,
+            &partial_path,
+        )?;
+        fs::rename(&partial_path, &archive_path)
+            .map_err(|e| format!("Failed to finish archival backup: {}", e))?;
+
+        let manifest = ArchivalManifest {
+            filename: filename.clone(),
+            created_at: Local::now().to_rfc3339(),
+            schema_version,
+            uncompressed_size,
+            compressed_size,
+            archive_size: fs::metadata(&archive_path)
+                .map_err(|e| format!("Failed to read archive size: {}", e))?
+                .len(),
+            sha256,
+            compression: "gzip".to_string(),
+            cipher: "AES-256-GCM (STREAM, BE32)".to_string(),
+            chunk_size: ENCRYPTION_CHUNK_SIZE,
+            kdf: "PBKDF2-HMAC-SHA256".to_string(),
+            kdf_iterations: ARCHIVAL_KDF_ITERATIONS,
+        };
+        let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+        fs::write(manifest_sidecar_path(&archive_path), json)
+            .map_err(|e| format!("Failed to write archive manifest: {}", e))?;
+        write_checksum_sidecar(&archive_path, &manifest.sha256)?;
+        Ok(manifest)
+    })();
+    let _ = fs::remove_file(&snapshot_path);
+    let _ = fs::remove_file(&partial_path);
+    result
+}
+
+/// Gzips `snapshot` and seals it into `output` in the encrypted container,
+/// hashing the ciphertext as it is written. Returns its SHA-256 and the
+/// compressed size.
+pub(crate) fn pack_archival_backup(
+    snapshot: &Path,
+    passphrase: &str,
+    iterations: u32,
+    output: &Path,
+) -> Result<(String, u64), String> {
+    use aes_gcm::aead::{rand_core::RngCore, OsRng};
+    use flate2::{write::GzEncoder, Compression};
+
+    let mut header = EncryptionHeader {
+        iterations,
+        salt: [0u8; ENCRYPTION_SALT_LEN],
+        nonce_prefix: [0u8; ENCRYPTION_NONCE_PREFIX_LEN],
+    };
+    OsRng.fill_bytes(&mut header.salt);
+    OsRng.fill_bytes(&mut header.nonce_prefix);
+    let key = derive_encryption_key(passphrase, &header);
+
+    let io_err = |e: std::io::Error| format!("Failed to write archival backup: {}", e);
+    let mut writer = ArchiveWriter {
+        file: fs::File::create(output).map_err(io_err)?,
+        hasher: sha2::Digest::new(),
+        encryptor: Some(EncryptorBE32::from_aead(
+            Aes256Gcm::new(&key),
+            (&header.nonce_prefix).into(),
+        )),
+        pending: Vec::with_capacity(ENCRYPTION_CHUNK_SIZE),
+        plaintext_len: 0,
+    };
+    writer
+        .write_hashed(ENCRYPTION_MAGIC)
+        .and_then(|_| writer.write_hashed(&header.iterations.to_le_bytes()))
+        .and_then(|_| writer.write_hashed(&header.salt))
+        .and_then(|_| writer.write_hashed(&header.nonce_prefix))
+        .map_err(io_err)?;
+
+    let mut snapshot = fs::File::open(snapshot).map_err(io_err)?;
+    let mut encoder = GzEncoder::new(writer, Compression::default());
+    std::io::copy(&mut snapshot, &mut encoder).map_err(io_err)?;
+    encoder
+        .finish()
+        .and_then(ArchiveWriter::finish)
+        .map_err(io_err)
+}
+
+/// Reverses create_archival_backup: verifies the archive against its manifest
+/// checksum, then decrypts and decompresses it into a .db backup and restores
+/// that the same way restore_database does (safety backup first)
+#[tauri::command]
+pub(crate) async fn restore_archival_backup(
+    app: AppHandle,
+    archive_filename: String,
+    passphrase: String,
+) -> Result<String, String> {
+    require_primary_instance(&app)?;
+    let is_plain_name = !archive_filename.contains(['/', '\\'])
+        && Path::new(&archive_filename).file_name()
+            == Some(std::ffi::OsStr::new(&archive_filename));
+    let Some(stem) = archive_filename
+        .strip_suffix(ARCHIVAL_BACKUP_SUFFIX)
+        .filter(|stem| is_plain_name && !stem.is_empty())
+    else {
+        return Err(format!(
+            "Invalid archival backup filename: {}",
+            archive_filename
+        ));
+    };
+    let backups_dir = get_backups_dir(&app)?;
+    let archive_path = backups_dir.join(&archive_filename);
+    if !archive_path.is_file() {
+        return Err(format!("Archival backup not found: {}", archive_filename));
+    }
+    let restored_filename =
+        imported_backup_filename(&backups_dir, Path::new(&format!("{}.db", stem)));
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let restored_path = backups_dir.join(&restored_filename);
+        let partial_path = backups_dir.join(format!("{}.partial", restored_filename));
+        let unpacked = unpack_archival_backup(&archive_path, &passphrase, &partial_path)
+            .and_then(|_| check_backup_restorable(&partial_path, &archive_filename))
+            .and_then(|_| {
+                fs::rename(&partial_path, &restored_path)
+                    .map_err(|e| format!("Failed to unpack archival backup: {}", e))
+            });
+        if let Err(e) = unpacked {
+            let _ = fs::remove_file(&partial_path);
+            return Err(e);
+        }
+        write_checksum_sidecar(&restored_path, &compute_file_sha256(&restored_path)?)?;
+
+        let message = restore_database(app, restored_filename.clone())?;
+        Ok(format!(
+            "Unpacked {} as {}. {}",
+            archive_filename, restored_filename, message
+        ))
+    })
+    .await
+    .map_err(|e| format!("Archival restore task failed: {}", e))?
+}
+
+/// Checks the archive's checksum against its manifest, then decrypts and gunzips
+/// it into `output`, which must end up the size the manifest recorded
+pub(crate) fn unpack_archival_backup(
+    archive_path: &Path,
+    passphrase: &str,
+    output: &Path,
+) -> Result<(), String> {
+    use flate2::write::GzDecoder;
+
+    let manifest: ArchivalManifest = fs::read_to_string(manifest_sidecar_path(archive_path))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .ok_or("Archival backup has no readable manifest, so its checksum can't be verified")?;
+    if compute_file_sha256(archive_path)? != manifest.sha256.to_lowercase() {
+        return Err(
+            "Archival backup checksum mismatch: the file is corrupt or was modified".to_string(),
+        );
+    }
+
+    let io_err = |e: std::io::Error| format!("Failed to unpack archival backup: {}", e);
+    let mut decoder = GzDecoder::new(fs::File::create(output).map_err(io_err)?);
+    decrypt_backup_file(archive_path, passphrase, &mut decoder)?;
+    decoder
+        .finish()
+        .and_then(|out| out.sync_all())
+        .map_err(io_err)?;
+
+    let size = fs::metadata(output).map_err(io_err)?.len();
+    if size != manifest.uncompressed_size {
+        return Err(format!(
+            "Unpacked database is {} bytes; the manifest recorded {}",
+            size, manifest.uncompressed_size
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{sample_database, sha256_hex, TempDir};
+
+    #[test]
+    fn backup_checksum_matches_the_written_file() {
+        let snapshot = sample_database();
+        let backup = TempFileGuard(unique_temp_path("test_backup", "db"));
+
+        let checksum = finish_backup_copy(&snapshot.0, &backup.0).unwrap();
+
+        let written = fs::read(&backup.0).unwrap();
+        assert_eq!(checksum, sha256_hex(&written));
+        assert_eq!(written, fs::read(&snapshot.0).unwrap());
+    }
+
+    /// A listing entry for a file in a test folder
+    fn backup_info(dir: &TempDir, filename: &str, modified_epoch_ms: i64) -> BackupFileInfo {
+        let path = dir.0.join(filename);
+        BackupFileInfo {
+            filename: filename.to_string(),
+            path: path.to_string_lossy().to_string(),
+            file_size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            modified_at: String::new(),
+            modified_epoch_ms: Some(modified_epoch_ms),
+            protected: false,
+            redacted: false,
+            captured_at: None,
+        }
+    }
+
+    #[test]
+    fn retention_keeps_the_last_good_backup_unless_forced() {
+        let dir = TempDir::new("test_retention");
+        let good = sample_database();
+        fs::copy(&good.0, dir.0.join("old_good.db")).unwrap();
+        fs::copy(&good.0, dir.0.join("older_good.db")).unwrap();
+        fs::write(dir.0.join("recent_corrupt.db"), b"not a database").unwrap();
+        let backups = || {
+            vec![
+                backup_info(&dir, "recent_corrupt.db", 3_000),
+                backup_info(&dir, "old_good.db", 1_000),
+                backup_info(&dir, "older_good.db", 500),
+            ]
+        };
+        let names = |plan: &RetentionPlan| {
+            plan.expired
+                .iter()
+                .map(|b| b.filename.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let plan = plan_retention(backups(), 2_000, 7, false);
+        assert_eq!(plan.preserved_last_good.as_deref(), Some("old_good.db"));
+        assert_eq!(names(&plan), ["older_good.db"]);
+        assert!(plan.warning.is_some());
+
+        let plan = plan_retention(backups(), 2_000, 7, true);
+        assert_eq!(plan.preserved_last_good, None);
+        assert_eq!(names(&plan), ["old_good.db", "older_good.db"]);
+    }
+
+    #[test]
+    fn retention_deletes_expired_backups_when_a_recent_one_is_good() {
+        let dir = TempDir::new("test_retention_good");
+        let good = sample_database();
+        fs::copy(&good.0, dir.0.join("recent.db")).unwrap();
+        fs::copy(&good.0, dir.0.join("old.db")).unwrap();
+        fs::copy(&good.0, dir.0.join("pre_reset_safety_old.db")).unwrap();
+        let mut protected = backup_info(&dir, "old_protected.db", 1_000);
+        protected.protected = true;
+
+        let plan = plan_retention(
+            vec![
+                backup_info(&dir, "recent.db", 3_000),
+                backup_info(&dir, "old.db", 1_000),
+                protected,
+                backup_info(&dir, "pre_reset_safety_old.db", 500),
+            ],
+            2_000,
+            7,
+            false,
+        );
+
+        let expired: Vec<_> = plan.expired.iter().map(|b| b.filename.as_str()).collect();
+        assert_eq!(expired, ["old.db"]);
+        assert!(plan.preserved_last_good.is_none() && plan.warning.is_none());
+    }
+
+    #[test]
+    fn prune_keeps_the_newest_backups_and_limits_safety_ones_separately() {
+        let dir = TempDir::new("test_prune");
+        let mut protected = backup_info(&dir, "protected.db", 100);
+        protected.protected = true;
+        let backups = || {
+            vec![
+                backup_info(&dir, "pre_restore_safety_3.db", 6_000),
+                backup_info(&dir, "newest.db", 5_000),
+                backup_info(&dir, "pre_import_safety_2.db", 4_000),
+                backup_info(&dir, "middle.db", 3_000),
+                backup_info(&dir, "pre_restore_safety_1.db", 2_000),
+                backup_info(&dir, "oldest.db", 1_000),
+                protected.clone(),
+            ]
+        };
+        let pruned = |keep_count, cutoff_ms, safety_keep_count| {
+            select_prunable_backups(backups(), keep_count, cutoff_ms, safety_keep_count)
+                .into_iter()
+                .map(|b| b.filename)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(pruned(2, None, 2), ["oldest.db", "pre_restore_safety_1.db"]);
+        // Age removes normal backups only, and never the newest one
+        assert_eq!(pruned(5, Some(10_000), 5), ["middle.db", "oldest.db"]);
+        assert_eq!(
+            pruned(1, None, 1),
+            [
+                "middle.db",
+                "oldest.db",
+                "pre_import_safety_2.db",
+                "pre_restore_safety_1.db"
+            ]
+        );
+    }
+
+    #[test]
+    fn size_cap_removes_the_oldest_deletable_backups_first() {
+        let dir = TempDir::new("test_size_cap");
+        let epoch = std::time::UNIX_EPOCH;
+        for (index, name) in ["pre_reset_safety_1.db", "a.db", "b.db", "c.db"]
+            .iter()
+            .enumerate()
+        {
+            let file = fs::File::create(dir.0.join(name)).unwrap();
+            file.set_len(1_000).unwrap();
+            file.set_modified(epoch + Duration::from_secs(1_000 + index as u64))
+                .unwrap();
+        }
+
+        let outcome = enforce_backup_size_cap(&dir.0, 2_500, Some("c.db")).unwrap();
+        assert_eq!(outcome.removed, ["a.db", "b.db"]);
+        assert_eq!(outcome.total_bytes, 2_000);
+        assert!(outcome.under_cap);
+
+        // Only the safety backup and the kept one are left, so the cap can't be met
+        let outcome = enforce_backup_size_cap(&dir.0, 500, Some("c.db")).unwrap();
+        assert!(outcome.removed.is_empty() && !outcome.under_cap);
+        assert!(dir.0.join("pre_reset_safety_1.db").exists() && dir.0.join("c.db").exists());
+    }
+
+    #[test]
+    fn size_cap_never_removes_a_protected_backup() {
+        let dir = TempDir::new("test_size_cap_protected");
+        let backup = dir.0.join("protected.db");
+        fs::write(&backup, vec![0u8; 1_000]).unwrap();
+        let manifest = BackupManifest {
+            protected: true,
+            ..Default::default()
+        };
+        write_backup_manifest(&backup, &manifest).unwrap();
+
+        let outcome = enforce_backup_size_cap(&dir.0, 100, None).unwrap();
+        assert!(outcome.removed.is_empty() && !outcome.under_cap);
+        assert!(backup.exists());
+    }
+
+    #[test]
+    fn backup_inside_the_cooldown_returns_the_earlier_one() {
+        let previous = BackupResult {
+            filename: "motormods_backup_2024-03-01_10-00-00.db".to_string(),
+            path: String::new(),
+            file_size: 4_096,
+            created_at: String::new(),
+            backup_dir: String::new(),
+            warning: None,
+            checksum: "abc".to_string(),
+            duration_ms: 5,
+            throughput_mbps: 1.0,
+            skipped_reason: None,
+            nothing_to_back_up: false,
+            verified: true,
+            integrity_error: None,
+        };
+        let cooldown = Duration::from_secs(30);
+        let just_now = (Instant::now(), previous.clone());
+
+        let skipped = backup_within_cooldown(Some(&just_now), cooldown).unwrap();
+        assert_eq!(skipped.filename, previous.filename);
+        assert_eq!(
+            skipped.skipped_reason.as_deref(),
+            Some("Backup taken 0 seconds ago")
+        );
+
+        assert!(backup_within_cooldown(Some(&just_now), Duration::ZERO).is_none());
+        assert!(backup_within_cooldown(None, cooldown).is_none());
+        if let Some(earlier) = Instant::now().checked_sub(Duration::from_secs(60)) {
+            assert!(backup_within_cooldown(Some(&(earlier, previous)), cooldown).is_none());
+        }
+    }
+
+    fn redaction_column(table: &str, column: &str) -> RedactionColumn {
+        RedactionColumn {
+            table: table.to_string(),
+            column: column.to_string(),
+        }
+    }
+
+    fn product_names(path: &Path) -> Vec<Option<String>> {
+        let conn = Connection::open(path).unwrap();
+        let mut stmt = conn
+            .prepare("SELECT name FROM products ORDER BY id")
+            .unwrap();
+        let names = stmt
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        names
+    }
+
+    #[test]
+    fn redaction_replaces_values_and_leaves_nothing_in_the_file() {
+        let db = sample_database();
+        let columns = [
+            redaction_column("products", "name"),
+            redaction_column("invoices", "customer_name"),
+        ];
+
+        redact_database(&db.0, &columns, false, false).unwrap();
+
+        let placeholder = Some(REDACTION_PLACEHOLDER.to_string());
+        assert_eq!(product_names(&db.0), [placeholder.clone(), placeholder]);
+        let bytes = fs::read(&db.0).unwrap();
+        assert!(!bytes.windows(9).any(|w| w == b"Brake pad"));
+    }
+
+    #[test]
+    fn redaction_hashes_consistently_and_keeps_nulls() {
+        let db = sample_database();
+        Connection::open(&db.0)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE invoices (id TEXT PRIMARY KEY, customer_phone TEXT);
+                 INSERT INTO invoices VALUES ('1', '555-0100'), ('2', '555-0100'), ('3', NULL);",
+            )
+            .unwrap();
+
+        redact_database(
+            &db.0,
+            &[redaction_column("invoices", "customer_phone")],
+            true,
+            true,
+        )
+        .unwrap();
+
+        let conn = Connection::open(&db.0).unwrap();
+        let phones: Vec<Option<String>> = conn
+            .prepare("SELECT customer_phone FROM invoices ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let hashed = phones[0].clone().unwrap();
+        assert!(hashed.starts_with("redacted-") && hashed.len() == "redacted-".len() + 16);
+        assert_eq!(phones, [Some(hashed.clone()), Some(hashed), None]);
+    }
+
+    #[test]
+    fn redaction_with_explicit_columns_rejects_a_missing_one() {
+        let db = sample_database();
+        let error = redact_database(
+            &db.0,
+            &[redaction_column("invoices", "customer_name")],
+            true,
+            false,
+        )
+        .unwrap_err();
+
+        assert_eq!(error, "Column not found: invoices.customer_name");
+        assert_eq!(
+            product_names(&db.0),
+            [Some("Brake pad".to_string()), Some("Chain".to_string())]
+        );
+    }
+
+    /// Packs `snapshot` as an archival backup in `dir` with its manifest,
+    /// using few KDF iterations to keep the test quick
+    fn archive_with_manifest(snapshot: &Path, dir: &TempDir, passphrase: &str) -> PathBuf {
+        let archive = dir.0.join(format!("test{}", ARCHIVAL_BACKUP_SUFFIX));
+        let (sha256, compressed_size) =
+            pack_archival_backup(snapshot, passphrase, 1_000, &archive).unwrap();
+        let manifest = ArchivalManifest {
+            filename: "test".to_string(),
+            created_at: String::new(),
+            schema_version: 0,
+            uncompressed_size: fs::metadata(snapshot).unwrap().len(),
+            compressed_size,
+            archive_size: fs::metadata(&archive).unwrap().len(),
+            sha256,
+            compression: "gzip".to_string(),
+            cipher: String::new(),
+            chunk_size: ENCRYPTION_CHUNK_SIZE,
+            kdf: String::new(),
+            kdf_iterations: 1_000,
+        };
+        fs::write(
+            manifest_sidecar_path(&archive),
+            serde_json::to_string(&manifest).unwrap(),
+        )
+        .unwrap();
+        archive
+    }
+
+    #[test]
+    fn archival_backup_round_trips_to_the_original_database() {
+        let db = sample_database();
+        // Random rows so the compressed data spans several encryption chunks
+        Connection::open(&db.0)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE blobs (data BLOB);
+                 WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 50)
+                 INSERT INTO blobs SELECT randomblob(4096) FROM n;",
+            )
+            .unwrap();
+        let dir = TempDir::new("test_archive");
+        let archive = archive_with_manifest(&db.0, &dir, "correct horse");
+        assert_eq!(
+            compute_file_sha256(&archive).unwrap(),
+            sha256_hex(&fs::read(&archive).unwrap())
+        );
+
+        let unpacked = dir.0.join("unpacked.db");
+        unpack_archival_backup(&archive, "correct horse", &unpacked).unwrap();
+
+        assert_eq!(fs::read(&unpacked).unwrap(), fs::read(&db.0).unwrap());
+    }
+
+    #[test]
+    fn archival_backup_is_refused_when_tampered_or_with_a_wrong_passphrase() {
+        let db = sample_database();
+        let dir = TempDir::new("test_archive_checks");
+        let archive = archive_with_manifest(&db.0, &dir, "correct horse");
+        let unpacked = dir.0.join("unpacked.db");
+
+        let error = unpack_archival_backup(&archive, "wrong horse", &unpacked).unwrap_err();
+        assert!(!error.contains("checksum"), "{}", error);
+
+        let mut bytes = fs::read(&archive).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&archive, bytes).unwrap();
+        let error = unpack_archival_backup(&archive, "correct horse", &unpacked).unwrap_err();
+        assert_eq!(
+            error,
+            "Archival backup checksum mismatch: the file is corrupt or was modified"
+        );
+    }
+
+    #[test]
+    fn backup_filename_skips_names_already_taken() {
+        let dir = TempDir::new("test_backup_names");
+        let stem = "motormods_backup_2024-03-01_10-00-00";
+        assert_eq!(unused_backup_filename(&dir.0, stem), format!("{}.db", stem));
+
+        fs::write(dir.0.join(format!("{}.db.gz", stem)), b"compressed").unwrap();
+        assert_eq!(
+            unused_backup_filename(&dir.0, stem),
+            format!("{}_2.db", stem)
+        );
+
+        fs::write(dir.0.join(format!("{}_2.db.snapshot", stem)), b"partial").unwrap();
+        assert_eq!(
+            unused_backup_filename(&dir.0, stem),
+            format!("{}_3.db", stem)
+        );
+    }
+
+    #[test]
+    fn deterministic_backup_is_byte_identical_across_runs() {
+        let source = sample_database();
+        let backup = TempFileGuard(unique_temp_path("test_deterministic", "db"));
+        let modified = std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        write_deterministic_backup(&source.0, &backup.0, modified).unwrap();
+        let first = fs::read(&backup.0).unwrap();
+        write_deterministic_backup(&source.0, &backup.0, modified).unwrap();
+        let second = fs::read(&backup.0).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(
+            fs::metadata(&backup.0).unwrap().modified().unwrap(),
+            modified
+        );
+    }
+
+    /// Seals `plain` in the encrypted container the way run_archival_backup
+    /// does, gzipping it first when `compress` is set
+    fn seal_backup(plain: &Path, passphrase: &str, compress: bool) -> TempFileGuard {
+        use flate2::{write::GzEncoder, Compression};
+
+        let sealed = TempFileGuard(unique_temp_path("test_sealed", "enc"));
+        let header = EncryptionHeader {
+            iterations: 1_000,
+            salt: [7u8; ENCRYPTION_SALT_LEN],
+            nonce_prefix: [9u8; ENCRYPTION_NONCE_PREFIX_LEN],
+        };
+        let key = derive_encryption_key(passphrase, &header);
+        let mut writer = ArchiveWriter {
+            file: fs::File::create(&sealed.0).unwrap(),
+            hasher: sha2::Digest::new(),
+            encryptor: Some(EncryptorBE32::from_aead(
+                Aes256Gcm::new(&key),
+                (&header.nonce_prefix).into(),
+            )),
+            pending: Vec::new(),
+            plaintext_len: 0,
+        };
+        writer.write_hashed(ENCRYPTION_MAGIC).unwrap();
+        writer
+            .write_hashed(&header.iterations.to_le_bytes())
+            .unwrap();
+        writer.write_hashed(&header.salt).unwrap();
+        writer.write_hashed(&header.nonce_prefix).unwrap();
+
+        let mut source = fs::File::open(plain).unwrap();
+        if compress {
+            let mut encoder = GzEncoder::new(writer, Compression::default());
+            std::io::copy(&mut source, &mut encoder).unwrap();
+            encoder.finish().unwrap().finish().unwrap();
+        } else {
+            std::io::copy(&mut source, &mut writer).unwrap();
+            writer.finish().unwrap();
+        }
+        sealed
+    }
+
+    #[test]
+    fn encrypted_backup_decrypts_to_the_original_database() {
+        let source = sample_database();
+        // Spans several ENCRYPTION_CHUNK_SIZE chunks
+        Connection::open(&source.0)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE filler (data BLOB); INSERT INTO filler VALUES (randomblob(200000));",
+            )
+            .unwrap();
+        let original = fs::read(&source.0).unwrap();
+
+        for compress in [false, true] {
+            let sealed = seal_backup(&source.0, "correct horse", compress);
+            assert!(is_encrypted_backup(&sealed.0).unwrap());
+
+            let decrypted = decrypt_backup_to_temp(&sealed.0, "correct horse").unwrap();
+            assert_eq!(fs::read(&decrypted.0).unwrap(), original);
+        }
+        assert!(!is_encrypted_backup(&source.0).unwrap());
+    }
+
+    #[test]
+    fn encrypted_backup_rejects_a_wrong_passphrase() {
+        let source = sample_database();
+        let sealed = seal_backup(&source.0, "correct horse", false);
+
+        let err = decrypt_backup_to_temp(&sealed.0, "wrong horse")
+            .map(|_| ())
+            .unwrap_err();
+        assert!(err.contains("Wrong passphrase"), "{}", err);
+    }
+
+    fn encryption_header_bytes(iterations: u32) -> Vec<u8> {
+        let mut bytes = ENCRYPTION_MAGIC.to_vec();
+        bytes.extend_from_slice(&iterations.to_le_bytes());
+        bytes.extend_from_slice(&[1u8; ENCRYPTION_SALT_LEN]);
+        bytes.extend_from_slice(&[2u8; ENCRYPTION_NONCE_PREFIX_LEN]);
+        bytes
+    }
+
+    #[test]
+    fn encryption_header_is_read_back() {
+        let bytes = encryption_header_bytes(600_000);
+
+        let header = read_encryption_header(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(header.iterations, 600_000);
+        assert_eq!(header.salt, [1u8; ENCRYPTION_SALT_LEN]);
+        assert_eq!(header.nonce_prefix, [2u8; ENCRYPTION_NONCE_PREFIX_LEN]);
+    }
+
+    #[test]
+    fn encryption_header_rejects_bad_magic_iterations_and_truncation() {
+        let header_err = |bytes: &[u8]| {
+            let mut reader = bytes;
+            read_encryption_header(&mut reader).map(|_| ()).unwrap_err()
+        };
+
+        let mut wrong_magic = encryption_header_bytes(1_000);
+        wrong_magic[0] = b'X';
+        assert_eq!(
+            header_err(&wrong_magic),
+            "Not an encrypted MotorMods backup"
+        );
+
+        assert_eq!(
+            header_err(&encryption_header_bytes(0)),
+            "Encrypted backup header is corrupt"
+        );
+        assert_eq!(
+            header_err(&encryption_header_bytes(ENCRYPTION_MAX_ITERATIONS + 1)),
+            "Encrypted backup header is corrupt"
+        );
+
+        let truncated = encryption_header_bytes(1_000);
+        assert!(header_err(&truncated[..truncated.len() - 1])
+            .starts_with("Failed to read encrypted backup header"));
+    }
+}
@@ -0,0 +1,1068 @@
+use super::*;
+
+// ============================================
+// EXPORT TYPES
+// ============================================
+
+/// PORTABLE_MARKER_FILE in a folder written by export_portable_backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortableBackupMarker {
+    pub format: String,
+    pub backup_filename: String,
+    pub sha256: String,
+    pub file_size: u64,
+    pub schema_version: i64,
+    pub captured_at: Option<String>,
+    pub exported_at: String,
+    pub app_version: String,
+}
+
+/// A portable backup folder found by detect_portable_backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortableBackupInfo {
+    pub folder: String,
+    /// Full path of the .db inside the folder
+    pub backup_path: String,
+    /// The .db still matches the checksum recorded at export
+    pub checksum_ok: bool,
+    #[serde(flatten)]
+    pub marker: PortableBackupMarker,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CsvExportResult {
+    pub table: String,
+    /// Every file written, in order; a single file unless rows_per_file split it
+    pub files: Vec<String>,
+    pub total_rows: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParquetExportResult {
+    pub table: String,
+    pub path: String,
+    pub rows_written: u64,
+    pub file_size: u64,
+}
+
+// Marker that makes a folder from export_portable_backup recognisable on import
+pub(crate) const PORTABLE_MARKER_FILE: &str = "motormods-portable.json";
+pub(crate) const PORTABLE_FORMAT: &str = "motormods-portable-v1";
+
+/// Rows buffered per Parquet row group, which bounds export memory use
+pub(crate) const PARQUET_ROW_GROUP_ROWS: usize = 10_000;
+
+// ============================================
+// BACKUP EXPORT
+// ============================================
+
+/// Exports a backup to a specified destination
+#[tauri::command]
+pub(crate) fn export_backup(
+    app: AppHandle,
+    backup_filename: String,
+    destination_path: String,
+) -> Result<String, String> {
+    let backup_path = resolve_any_backup_file(&app, &backup_filename)?;
+    let destination = normalize_user_path(&app, &destination_path)?;
+
+    let _operation = begin_operation(&app, "export", false);
+    copy_file_durable(&backup_path, &destination).map_err(|e| {
+        describe_copy_error("Failed to export backup", &e, &backup_path, &destination)
+    })?;
+
+    Ok(format!("Backup exported to: {}", destination.display()))
+}
+
+/// Writes a backup into a new <stem>_portable folder under `destination_dir`:
+/// the .db, its manifest and checksum sidecars, a README for whoever receives
+/// it, and PORTABLE_MARKER_FILE, which import_backup_interactive recognises.
+/// Returns the folder path.
+#[tauri::command]
+pub(crate) fn export_portable_backup(
+    app: AppHandle,
+    backup_filename: String,
+    destination_dir: String,
+) -> Result<String, String> {
+    let backup_path = resolve_backup_file(&app, &backup_filename)?;
+    let destination = normalize_user_path(&app, &destination_dir)?;
+    if !destination.is_dir() {
+        return Err(format!("{} is not a folder", destination.display()));
+    }
+    let stem = backup_filename.trim_end_matches(".db");
+    let folder = destination.join(format!("{}_portable", stem));
+    if folder.exists() {
+        return Err(format!("{} already exists", folder.display()));
+    }
+
+    let _operation = begin_operation(&app, "export", false);
+    let manifest = read_backup_manifest(&backup_path);
+    let (schema_version, _) = read_schema_ids(&open_database(
+        &backup_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY,
+    )?)?;
+    let sha256 = compute_file_sha256(&backup_path)?;
+    if read_checksum_sidecar(&backup_path).is_some_and(|recorded| recorded != sha256) {
+        return Err(format!(
+            "{} does not match its recorded checksum; not exporting a damaged backup",
+            backup_filename
+        ));
+    }
+    let file_size = fs::metadata(&backup_path)
+        .map_err(|e| format!("Failed to read backup size: {}", e))?
+        .len();
+    let marker = PortableBackupMarker {
+        format: PORTABLE_FORMAT.to_string(),
+        backup_filename: backup_filename.clone(),
+        sha256: sha256.clone(),
+        file_size,
+        schema_version,
+        captured_at: manifest.captured_at.clone(),
+        exported_at: Local::now().to_rfc3339(),
+        app_version: app.package_info().version.to_string(),
+    };
+    let readme = format!(
+        "MotorMods portable backup\n\
+         =========================\n\n\
+         Backup:       {}\n\
+         Data from:    {}\n\
+         Exported:     {}\n\
+         App version:  {}\n\
+         SHA-256:      {}\n\n\
+         To restore it, open MotorMods, go to Backup & Restore, choose Import\n\
+         Backup and pick {} (or {}) in this folder. The app checks the\n\
+         checksum before importing and keeps a safety copy of the current data.\n\n\
+         Keep all files in this folder together.\n",
+        backup_filename,
+        marker.captured_at.as_deref().unwrap_or("unknown"),
+        marker.exported_at,
+        marker.app_version,
+        sha256,
+        PORTABLE_MARKER_FILE,
+        backup_filename
+    );
+
+    let written = (|| -> Result<(), String> {
+        fs::create_dir(&folder)
+            .map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+        let copied_path = folder.join(&backup_filename);
+        copy_file_durable(&backup_path, &copied_path).map_err(|e| {
+            describe_copy_error("Failed to export backup", &e, &backup_path, &copied_path)
+        })?;
+        write_checksum_sidecar(&copied_path, &sha256)?;
+        write_backup_manifest(&copied_path, &manifest)?;
+        fs::write(folder.join("README.txt"), readme)
+            .map_err(|e| format!("Failed to write README: {}", e))?;
+        let marker_json = serde_json::to_string_pretty(&marker).map_err(|e| e.to_string())?;
+        // The marker goes last, so a folder that has one is complete
+        fs::write(folder.join(PORTABLE_MARKER_FILE), marker_json)
+            .map_err(|e| format!("Failed to write {}: {}", PORTABLE_MARKER_FILE, e))
+    })();
+    if let Err(e) = written {
+        let _ = fs::remove_dir_all(&folder);
+        return Err(e);
+    }
+
+    Ok(folder.to_string_lossy().to_string())
+}
+
+// ============================================
+// PARQUET EXPORT
+// ============================================
+
+/// Arrow column builder chosen from a SQLite declared type
+pub(crate) enum ParquetColumn {
+    Integer(arrow_array::builder::Int64Builder),
+    Real(arrow_array::builder::Float64Builder),
+    Text(arrow_array::builder::StringBuilder),
+    Blob(arrow_array::builder::BinaryBuilder),
+}
+
+impl ParquetColumn {
+    /// Follows SQLite's column affinity rules for the declared type
+    pub(crate) fn for_declared_type(declared: &str) -> (Self, arrow_schema::DataType) {
+        use arrow_array::builder::{BinaryBuilder, Float64Builder, Int64Builder, StringBuilder};
+        use arrow_schema::DataType;
+
+        let declared = declared.to_uppercase();
+        if declared.contains("INT") {
+            (Self::Integer(Int64Builder::new()), DataType::Int64)
+        } else if ["CHAR", "CLOB", "TEXT"]
+            .iter()
+            .any(|t| declared.contains(t))
+        {
+            (Self::Text(StringBuilder::new()), DataType::Utf8)
+        } else if declared.is_empty() || declared.contains("BLOB") {
+            (Self::Blob(BinaryBuilder::new()), DataType::Binary)
+        } else {
+            (Self::Real(Float64Builder::new()), DataType::Float64)
+        }
+    }
+
+    /// Appends a value, converting across SQLite's dynamic types where it can;
+    /// values that don't fit the column become null
+    pub(crate) fn append(&mut self, value: rusqlite::types::ValueRef<'_>) {
+        use rusqlite::types::ValueRef;
+
+        match self {
+            Self::Integer(b) => b.append_option(match value {
+                ValueRef::Integer(i) => Some(i),
+                ValueRef::Real(f) if f.fract() == 0.0 => Some(f as i64),
+                ValueRef::Text(t) => std::str::from_utf8(t)
+                    .ok()
+                    .and_then(|t| t.trim().parse().ok()),
+                _ => None,
+            }),
+            Self::Real(b) => b.append_option(match value {
+                ValueRef::Integer(i) => Some(i as f64),
+                ValueRef::Real(f) => Some(f),
+                ValueRef::Text(t) => std::str::from_utf8(t)
+                    .ok()
+                    .and_then(|t| t.trim().parse().ok()),
+                _ => None,
+            }),
+            Self::Text(b) => b.append_option(match value {
+                ValueRef::Null => None,
+                ValueRef::Integer(i) => Some(i.to_string()),
+                ValueRef::Real(f) => Some(f.to_string()),
+                ValueRef::Text(t) | ValueRef::Blob(t) => {
+                    Some(String::from_utf8_lossy(t).into_owned())
+                }
+            }),
+            Self::Blob(b) => match value {
+                ValueRef::Null => b.append_null(),
+                ValueRef::Integer(i) => b.append_value(i.to_string()),
+                ValueRef::Real(f) => b.append_value(f.to_string()),
+                ValueRef::Text(t) | ValueRef::Blob(t) => b.append_value(t),
+            },
+        }
+    }
+
+    pub(crate) fn finish(&mut self) -> arrow_array::ArrayRef {
+        use std::sync::Arc;
+
+        match self {
+            Self::Integer(b) => Arc::new(b.finish()),
+            Self::Real(b) => Arc::new(b.finish()),
+            Self::Text(b) => Arc::new(b.finish()),
+            Self::Blob(b) => Arc::new(b.finish()),
+        }
+    }
+}
+
+/// Exports one table to a Parquet file, one row group per 10,000 rows
+#[tauri::command]
+pub(crate) async fn export_table_parquet(
+    app: AppHandle,
+    table_name: String,
+    destination_path: String,
+) -> Result<ParquetExportResult, String> {
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let destination = normalize_user_path(&app, &destination_path)?;
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        write_table_parquet(&db_path, &table_name, &destination)
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))
+    .and_then(|r| r);
+
+    let record = match &result {
+        Ok(r) => OperationRecord::new(
+            "export_parquet",
+            true,
+            format!(
+                "Exported {} rows of {} to {}",
+                r.rows_written, r.table, r.path
+            ),
+        ),
+        Err(e) => OperationRecord::new("export_parquet", false, e.clone()),
+    };
+    log_operation(&app, record);
+
+    result
+}
+
+pub(crate) fn write_table_parquet(
+    db_path: &Path,
+    table_name: &str,
+    destination: &Path,
+) -> Result<ParquetExportResult, String> {
+    use std::sync::Arc;
+
+    let conn = open_database(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    if table_name.starts_with("sqlite_") || !table_exists(&conn, table_name) {
+        return Err(format!("Table not found: {}", table_name));
+    }
+
+    let quoted = format!("\"{}\"", table_name.replace('"', "\"\""));
+    let mut info = conn
+        .prepare(&format!("PRAGMA table_info({})", quoted))
+        .map_err(|e| format!("Failed to read columns of {}: {}", table_name, e))?;
+    let columns: Vec<(String, String)> = info
+        .query_map([], |row| Ok((row.get(1)?, row.get(2)?)))
+        .map_err(|e| format!("Failed to read columns of {}: {}", table_name, e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut builders = Vec::with_capacity(columns.len());
+    let mut fields = Vec::with_capacity(columns.len());
+    for (name, declared) in &columns {
+        let (builder, data_type) = ParquetColumn::for_declared_type(declared);
+        builders.push(builder);
+        fields.push(arrow_schema::Field::new(name, data_type, true));
+    }
+    let schema = Arc::new(arrow_schema::Schema::new(fields));
+
+    let file = fs::File::create(destination)
+        .map_err(|e| format!("Failed to create {}: {}", destination.display(), e))?;
+    let properties = parquet::file::properties::WriterProperties::builder()
+        .set_compression(parquet::basic::Compression::SNAPPY)
+        .set_max_row_group_row_count(Some(PARQUET_ROW_GROUP_ROWS))
+        .build();
+    let mut writer = parquet::arrow::ArrowWriter::try_new(file, schema.clone(), Some(properties))
+        .map_err(|e| format!("Failed to start Parquet file: {}", e))?;
+
+    let column_list: Vec<String> = columns
+        .iter()
+        .map(|(name, _)| format!("\"{}\"", name.replace('"', "\"\"")))
+        .collect();
+    let mut select = conn
+        .prepare(&format!(
+            "SELECT {} FROM {}",
+            column_list.join(", "),
+            quoted
+        ))
+        .map_err(|e| format!("Failed to read {}: {}", table_name, e))?;
+    let mut rows = select
+        .query([])
+        .map_err(|e| format!("Failed to read {}: {}", table_name, e))?;
+
+    let mut write_batch = |builders: &mut Vec<ParquetColumn>| -> Result<(), String> {
+        let arrays: Vec<arrow_array::ArrayRef> = builders.iter_mut().map(|b| b.finish()).collect();
+        let batch = arrow_array::RecordBatch::try_new(schema.clone(), arrays)
+            .map_err(|e| format!("Failed to build row group: {}", e))?;
+        writer
+            .write(&batch)
+            .map_err(|e| format!("Failed to write Parquet data: {}", e))
+    };
+
+    let mut rows_written = 0u64;
+    let mut buffered = 0usize;
+    let outcome = loop {
+        match rows.next() {
+            Ok(Some(row)) => {
+                for (index, builder) in builders.iter_mut().enumerate() {
+                    let value = row
+                        .get_ref(index)
+                        .unwrap_or(rusqlite::types::ValueRef::Null);
+                    builder.append(value);
+                }
+                rows_written += 1;
+                buffered += 1;
+                if buffered == PARQUET_ROW_GROUP_ROWS {
+                    if let Err(e) = write_batch(&mut builders) {
+                        break Err(e);
+                    }
+                    buffered = 0;
+                }
+            }
+            Ok(None) => break Ok(()),
+            Err(e) => break Err(format!("Failed to read {}: {}", table_name, e)),
+        }
+    };
+    let outcome = outcome.and_then(|_| {
+        if buffered > 0 {
+            write_batch(&mut builders)?;
+        }
+        Ok(())
+    });
+
+    let closed = outcome.and_then(|_| {
+        writer
+            .close()
+            .map(|_| ())
+            .map_err(|e| format!("Failed to finish Parquet file: {}", e))
+    });
+    if let Err(e) = closed {
+        let _ = fs::remove_file(destination);
+        return Err(e);
+    }
+
+    let file_size = fs::metadata(destination)
+        .map_err(|e| format!("Failed to read exported file: {}", e))?
+        .len();
+
+    Ok(ParquetExportResult {
+        table: table_name.to_string(),
+        path: destination.to_string_lossy().to_string(),
+        rows_written,
+        file_size,
+    })
+}
+
+// ============================================
+// CSV TABLE EXPORT
+// ============================================
+
+/// Exports one table to CSV with a header row. With rows_per_file set, the rows
+/// are split across <name>_001.csv, <name>_002.csv, ... each with its own header,
+/// so spreadsheet row limits aren't hit. Rows are streamed, not buffered.
+#[tauri::command]
+pub(crate) async fn export_table_csv(
+    app: AppHandle,
+    table_name: String,
+    destination_path: String,
+    rows_per_file: Option<u64>,
+) -> Result<CsvExportResult, String> {
+    if rows_per_file == Some(0) {
+        return Err("rows_per_file must be at least 1".to_string());
+    }
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let destination = normalize_user_path(&app, &destination_path)?;
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        write_table_csv(&db_path, &table_name, &destination, rows_per_file)
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))
+    .and_then(|r| r);
+
+    let record = match &result {
+        Ok(r) => OperationRecord::new(
+            "export_csv",
+            true,
+            format!(
+                "Exported {} rows of {} to {} file(s)",
+                r.total_rows,
+                r.table,
+                r.files.len()
+            ),
+        ),
+        Err(e) => OperationRecord::new("export_csv", false, e.clone()),
+    };
+    log_operation(&app, record);
+
+    result
+}
+
+/// <stem>_NNN.<ext> next to the requested destination
+pub(crate) fn numbered_export_path(destination: &Path, index: usize) -> PathBuf {
+    let stem = destination
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "export".to_string());
+    let extension = destination
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_else(|| "csv".to_string());
+    destination.with_file_name(format!("{}_{:03}.{}", stem, index, extension))
+}
+
+pub(crate) fn write_table_csv(
+    db_path: &Path,
+    table_name: &str,
+    destination: &Path,
+    rows_per_file: Option<u64>,
+) -> Result<CsvExportResult, String> {
+    use rusqlite::types::ValueRef;
+
+    let conn = open_database(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    if table_name.starts_with("sqlite_") || !table_exists(&conn, table_name) {
+        return Err(format!("Table not found: {}", table_name));
+    }
+
+    let quoted = format!("\"{}\"", table_name.replace('"', "\"\""));
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM {}", quoted))
+        .map_err(|e| format!("Failed to read {}: {}", table_name, e))?;
+    let header: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+
+    let open_writer = |path: &Path| -> Result<csv::Writer<fs::File>, String> {
+        let mut writer = csv::Writer::from_path(path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        writer
+            .write_record(&header)
+            .map_err(|e| format!("Failed to write CSV: {}", e))?;
+        Ok(writer)
+    };
+    let path_for = |index: usize| match rows_per_file {
+        Some(_) => numbered_export_path(destination, index),
+        None => destination.to_path_buf(),
+    };
+
+    let mut files = vec![path_for(1)];
+    let mut writer = open_writer(&files[0])?;
+    let mut rows_in_file: u64 = 0;
+    let mut total_rows: u64 = 0;
+    let mut rows = stmt
+        .query([])
+        .map_err(|e| format!("Failed to read {}: {}", table_name, e))?;
+    while let Some(row) = rows
+        .next()
+        .map_err(|e| format!("Failed to read {}: {}", table_name, e))?
+    {
+        if rows_per_file.is_some_and(|limit| rows_in_file >= limit) {
+            writer
+                .flush()
+                .map_err(|e| format!("Failed to write CSV: {}", e))?;
+            let next = path_for(files.len() + 1);
+            writer = open_writer(&next)?;
+            files.push(next);
+            rows_in_file = 0;
+        }
+
+        let mut record = csv::StringRecord::with_capacity(64, header.len());
+        for index in 0..header.len() {
+            let value = row
+                .get_ref(index)
+                .map_err(|e| format!("Failed to read {}: {}", table_name, e))?;
+            match value {
+                ValueRef::Null => record.push_field(""),
+                ValueRef::Integer(v) => record.push_field(&v.to_string()),
+                ValueRef::Real(v) => record.push_field(&v.to_string()),
+                ValueRef::Text(v) => record.push_field(&String::from_utf8_lossy(v)),
+                ValueRef::Blob(v) => {
+                    record.push_field(&v.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+                }
+            }
+        }
+        writer
+            .write_record(&record)
+            .map_err(|e| format!("Failed to write CSV: {}", e))?;
+        rows_in_file += 1;
+        total_rows += 1;
+    }
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to write CSV: {}", e))?;
+
+    Ok(CsvExportResult {
+        table: table_name.to_string(),
+        files: files
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect(),
+        total_rows,
+    })
+}
+
+// ============================================
+// SQL DUMP (archival export/import)
+// ============================================
+
+/// First statements of every dump, matching sqlite3's .dump output
+pub(crate) const SQL_DUMP_HEADER: &str = "PRAGMA foreign_keys=OFF;\nBEGIN TRANSACTION;\n";
+
+/// Writes one value as a SQL literal that reads back with the same type
+pub(crate) fn write_sql_literal(
+    out: &mut (impl Write + ?Sized),
+    value: rusqlite::types::ValueRef<'_>,
+) -> std::io::Result<()> {
+    use rusqlite::types::ValueRef;
+
+    match value {
+        ValueRef::Null => out.write_all(b"NULL"),
+        ValueRef::Integer(v) => write!(out, "{}", v),
+        // NaN is stored as NULL by SQLite; 1e999 is how sqlite3 spells infinity
+        ValueRef::Real(v) if v.is_nan() => out.write_all(b"NULL"),
+        ValueRef::Real(v) if v.is_infinite() => {
+            out.write_all(if v > 0.0 { b"1e999" } else { b"-1e999" })
+        }
+        // {:?} keeps a trailing .0 so whole numbers stay REAL
+        ValueRef::Real(v) => write!(out, "{:?}", v),
+        ValueRef::Text(bytes) => {
+            write!(
+                out,
+                "'{}'",
+                String::from_utf8_lossy(bytes).replace('\'', "''")
+            )
+        }
+        ValueRef::Blob(bytes) => {
+            out.write_all(b"X'")?;
+            for byte in bytes {
+                write!(out, "{:02X}", byte)?;
+            }
+            out.write_all(b"'")
+        }
+    }
+}
+
+/// Streams schema and rows of `conn` as a SQL script, tables first so
+/// indexes, triggers and views are created after their data is in place
+pub(crate) fn write_sql_dump(conn: &Connection, out: &mut impl Write) -> Result<(), String> {
+    let io_err = |e: std::io::Error| format!("Failed to write dump: {}", e);
+
+    let mut objects_stmt = conn
+        .prepare(
+            "SELECT type, name, sql FROM sqlite_master
+             WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%'
+             ORDER BY rowid",
+        )
+        .map_err(|e| format!("Failed to read schema: {}", e))?;
+    let objects: Vec<(String, String, String)> = objects_stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| format!("Failed to read schema: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    out.write_all(SQL_DUMP_HEADER.as_bytes()).map_err(io_err)?;
+
+    let dump_rows = |table: &str, out: &mut dyn Write| -> Result<(), String> {
+        let quoted = format!("\"{}\"", table.replace('"', "\"\""));
+        let mut stmt = conn
+            .prepare(&format!("SELECT * FROM {}", quoted))
+            .map_err(|e| format!("Failed to read {}: {}", table, e))?;
+        let column_count = stmt.column_count();
+        let mut rows = stmt
+            .query([])
+            .map_err(|e| format!("Failed to read {}: {}", table, e))?;
+        while let Some(row) = rows
+            .next()
+            .map_err(|e| format!("Failed to read {}: {}", table, e))?
+        {
+            write!(out, "INSERT INTO {} VALUES(", quoted).map_err(io_err)?;
+            for i in 0..column_count {
+                if i > 0 {
+                    out.write_all(b",").map_err(io_err)?;
+                }
+                let value = row
+                    .get_ref(i)
+                    .map_err(|e| format!("Failed to read {}: {}", table, e))?;
+                write_sql_literal(&mut *out, value).map_err(io_err)?;
+            }
+            out.write_all(b");\n").map_err(io_err)?;
+        }
+        Ok(())
+    };
+
+    for (kind, name, sql) in objects.iter().filter(|(kind, _, _)| kind == "table") {
+        // Virtual tables can't be recreated with plain statements
+        if sql.to_uppercase().starts_with("CREATE VIRTUAL TABLE") {
+            log::warn!("Skipping virtual {} {} in SQL dump", kind, name);
+            continue;
+        }
+        writeln!(out, "{};", sql).map_err(io_err)?;
+        dump_rows(name, out)?;
+    }
+
+    // AUTOINCREMENT counters live in sqlite_sequence, created with the first such table
+    if table_exists(conn, "sqlite_sequence") {
+        out.write_all(b"DELETE FROM sqlite_sequence;\n")
+            .map_err(io_err)?;
+        dump_rows("sqlite_sequence", out)?;
+    }
+
+    for (_, _, sql) in objects.iter().filter(|(kind, _, _)| kind != "table") {
+        writeln!(out, "{};", sql).map_err(io_err)?;
+    }
+
+    // Migrations key off user_version, so it travels with the dump
+    let user_version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read user_version: {}", e))?;
+    writeln!(out, "PRAGMA user_version={};", user_version).map_err(io_err)?;
+    out.write_all(b"COMMIT;\n").map_err(io_err)?;
+    Ok(())
+}
+
+/// Checks a dump starts with the BEGIN TRANSACTION header and wasn't cut short
+pub(crate) fn validate_sql_dump(script: &str) -> Result<(), String> {
+    let mut lines = script
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty());
+    let mut first = lines.next();
+    if first == Some("PRAGMA foreign_keys=OFF;") {
+        first = lines.next();
+    }
+    if first != Some("BEGIN TRANSACTION;") {
+        return Err("Not a SQL dump: expected a BEGIN TRANSACTION header".to_string());
+    }
+    if !script.trim_end().ends_with("COMMIT;") {
+        return Err("The SQL dump is incomplete: it doesn't end with COMMIT".to_string());
+    }
+    Ok(())
+}
+
+/// Writes the live database as a schema + data SQL script (like sqlite3 .dump),
+/// streamed row by row so large databases don't need to fit in memory
+#[tauri::command]
+pub(crate) fn export_sql_dump(app: AppHandle, destination_path: String) -> Result<String, String> {
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+    let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let _operation = begin_operation(&app, "export", false);
+
+    let destination = normalize_user_path(&app, &destination_path)?;
+    let file = fs::File::create(&destination)
+        .map_err(|e| format!("Failed to create {}: {}", destination.display(), e))?;
+    let mut out = std::io::BufWriter::new(file);
+
+    let result = write_sql_dump(&conn, &mut out).and_then(|_| {
+        out.into_inner()
+            .map_err(|e| format!("Failed to write dump: {}", e.error()))?
+            .sync_all()
+            .map_err(|e| format!("Failed to write dump: {}", e))
+    });
+    if let Err(e) = result {
+        // A partial dump looks valid up to the point it stops, so don't leave one
+        let _ = fs::remove_file(&destination);
+        return Err(e);
+    }
+
+    Ok(format!("SQL dump exported to {}", destination.display()))
+}
+
+/// Replaces the live database with one rebuilt from a SQL dump. The dump is
+/// loaded into a fresh database first, so a bad script leaves the live one alone.
+#[tauri::command]
+pub(crate) fn import_sql_dump(app: AppHandle, source_path: String) -> Result<String, String> {
+    require_primary_instance(&app)?;
+    let source_path = normalize_user_path(&app, &source_path)?
+        .to_string_lossy()
+        .to_string();
+    let script = fs::read_to_string(&source_path)
+        .map_err(|e| format!("Failed to read {}: {}", source_path, e))?;
+    validate_sql_dump(&script)?;
+
+    let db_state = app.state::<DatabaseState>();
+    let _busy = db_state.busy.try_lock().map_err(|_| {
+        "A database operation is in progress. Try again when it finishes.".to_string()
+    })?;
+
+    let db_path = get_db_path(&app)?;
+    let _operation = begin_operation(&app, "import", false);
+    let fresh_path = unique_temp_path("sql_import", "db");
+    let built = (|| -> Result<(), String> {
+        let conn = Connection::open(&fresh_path)
+            .map_err(|e| format!("Failed to create database: {}", e))?;
+        conn.execute_batch(&script)
+            .map_err(|e| format!("Failed to run SQL dump: {}", e))?;
+        match run_integrity_check(&conn).as_str() {
+            "ok" => Ok(()),
+            problem => Err(format!(
+                "Imported database failed integrity check: {}",
+                problem
+            )),
+        }
+    })();
+    if let Err(e) = built {
+        let _ = fs::remove_file(&fresh_path);
+        return Err(e);
+    }
+
+    let safety_filename = safety_backup_filename("import");
+    if db_path.exists() {
+        let safety_path = get_backups_dir(&app)?.join(&safety_filename);
+        if let Err(e) = snapshot_database(&db_path, &safety_path) {
+            let _ = fs::remove_file(&fresh_path);
+            return Err(format!("Failed to create safety backup: {}", e));
+        }
+        record_safety_origin(&safety_path, "import_sql_dump", Some(&source_path));
+    }
+
+    if let Err(e) = remove_stale_sidecars(&app, &db_path) {
+        log::warn!("{}", e);
+    }
+    let copied = copy_file_durable(&fresh_path, &db_path)
+        .map_err(|e| describe_copy_error("Failed to import SQL dump", &e, &fresh_path, &db_path));
+    let _ = fs::remove_file(&fresh_path);
+    copied?;
+
+    log_operation(
+        &app,
+        OperationRecord::new(
+            "import_sql_dump",
+            true,
+            format!(
+                "Imported {}. Safety backup created: {}",
+                source_path, safety_filename
+            ),
+        ),
+    );
+
+    Ok(format!(
+        "SQL dump imported. Safety backup created: {}",
+        safety_filename
+    ))
+}
+
+// ============================================
+// SUBSET EXPORT
+// ============================================
+
+/// Selects the starting rows of export_subset_db: `table`.`column` = `value`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubsetFilter {
+    pub table: String,
+    pub column: String,
+    /// String, number or boolean
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableExportCount {
+    pub table: String,
+    pub exported: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubsetExportResult {
+    pub destination_path: String,
+    /// Every table in the schema, including those with no matching rows
+    pub tables: Vec<TableExportCount>,
+}
+
+/// One foreign key: (child column, parent column) pairs from child to parent
+pub(crate) struct SubsetForeignKey {
+    pub(crate) child: String,
+    pub(crate) parent: String,
+    pub(crate) columns: Vec<(String, String)>,
+}
+
+/// Foreign keys between `tables`; a key without target columns points at the
+/// parent's primary key
+pub(crate) fn read_foreign_keys(
+    conn: &Connection,
+    tables: &[String],
+) -> Result<Vec<SubsetForeignKey>, String> {
+    let read_err = |e: rusqlite::Error| format!("Failed to read foreign keys: {}", e);
+    let mut keys: Vec<SubsetForeignKey> = Vec::new();
+    for child in tables {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, \"table\", \"from\", \"to\" FROM pragma_foreign_key_list(?1)
+                 ORDER BY id, seq",
+            )
+            .map_err(read_err)?;
+        let rows: Vec<(i64, String, String, Option<String>)> = stmt
+            .query_map(params![child], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .map_err(read_err)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut current_id = None;
+        for (id, parent, from, to) in rows {
+            if !tables.contains(&parent) {
+                continue;
+            }
+            if current_id != Some(id) {
+                current_id = Some(id);
+                keys.push(SubsetForeignKey {
+                    child: child.clone(),
+                    parent: parent.clone(),
+                    columns: Vec::new(),
+                });
+            }
+            let to = match to {
+                Some(to) => to,
+                None => conn
+                    .query_row(
+                        "SELECT name FROM pragma_table_info(?1) WHERE pk > 0 ORDER BY pk",
+                        params![parent],
+                        |row| row.get(0),
+                    )
+                    .map_err(read_err)?,
+            };
+            if let Some(key) = keys.last_mut() {
+                key.columns.push((from, to));
+            }
+        }
+    }
+    Ok(keys)
+}
+
+/// Writes a new database at `destination_path` with the full schema but only
+/// the rows matching `filter`, the rows that reference them (following
+/// foreign keys down, e.g. a customer's invoices and their items) and the rows
+/// those reference (e.g. the products on those items), so the copy is
+/// self-consistent.
+#[tauri::command]
+pub(crate) fn export_subset_db(
+    app: AppHandle,
+    filter: SubsetFilter,
+    destination_path: String,
+) -> Result<SubsetExportResult, String> {
+    use rusqlite::types::Value;
+
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+    let source = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let schema = read_live_schema(&source)?;
+
+    let table = schema
+        .tables
+        .iter()
+        .find(|t| t.name == filter.table)
+        .ok_or_else(|| format!("Unknown table: {}", filter.table))?;
+    if !table.columns.iter().any(|c| c.name == filter.column) {
+        return Err(format!(
+            "Unknown column {} in table {}",
+            filter.column, filter.table
+        ));
+    }
+    let value = match &filter.value {
+        serde_json::Value::String(text) => Value::Text(text.clone()),
+        serde_json::Value::Bool(flag) => Value::Integer(*flag as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        _ => return Err("Filter value must be a string, number or boolean".to_string()),
+    };
+
+    let tables: Vec<String> = schema.tables.iter().map(|t| t.name.clone()).collect();
+    let foreign_keys = read_foreign_keys(&source, &tables)?;
+    let mut objects_stmt = source
+        .prepare(
+            "SELECT type, sql FROM sqlite_master
+             WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%'
+             ORDER BY rowid",
+        )
+        .map_err(|e| format!("Failed to read schema: {}", e))?;
+    let objects: Vec<(String, String)> = objects_stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| format!("Failed to read schema: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    let user_version: i64 = source
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read user_version: {}", e))?;
+    drop(objects_stmt);
+    drop(source);
+
+    let destination = normalize_user_path(&app, &destination_path)?;
+    if destination.exists() {
+        return Err(format!("{} already exists", destination.display()));
+    }
+
+    let quote = |name: &str| format!("\"{}\"", name.replace('"', "\"\""));
+    // Picked rows per table live in temp tables named by index, so no quoting games
+    let selection = |name: &str| {
+        format!(
+            "temp.subset_{}",
+            tables.iter().position(|t| t == name).unwrap_or_default()
+        )
+    };
+
+    let exported = (|| -> Result<Vec<TableExportCount>, String> {
+        let mut conn = Connection::open(&destination)
+            .map_err(|e| format!("Failed to create {}: {}", destination.display(), e))?;
+        conn.execute(
+            "ATTACH DATABASE ?1 AS src",
+            params![db_path.to_string_lossy().to_string()],
+        )
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let run = |sql: &str| {
+            tx.execute(sql, [])
+                .map_err(|e| format!("Subset export failed: {}", e))
+        };
+
+        for name in &tables {
+            run(&format!(
+                "CREATE TEMP TABLE {} (rid INTEGER PRIMARY KEY)",
+                selection(name).trim_start_matches("temp.")
+            ))?;
+        }
+        tx.execute(
+            &format!(
+                "INSERT INTO {} SELECT rowid FROM src.{} WHERE {} = ?1",
+                selection(&filter.table),
+                quote(&filter.table),
+                quote(&filter.column)
+            ),
+            params![value],
+        )
+        .map_err(|e| format!("Subset export failed: {}", e))?;
+
+        // Down to referencing rows until nothing changes, then up to referenced ones
+        for downward in [true, false] {
+            loop {
+                let mut added = 0;
+                for key in &foreign_keys {
+                    let join = key
+                        .columns
+                        .iter()
+                        .map(|(from, to)| format!("c.{} = p.{}", quote(from), quote(to)))
+                        .collect::<Vec<_>>()
+                        .join(" AND ");
+                    let (target, picked, source_alias, picked_alias) = if downward {
+                        (&key.child, &key.parent, "c", "p")
+                    } else {
+                        (&key.parent, &key.child, "p", "c")
+                    };
+                    added += run(&format!(
+                        "INSERT OR IGNORE INTO {} SELECT {}.rowid FROM src.{} c JOIN src.{} p ON {}
+                         WHERE {}.rowid IN (SELECT rid FROM {})",
+                        selection(target),
+                        source_alias,
+                        quote(&key.child),
+                        quote(&key.parent),
+                        join,
+                        picked_alias,
+                        selection(picked)
+                    ))?;
+                }
+                if added == 0 {
+                    break;
+                }
+            }
+        }
+
+        // Tables first so triggers don't fire on the copied rows
+        for (_, sql) in objects.iter().filter(|(kind, _)| kind == "table") {
+            run(sql)?;
+        }
+        let mut counts = Vec::with_capacity(tables.len());
+        for name in &tables {
+            let exported = run(&format!(
+                "INSERT INTO main.{0} SELECT * FROM src.{0} WHERE rowid IN (SELECT rid FROM {1})",
+                quote(name),
+                selection(name)
+            ))?;
+            counts.push(TableExportCount {
+                table: name.clone(),
+                exported,
+            });
+        }
+        for (_, sql) in objects.iter().filter(|(kind, _)| kind != "table") {
+            run(sql)?;
+        }
+        run(&format!("PRAGMA user_version = {}", user_version))?;
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit subset export: {}", e))?;
+        conn.execute_batch("DETACH DATABASE src")
+            .map_err(|e| format!("Failed to close database: {}", e))?;
+        Ok(counts)
+    })();
+
+    match exported {
+        Ok(tables) => Ok(SubsetExportResult {
+            destination_path: destination.to_string_lossy().to_string(),
+            tables,
+        }),
+        Err(e) => {
+            let _ = fs::remove_file(&destination);
+            Err(e)
+        }
+    }
+}
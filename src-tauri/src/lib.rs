@@ -1,71 +1,2071 @@
-use chrono::Local;
-use rusqlite::{Connection, params};
+use aes_gcm::aead::stream::{DecryptorBE32, EncryptorBE32};
+use aes_gcm::{Aes256Gcm, Key, KeyInit};
+use chrono::{Datelike, Local};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
-use tauri::{AppHandle, Manager};
+use std::io::{Read, Seek, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+#[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_dialog::DialogExt;
+
+mod automation;
+mod backup;
+mod export;
+mod printing;
+mod restore;
+
+use automation::*;
+use backup::*;
+use export::*;
+use printing::*;
+use restore::*;
 
 // ============================================
 // BACKUP/RESTORE TYPES
 // ============================================
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct BackupResult {
-    pub filename: String,
-    pub path: String,
-    pub file_size: u64,
-    pub created_at: String,
+/// Durability profile and the pragma values a connection ends up with
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DurabilityProfile {
+    /// None until set_durability_profile has been called; SQLite defaults apply
+    pub profile: Option<String>,
+    pub synchronous: String,
+    pub journal_mode: String,
+    pub warning: Option<String>,
+}
+
+/// A run of SQLITE_BUSY/SQLITE_LOCKED retries that eventually got through
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockRetryEvent {
+    /// When the retries started
+    pub at: String,
+    pub operation: String,
+    pub retries: u32,
+    pub waited_ms: u64,
+}
+
+/// Response from get_lock_diagnostics
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LockDiagnostics {
+    pub journal_mode: String,
+    pub wal_present: bool,
+    pub wal_size: u64,
+    /// Applied to every connection the backend opens and to the frontend's
+    pub busy_timeout_ms: u64,
+    /// Newest first
+    pub recent_retries: Vec<LockRetryEvent>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForeignKeyViolation {
+    pub table: String,
+    pub rowid: Option<i64>,
+    pub parent: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// First line of PRAGMA integrity_check ("ok" when healthy)
+    pub integrity: String,
+    pub foreign_keys_enabled: bool,
+    pub foreign_key_violation_count: usize,
+    /// Up to the first 100 violations from PRAGMA foreign_key_check
+    pub foreign_key_violations: Vec<ForeignKeyViolation>,
+}
+
+/// SQLite settings and page counts for the performance panel
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SqliteRuntimeStats {
+    /// Pages when positive, KiB when negative (SQLite's convention)
+    pub cache_size: i64,
+    pub page_size: i64,
+    pub page_count: i64,
+    pub freelist_count: i64,
+    pub journal_mode: String,
+    /// 0 = OFF, 1 = NORMAL, 2 = FULL, 3 = EXTRA
+    pub synchronous: i64,
+    pub compile_options: Vec<String>,
+}
+
+/// Tables and columns the app expects, e.g. the embedded expected_schema.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaDefinition {
+    pub tables: Vec<TableDefinition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableDefinition {
+    pub name: String,
+    pub columns: Vec<ColumnDefinition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnDefinition {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub column_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnRef {
+    pub table: String,
+    pub column: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnTypeMismatch {
+    pub table: String,
+    pub column: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SchemaDiff {
+    pub matches: bool,
+    pub missing_tables: Vec<String>,
+    pub extra_tables: Vec<String>,
+    pub missing_columns: Vec<ColumnRef>,
+    pub extra_columns: Vec<ColumnRef>,
+    pub type_mismatches: Vec<ColumnTypeMismatch>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalConsistency {
+    pub database_exists: bool,
+    pub wal_present: bool,
+    pub wal_size: u64,
+    pub shm_present: bool,
+    /// None when there is no (or an empty) -wal file to check
+    pub wal_header_valid: Option<bool>,
+    pub wal_page_size: Option<u32>,
+    pub database_page_size: Option<u32>,
+    /// Checkpoint sequence from the -wal header
+    pub wal_checkpoint_seq: Option<u32>,
+    /// Whether the -shm index was built for this -wal (same salts and page
+    /// size); None when there is no initialized index to compare against
+    pub index_matches_wal: Option<bool>,
+    /// Frames whose salts match the WAL header, i.e. ones SQLite would replay
+    pub valid_frames: u64,
+    pub consistent: bool,
+    /// The sidecars can't belong to a live connection and are safe to remove
+    pub stale: bool,
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableRowCount {
+    pub table: String,
+    pub count: i64,
+    /// True when the count came from sqlite_stat1 rather than COUNT(*)
+    pub approximate: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepairProgress {
+    pub current: usize,
+    pub total: usize,
+    pub table: String,
+}
+
+/// A table or index that lost data (or was skipped) during repair
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RepairLoss {
+    pub object: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RepairResult {
+    /// False when the database was already healthy and left untouched
+    pub repaired: bool,
+    pub integrity_before: String,
+    pub safety_backup: Option<String>,
+    pub rows_recovered: i64,
+    pub dropped: Vec<RepairLoss>,
+    pub message: String,
+}
+
+/// Which database file the app's own commands operate on
+struct DatabaseState {
+    /// File stem under app_config_dir, e.g. "motormods" for motormods.db
+    active_name: Mutex<String>,
+    /// Held by commands that write to the database inside a transaction
+    busy: Mutex<()>,
+    /// Set while begin_trial_restore has the app pointed at a trial copy
+    trial: Mutex<Option<TrialRestore>>,
+}
+
+impl Default for DatabaseState {
+    fn default() -> Self {
+        Self {
+            active_name: Mutex::new(DEFAULT_DB_NAME.to_string()),
+            busy: Mutex::new(()),
+            trial: Mutex::new(None),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseSwitched {
+    pub name: String,
+    pub path: String,
+    /// Connection string for tauri-plugin-sql, as returned by get_database_url
+    pub connection: String,
+}
+
+/// A resolved configuration value and where it came from:
+/// "default", "settings", "env" or "platform"
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigValue {
+    pub value: String,
+    pub source: String,
+}
+
+/// What get_effective_config resolved; never includes passphrases or keys
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EffectiveConfig {
+    pub database_path: ConfigValue,
+    pub backups_dir: ConfigValue,
+    pub printer: ConfigValue,
+    pub print_timeout_seconds: ConfigValue,
+    pub backup_retention_days: ConfigValue,
+    pub backup_max_dir_bytes: ConfigValue,
+    pub backup_keep_count: ConfigValue,
+    pub backup_cooldown_seconds: ConfigValue,
+    pub auto_backup_enabled: ConfigValue,
+    pub auto_backup_paused: ConfigValue,
+    pub auto_backup_time: ConfigValue,
+    pub foreign_keys_enabled: ConfigValue,
+}
+
+/// Last successful backup_database run, used for the repeat-press cooldown
+#[derive(Default)]
+struct BackupState {
+    last_success: Mutex<Option<(Instant, BackupResult)>>,
+    /// Set once the backup-on-exit run has started, so the next close goes through
+    shutdown_backup_started: AtomicBool,
+}
+
+/// In-flight backup/restore/import/export operations, for list_active_operations
+#[derive(Default)]
+struct OperationRegistry {
+    next_id: AtomicU64,
+    active: Mutex<HashMap<u64, ActiveOperationEntry>>,
+}
+
+struct ActiveOperationEntry {
+    operation: String,
+    started_at: String,
+    progress_percent: Option<f64>,
+    /// Present when the operation checks for cancel_backup_operation
+    cancel: Option<Arc<AtomicBool>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveOperation {
+    pub id: u64,
+    /// "backup", "restore", "import" or "export"
+    pub operation: String,
+    pub started_at: String,
+    /// None when the operation has no measurable progress
+    pub progress_percent: Option<f64>,
+    pub cancellable: bool,
+}
+
+/// Registry entry for a running operation; dropping it removes the entry
+struct OperationGuard {
+    app: AppHandle,
+    id: u64,
+    cancel: Arc<AtomicBool>,
+}
+
+impl OperationGuard {
+    fn set_progress(&self, percent: f64) {
+        let registry = self.app.state::<OperationRegistry>();
+        let mut active = registry.active.lock().unwrap();
+        if let Some(entry) = active.get_mut(&self.id) {
+            entry.progress_percent = Some((percent.clamp(0.0, 100.0) * 10.0).round() / 10.0);
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        let registry = self.app.state::<OperationRegistry>();
+        registry.active.lock().unwrap().remove(&self.id);
+    }
+}
+
+/// Advisory lock on instance.lock in the app data directory, taken at startup so a
+/// second copy of the app can tell it isn't the one managing the database
+#[derive(Default)]
+struct InstanceLock {
+    /// Held open for the life of the process; the OS releases the lock on exit
+    file: Mutex<Option<fs::File>>,
+    secondary: AtomicBool,
+}
+
+/// One entry in the operation history log (operation_history.jsonl)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationRecord {
+    pub timestamp: String,
+    pub operation: String,
+    pub success: bool,
+    pub details: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub throughput_mbps: Option<f64>,
+    /// Size of the live database file when the operation ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database_size: Option<u64>,
+}
+
+impl OperationRecord {
+    fn new(operation: &str, success: bool, details: impl Into<String>) -> Self {
+        Self {
+            timestamp: Local::now().to_rfc3339(),
+            operation: operation.to_string(),
+            success,
+            details: details.into(),
+            filename: None,
+            duration_ms: None,
+            throughput_mbps: None,
+            database_size: None,
+        }
+    }
+
+    fn with_filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    fn with_throughput(mut self, duration_ms: u64, throughput_mbps: f64) -> Self {
+        self.duration_ms = Some(duration_ms);
+        self.throughput_mbps = Some(throughput_mbps);
+        self
+    }
+
+    fn with_database_size(mut self, database_size: Option<u64>) -> Self {
+        self.database_size = database_size;
+        self
+    }
+}
+
+/// One database size sample for get_database_growth_trend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrowthPoint {
+    pub timestamp: String,
+    pub epoch_ms: i64,
+    pub size_bytes: u64,
+    /// "history" (recorded by a backup), "backup" (a backup file's size) or "current"
+    pub source: String,
+}
+
+/// Also the payload of the "database-growth-alert" event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseGrowthTrend {
+    /// Oldest first
+    pub points: Vec<GrowthPoint>,
+    /// Over the last 7 days; None without two samples far enough apart
+    pub growth_bytes_per_day: Option<f64>,
+    pub threshold_bytes_per_day: u64,
+    pub alert: bool,
+}
+
+const DEFAULT_DB_NAME: &str = "motormods";
+
+/// Schema this app version expects, kept in step with the frontend's initDatabase
+const EXPECTED_SCHEMA_JSON: &str = include_str!("../schema/expected_schema.json");
+
+// Tables to restore in order (respecting foreign key dependencies)
+const DATA_TABLES: &[&str] = &[
+    "products",
+    "invoices", 
+    "invoice_items",
+    "settings",
+    "stock_adjustments",
+    "sales_returns",
+    "return_items",
+    "backup_log",
+    "users",
+];
+
+// backup_database(compress) gzips the finished .db into <name>.db.gz
+const COMPRESSED_BACKUP_SUFFIX: &str = ".db.gz";
+
+const CHECKSUM_SIDECAR_EXT: &str = "sha256";
+const MANIFEST_SIDECAR_EXT: &str = "json";
+
+const SQLITE_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+
+// -wal file header: magic (low bit = checksum byte order), version, page size,
+// checkpoint seq, two salts, two checksums
+const WAL_HEADER_LEN: usize = 32;
+const WAL_MAGIC_LE: u32 = 0x377f_0682;
+const WAL_MAGIC_BE: u32 = 0x377f_0683;
+// -shm wal-index header, stored twice in native byte order: version, unused,
+// change counter, isInit, checksum order, page size, mxFrame, page count,
+// frame checksum, salts, header checksum
+const WAL_INDEX_HEADER_LEN: usize = 48;
+const WAL_INDEX_VERSION: u32 = 3_007_000;
+const GZIP_MAGIC: &[u8; 2] = &[0x1f, 0x8b];
+
+// Safety backups are created automatically before restore/import operations
+const SAFETY_BACKUP_PREFIXES: &[&str] = &[
+    "pre_restore_safety_",
+    "pre_import_safety_",
+    "pre_reset_safety_",
+    "pre_repair_safety_",
+    "pre_migration_safety_",
+    "pre_sync_safety_",
+];
+
+// Token the caller must echo back to confirm a destructive database reset
+const RESET_CONFIRM_TOKEN: &str = "RESET";
+
+const FOREIGN_KEYS_KEY: &str = "foreign_keys_enabled";
+
+// Settings key for the durability preset chosen with set_durability_profile
+const DURABILITY_PROFILE_KEY: &str = "durability_profile";
+// (profile, synchronous, journal_mode)
+const DURABILITY_PRESETS: &[(&str, &str, &str)] = &[
+    ("safe", "FULL", "WAL"),
+    ("balanced", "NORMAL", "WAL"),
+    ("fast", "OFF", "WAL"),
+];
+// Settings key for PRAGMA busy_timeout; the default matches rusqlite's own
+const BUSY_TIMEOUT_KEY: &str = "busy_timeout_ms";
+const DEFAULT_BUSY_TIMEOUT_MS: u64 = 5000;
+const MAX_BUSY_TIMEOUT_MS: u32 = 600_000;
+// Lock retry events kept in memory for get_lock_diagnostics
+const MAX_LOCK_RETRY_EVENTS: usize = 50;
+const FAST_DURABILITY_WARNING: &str = "The fast profile turns off syncing to disk: a power cut or crash can lose recent sales or corrupt the database. Keep regular backups.";
+const MAX_REPORTED_FK_VIOLATIONS: usize = 100;
+
+const OPERATION_HISTORY_FILE: &str = "operation_history.jsonl";
+const SCHEDULED_BACKUPS_FILE: &str = "scheduled_backups.json";
+// Kept outside the database because the settings table lives in whichever database is active
+const ACTIVE_DATABASE_FILE: &str = "active_database";
+// Locked rather than the database itself: on Windows a whole-file lock would
+// block SQLite's own byte-range locks
+const INSTANCE_LOCK_FILE: &str = "instance.lock";
+
+const LAST_RESTORE_FROM_META_KEY: &str = "last_restore_from";
+const LAST_RESTORE_AT_META_KEY: &str = "last_restore_at";
+
+// Seconds after a successful backup during which repeat presses are ignored
+const BACKUP_COOLDOWN_KEY: &str = "backup_cooldown_seconds";
+const DEFAULT_BACKUP_COOLDOWN_SECS: u64 = 10;
+
+// Extensions offered when picking a database to import; the header decides validity
+const SQLITE_FILE_EXTENSIONS: &[&str] = &["db", "sqlite", "sqlite3", "db3"];
+// Extension of .db.enc and .db.gz.enc backups, offered as a separate filter
+const ENCRYPTED_FILE_EXTENSIONS: &[&str] = &["enc"];
+
+// Database name used by begin_trial_restore for the trial copy
+const TRIAL_DB_NAME: &str = "trial_restore";
+
+// Every temp file the app creates starts with this (see unique_temp_path)
+const TEMP_FILE_PREFIX: &str = "motormods_";
+
+// Settings key for how long the backups folder must be quiet before
+// "backups-changed" is emitted; synced folders produce bursts of changes
+const BACKUPS_WATCH_DEBOUNCE_KEY: &str = "backups_watch_debounce_ms";
+const DEFAULT_BACKUPS_WATCH_DEBOUNCE_MS: u64 = 500;
+const BACKUPS_WATCH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Program used by open_backup_external instead of the OS default
+const EXTERNAL_SQLITE_TOOL_KEY: &str = "external_sqlite_tool";
+
+/// When set (bytes), enforce_max_backup_size runs after every backup
+const BACKUP_MAX_DIR_BYTES_KEY: &str = "backup_max_dir_bytes";
+
+/// When set, prune_backups keeps this many backups after every backup_database
+const BACKUP_KEEP_COUNT_KEY: &str = "backup_keep_count";
+/// Safety backups are counted separately so pruning normal backups never
+/// takes the recovery points of a restore chain with them
+const SAFETY_BACKUP_KEEP_COUNT_KEY: &str = "safety_backup_keep_count";
+const DEFAULT_SAFETY_BACKUP_KEEP_COUNT: usize = 5;
+
+// Backup copy pacing: optional MB/s cap, pages per step, progress event rate
+const BACKUP_RATE_LIMIT_KEY: &str = "backup_rate_limit_mbps";
+const BACKUP_STEP_PAGES: std::os::raw::c_int = 100;
+const BACKUP_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+// Auto-backup settings shared with the frontend's backupService
+const AUTO_BACKUP_ENABLED_KEY: &str = "auto_backup_enabled";
+const AUTO_BACKUP_PAUSED_KEY: &str = "auto_backup_paused";
+const AUTO_BACKUP_TIME_KEY: &str = "auto_backup_time";
+// Settings key for the backup taken when the main window is closed
+const BACKUP_ON_EXIT_KEY: &str = "backup_on_exit";
+const BACKUP_RETENTION_DAYS_KEY: &str = "backup_retention_days";
+const DEFAULT_BACKUP_RETENTION_DAYS: u32 = 30;
+const DEFAULT_AUTO_BACKUP_TIME: &str = "23:00";
+
+/// Environment variable that points the app at an explicit data directory when
+/// neither the platform config nor data directory is usable
+const APP_DIR_OVERRIDE_ENV: &str = "MOTORMODS_DATA_DIR";
+
+// Settings key for the age after which the last backup is considered stale
+const BACKUP_STALE_HOURS_KEY: &str = "backup_stale_hours";
+const DEFAULT_BACKUP_STALE_HOURS: u64 = 24;
+
+/// Largest database backup_to_bytes will hold in memory; beyond this use a file backup
+const BACKUP_TO_BYTES_MAX: u64 = 64 * 1024 * 1024;
+
+// Growth monitoring: alert above this many MB/day, measured over the last week
+const GROWTH_ALERT_MB_PER_DAY_KEY: &str = "growth_alert_mb_per_day";
+const DEFAULT_GROWTH_ALERT_MB_PER_DAY: u64 = 100;
+const GROWTH_RATE_WINDOW_DAYS: i64 = 7;
+
+/// Custom backups folder chosen with set_backups_directory; unset means <app dir>/backups
+const BACKUPS_DIR_KEY: &str = "backups_directory";
+
+// Settings key holding the weekday -> backup directory rotation map (JSON object)
+const BACKUP_ROTATION_KEY: &str = "backup_rotation_dirs";
+
+const WEEKDAYS: &[&str] = &[
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+// ============================================
+// HELPER FUNCTIONS
+// ============================================
+
+/// The chosen app directory and which candidate it came from
+static APP_DIR: std::sync::OnceLock<(PathBuf, &'static str)> = std::sync::OnceLock::new();
+
+/// Resolves the directory holding the database, backups and app state.
+/// MOTORMODS_DATA_DIR wins when set, and an unusable override is an error
+/// rather than silently ignored. Otherwise app_config_dir, then app_data_dir;
+/// the first that can be created is cached for the session. The frontend opens
+/// the database through get_database_url, so it always agrees with this.
+fn get_app_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    if let Some((dir, _)) = APP_DIR.get() {
+        return Ok(dir.clone());
+    }
+
+    if let Some(dir) = std::env::var_os(APP_DIR_OVERRIDE_ENV)
+        .filter(|v| !v.to_string_lossy().trim().is_empty())
+        .map(PathBuf::from)
+    {
+        fs::create_dir_all(&dir).map_err(|e| {
+            format!(
+                "{} is set to {}, which can't be used: {}",
+                APP_DIR_OVERRIDE_ENV,
+                dir.display(),
+                e
+            )
+        })?;
+        log::info!(
+            "Using data folder {} ({})",
+            dir.display(),
+            APP_DIR_OVERRIDE_ENV
+        );
+        return Ok(APP_DIR
+            .get_or_init(|| (dir, APP_DIR_OVERRIDE_ENV))
+            .0
+            .clone());
+    }
+
+    let candidates = [
+        (
+            "app_config_dir",
+            app.path().app_config_dir().map_err(|e| e.to_string()),
+        ),
+        (
+            "app_data_dir",
+            app.path().app_data_dir().map_err(|e| e.to_string()),
+        ),
+    ];
+
+    let mut errors = Vec::new();
+    for (label, candidate) in candidates {
+        let usable = candidate.and_then(|dir| {
+            fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+            Ok(dir)
+        });
+
+        match usable {
+            Ok(dir) => {
+                log::info!("Using data folder {} ({})", dir.display(), label);
+                return Ok(APP_DIR.get_or_init(|| (dir, label)).0.clone());
+            }
+            Err(e) => errors.push(format!("{}: {}", label, e)),
+        }
+    }
+
+    Err(format!(
+        "No usable app data directory found. Set {} to a writable folder. ({})",
+        APP_DIR_OVERRIDE_ENV,
+        errors.join("; ")
+    ))
+}
+
+/// Whether the app's own connections enforce foreign keys (PRAGMA foreign_keys
+/// is per-connection, so this is applied every time one is opened)
+static FOREIGN_KEYS_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Durability preset from DURABILITY_PRESETS applied to every connection; None keeps
+/// SQLite's defaults (synchronous is per-connection, journal_mode is stored in the file)
+static DURABILITY_PROFILE: Mutex<Option<&'static (&'static str, &'static str, &'static str)>> =
+    Mutex::new(None);
+
+/// PRAGMA busy_timeout for the app's connections, set with set_busy_timeout
+static BUSY_TIMEOUT_MS: AtomicU64 = AtomicU64::new(DEFAULT_BUSY_TIMEOUT_MS);
+
+/// Recent lock retries, oldest first, capped at MAX_LOCK_RETRY_EVENTS
+static LOCK_RETRY_EVENTS: Mutex<std::collections::VecDeque<LockRetryEvent>> =
+    Mutex::new(std::collections::VecDeque::new());
+
+/// Notes a run of lock retries for get_lock_diagnostics
+fn record_lock_retry(operation: &str, retries: u32, started: Instant) {
+    let waited = started.elapsed();
+    let at = Local::now() - chrono::Duration::from_std(waited).unwrap_or_default();
+    let mut events = LOCK_RETRY_EVENTS.lock().unwrap();
+    if events.len() == MAX_LOCK_RETRY_EVENTS {
+        events.pop_front();
+    }
+    events.push_back(LockRetryEvent {
+        at: at.to_rfc3339(),
+        operation: operation.to_string(),
+        retries,
+        waited_ms: waited.as_millis() as u64,
+    });
+}
+
+/// Opens the app database with the connection pragmas our commands rely on
+fn open_database(db_path: &Path, flags: OpenFlags) -> Result<Connection, String> {
+    let conn = Connection::open_with_flags(db_path, flags)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    conn.busy_timeout(Duration::from_millis(
+        BUSY_TIMEOUT_MS.load(Ordering::SeqCst),
+    ))
+    .map_err(|e| format!("Failed to configure database connection: {}", e))?;
+    let mut pragma = if FOREIGN_KEYS_ENABLED.load(Ordering::SeqCst) {
+        "PRAGMA foreign_keys = ON".to_string()
+    } else {
+        "PRAGMA foreign_keys = OFF".to_string()
+    };
+    if let Some((_, synchronous, _)) = *DURABILITY_PROFILE.lock().unwrap() {
+        pragma.push_str(&format!("; PRAGMA synchronous = {}", synchronous));
+    }
+    conn.execute_batch(&pragma)
+        .map_err(|e| format!("Failed to configure database connection: {}", e))?;
+    Ok(conn)
+}
+
+/// Takes the single-instance lock. If another instance holds it, this one is
+/// marked secondary and "secondary-instance-detected" is emitted.
+fn acquire_instance_lock(app: &AppHandle) {
+    use fs2::FileExt;
+
+    let state = app.state::<InstanceLock>();
+    let lock_path = match get_app_dir(app) {
+        Ok(dir) => dir.join(INSTANCE_LOCK_FILE),
+        Err(e) => {
+            log::warn!("Single-instance check skipped: {}", e);
+            return;
+        }
+    };
+    let file = match fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .read(true)
+        .write(true)
+        .open(&lock_path)
+    {
+        Ok(file) => file,
+        Err(e) => {
+            log::warn!(
+                "Single-instance check skipped, can't open {}: {}",
+                lock_path.display(),
+                e
+            );
+            return;
+        }
+    };
+
+    match file.try_lock_exclusive() {
+        Ok(()) => {
+            let _ = file.set_len(0);
+            let _ = (&file).write_all(std::process::id().to_string().as_bytes());
+            *state.file.lock().unwrap() = Some(file);
+        }
+        Err(e) => {
+            log::warn!(
+                "Another instance holds {} ({}); database changes are disabled",
+                lock_path.display(),
+                e
+            );
+            state.secondary.store(true, Ordering::SeqCst);
+            let _ = app.emit(
+                "secondary-instance-detected",
+                lock_path.to_string_lossy().to_string(),
+            );
+        }
+    }
+}
+
+/// Refuses database-changing commands while another instance owns the database
+fn require_primary_instance(app: &AppHandle) -> Result<(), String> {
+    if app.state::<InstanceLock>().secondary.load(Ordering::SeqCst) {
+        return Err(
+            "MotorMods is already open in another window. Close this one and use the other to change the database."
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// False when another running instance holds the single-instance lock
+#[tauri::command]
+fn is_primary_instance(app: AppHandle) -> bool {
+    !app.state::<InstanceLock>().secondary.load(Ordering::SeqCst)
+}
+
+/// Loads the durability preset saved by set_durability_profile
+fn load_durability_setting(app: &AppHandle) {
+    if let Some(value) = get_setting(app, DURABILITY_PROFILE_KEY) {
+        let preset = DURABILITY_PRESETS
+            .iter()
+            .find(|(name, _, _)| *name == value.trim());
+        *DURABILITY_PROFILE.lock().unwrap() = preset;
+    }
+}
+
+/// Loads the busy timeout saved by set_busy_timeout
+fn load_busy_timeout_setting(app: &AppHandle) {
+    if let Some(ms) = get_setting(app, BUSY_TIMEOUT_KEY).and_then(|v| v.trim().parse().ok()) {
+        BUSY_TIMEOUT_MS.store(ms, Ordering::SeqCst);
+    }
+}
+
+/// Loads the foreign key preference saved by set_foreign_keys_enabled
+fn load_foreign_keys_setting(app: &AppHandle) {
+    if let Some(value) = get_setting(app, FOREIGN_KEYS_KEY) {
+        FOREIGN_KEYS_ENABLED.store(value.trim() != "0", Ordering::SeqCst);
+    }
+}
+
+fn get_db_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_config_dir = get_app_dir(app)?;
+    let active_name = app
+        .state::<DatabaseState>()
+        .active_name
+        .lock()
+        .map_err(|_| "Database state is unavailable".to_string())?
+        .clone();
+    Ok(app_config_dir.join(format!("{}.db", active_name)))
+}
+
+/// Connection string for tauri-plugin-sql pointing at get_db_path. The plugin
+/// joins it onto app_config_dir, and an absolute path replaces that directory,
+/// so the frontend and these commands open the same file wherever it lives.
+#[tauri::command]
+fn get_database_url(app: AppHandle) -> Result<String, String> {
+    Ok(database_url(&get_db_path(&app)?))
+}
+
+fn database_url(db_path: &Path) -> String {
+    format!("sqlite:{}", db_path.to_string_lossy())
+}
+
+/// Restores the database chosen with switch_database in an earlier session.
+/// Falls back to the default database when the saved one is gone or invalid.
+fn load_active_database(app: &AppHandle) {
+    let Ok(app_dir) = get_app_dir(app) else {
+        return;
+    };
+    let Some(name) = fs::read_to_string(app_dir.join(ACTIVE_DATABASE_FILE))
+        .ok()
+        .and_then(|saved| validate_db_name(&saved).ok())
+    else {
+        return;
+    };
+    if !app_dir.join(format!("{}.db", name)).exists() {
+        log::warn!(
+            "Saved active database {}.db is missing; using the default",
+            name
+        );
+        return;
+    }
+    if let Ok(mut active_name) = app.state::<DatabaseState>().active_name.lock() {
+        *active_name = name;
+    }
+}
+
+/// Validates a database name (file stem) and strips an optional .db suffix
+fn validate_db_name(db_name: &str) -> Result<String, String> {
+    let name = db_name.trim().trim_end_matches(".db");
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+
+    if !valid {
+        return Err(format!(
+            "Invalid database name: {}. Use letters, numbers, '-' and '_' only",
+            db_name
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Turns a path typed or picked by the user into the absolute path we act on:
+/// a leading ~ is the home directory, relative paths are resolved against the
+/// home directory (never the process cwd) and . / .. are folded away. Empty
+/// paths, NUL bytes and ~user forms are rejected. The path needn't exist.
+fn normalize_user_path(app: &AppHandle, raw: &str) -> Result<PathBuf, String> {
+    use std::path::Component;
+
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Err("No path given".to_string());
+    }
+    if trimmed.contains('\0') {
+        return Err(format!("Invalid path: {}", trimmed.escape_debug()));
+    }
+
+    let home = || {
+        app.path()
+            .home_dir()
+            .map_err(|e| format!("Could not find the home directory: {}", e))
+    };
+    let path = if trimmed == "~" {
+        home()?
+    } else if let Some(rest) = trimmed
+        .strip_prefix("~/")
+        .or_else(|| trimmed.strip_prefix("~\\"))
+    {
+        home()?.join(rest)
+    } else if trimmed.starts_with('~') {
+        return Err(format!(
+            "Unsupported path: {}. Use ~/ for the home directory or a full path",
+            trimmed
+        ));
+    } else {
+        PathBuf::from(trimmed)
+    };
+    let path = if path.is_absolute() {
+        path
+    } else {
+        home()?.join(path)
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    Ok(normalized)
+}
+
+fn get_backups_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    if let Some(custom) = get_setting(app, BACKUPS_DIR_KEY)
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+    {
+        let custom_dir = PathBuf::from(&custom);
+        fs::create_dir_all(&custom_dir)
+            .map_err(|e| format!("Backups folder {} is not usable: {}", custom, e))?;
+        check_backups_dir_location(app, &custom_dir)
+            .map_err(|e| format!("Invalid backups folder setting: {}", e))?;
+        return Ok(custom_dir);
+    }
+
+    let app_config_dir = get_app_dir(app)?;
+    let backups_dir = app_config_dir.join("backups");
+
+    if !backups_dir.exists() {
+        fs::create_dir_all(&backups_dir).map_err(|e| e.to_string())?;
+    }
+
+    Ok(backups_dir)
+}
+
+/// Folders that hold backups: the default backups folder first, then every
+/// weekday rotation folder that is currently mounted and allowed. Listing and
+/// filename resolution search these in order, so what rotation wrote can be
+/// restored, verified and deleted like any other backup.
+fn backup_search_dirs(app: &AppHandle) -> Result<Vec<PathBuf>, String> {
+    let mut dirs = vec![get_backups_dir(app)?];
+    let mut seen: Vec<PathBuf> = dirs
+        .iter()
+        .map(|d| fs::canonicalize(d).unwrap_or_else(|_| d.clone()))
+        .collect();
+
+    let mut rotated: Vec<String> = get_backup_rotation_map(app)
+        .into_values()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+    rotated.sort();
+    for dir in rotated.into_iter().map(PathBuf::from) {
+        if !rotation_dir_available(app, &dir) {
+            continue;
+        }
+        let canonical = fs::canonicalize(&dir).unwrap_or_else(|_| dir.clone());
+        if !seen.contains(&canonical) {
+            seen.push(canonical);
+            dirs.push(dir);
+        }
+    }
+    Ok(dirs)
+}
+
+fn rotation_dir_available(app: &AppHandle, dir: &Path) -> bool {
+    dir.is_dir() && check_backups_dir_location(app, dir).is_ok()
+}
+
+/// Configured rotation folders that backup_search_dirs skipped because they
+/// are unmounted, missing or not allowed
+fn unavailable_rotation_dirs(app: &AppHandle) -> Vec<String> {
+    let mut dirs: Vec<String> = get_backup_rotation_map(app)
+        .into_values()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty() && !rotation_dir_available(app, Path::new(p)))
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+/// Rejects a backups folder that is the database folder or one of its parents,
+/// where backups would be taken into (and listed alongside) the live database
+fn check_backups_dir_location(app: &AppHandle, dir: &Path) -> Result<(), String> {
+    let db_dir = get_app_dir(app)?;
+    let db_dir = fs::canonicalize(&db_dir).unwrap_or(db_dir);
+    let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+
+    if db_dir.starts_with(&dir) {
+        return Err(format!(
+            "{} contains the live database folder {}. Choose a separate folder, such as a subfolder of it.",
+            dir.display(),
+            db_dir.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Sends `log` records at info and above to stderr; installed in run()
+struct StderrLogger;
+
+static STDERR_LOGGER: StderrLogger = StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Appends a record to the operation history log.
+/// Logging failures are logged as warnings but never fail the operation itself.
+fn log_operation(app: &AppHandle, record: OperationRecord) {
+    log::info!(
+        "Operation {} ({}): {}",
+        record.operation,
+        if record.success { "ok" } else { "failed" },
+        record.details
+    );
+
+    let result = get_app_dir(app).and_then(|dir| {
+        let line = serde_json::to_string(&record).map_err(|e| e.to_string())?;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(OPERATION_HISTORY_FILE))
+            .map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())
+    });
+
+    if let Err(e) = result {
+        log::warn!("Failed to write operation history: {}", e);
+    }
+}
+
+/// Copies a live database with SQLite's backup API so pending WAL content is included
+/// and writes from the app's other connections can't leave the copy half-applied.
+/// A plain file copy of the .db can miss un-checkpointed pages in -wal.
+/// An existing destination is never overwritten, and a failed copy is removed.
+fn snapshot_database(source_path: &Path, destination_path: &Path) -> Result<(), String> {
+    if destination_path.exists() {
+        return Err(format!("{} already exists", destination_path.display()));
+    }
+    // open_database sets busy_timeout, so a writer holding the lock is waited for
+    let source_conn = open_database(source_path, OpenFlags::default())?;
+    let copied = Connection::open(destination_path)
+        .map_err(|e| format!("Failed to create backup database: {}", e))
+        .and_then(|mut destination_conn| {
+            let backup = rusqlite::backup::Backup::new(&source_conn, &mut destination_conn)
+                .map_err(|e| format!("Failed to initialize backup: {}", e))?;
+            backup
+                .run_to_completion(100, Duration::from_millis(10), None)
+                .map_err(|e| format!("Failed to complete backup: {}", e))
+        });
+    if copied.is_err() {
+        let _ = fs::remove_file(destination_path);
+    }
+    copied
+}
+
+fn compute_file_sha256(path: &Path) -> Result<String, String> {
+    use sha2::Digest;
+
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut hasher = sha2::Sha256::new();
+    let mut buf = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Megabytes (10^6 bytes) per second, rounded to two decimals
+fn throughput_mbps(bytes: u64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64().max(0.001);
+    ((bytes as f64 / 1_000_000.0 / secs) * 100.0).round() / 100.0
+}
+
+/// <backup>.sha256 next to the backup, in sha256sum format
+fn checksum_sidecar_path(backup_path: &Path) -> PathBuf {
+    let mut name = backup_path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(CHECKSUM_SIDECAR_EXT);
+    PathBuf::from(name)
+}
+
+fn write_checksum_sidecar(backup_path: &Path, checksum: &str) -> Result<(), String> {
+    let filename = backup_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    fs::write(
+        checksum_sidecar_path(backup_path),
+        format!("{}  {}\n", checksum, filename),
+    )
+    .map_err(|e| format!("Failed to write checksum file: {}", e))
+}
+
+/// Reads the expected checksum from a backup's sidecar, if it has one
+fn read_checksum_sidecar(backup_path: &Path) -> Option<String> {
+    let contents = fs::read_to_string(checksum_sidecar_path(backup_path)).ok()?;
+    contents
+        .split_whitespace()
+        .next()
+        .map(|hash| hash.to_lowercase())
+}
+
+/// <backup>.json next to the backup
+fn manifest_sidecar_path(backup_path: &Path) -> PathBuf {
+    let mut name = backup_path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(MANIFEST_SIDECAR_EXT);
+    PathBuf::from(name)
+}
+
+/// Reads a backup's manifest; backups without one get the defaults
+fn read_backup_manifest(backup_path: &Path) -> BackupManifest {
+    fs::read_to_string(manifest_sidecar_path(backup_path))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn write_backup_manifest(backup_path: &Path, manifest: &BackupManifest) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
+    fs::write(manifest_sidecar_path(backup_path), json)
+        .map_err(|e| format!("Failed to write backup manifest: {}", e))
+}
+
+/// Records in a safety backup's manifest which operation it was taken before
+/// and what that operation applied. Failures only cost the description.
+fn record_safety_origin(safety_path: &Path, operation: &str, target: Option<&str>) {
+    let mut manifest = read_backup_manifest(safety_path);
+    manifest
+        .captured_at
+        .get_or_insert_with(|| Local::now().to_rfc3339());
+    manifest.safety_for = Some(operation.to_string());
+    manifest.safety_target = target.map(str::to_string);
+    if let Err(e) = write_backup_manifest(safety_path, &manifest) {
+        log::warn!("{}", e);
+    }
+}
+
+/// Human-readable origin of a safety backup, falling back to its filename prefix
+fn describe_safety_origin(filename: &str, operation: Option<&str>, target: Option<&str>) -> String {
+    let operation = operation.unwrap_or_else(|| {
+        ["restore", "import", "reset", "repair", "migration", "sync"]
+            .into_iter()
+            .find(|op| filename.starts_with(&format!("pre_{}_safety_", op)))
+            .unwrap_or("unknown")
+    });
+    let verb = match operation {
+        "restore" => "restoring",
+        "commit_trial" => "committing the trial restore of",
+        "restore_data" => "restoring data from",
+        "import" => "importing",
+        "import_csv_bundle" => "importing CSV bundle",
+        "import_sql_dump" => "importing SQL dump",
+        "reset" => return "Created before resetting the database".to_string(),
+        "repair" => return "Created before repairing the database".to_string(),
+        "migration" => "applying migration",
+        "sync" | "sync_from" => "syncing new rows from",
+        _ => return "Safety backup".to_string(),
+    };
+    // External sources are stored as full paths; the file name reads better
+    match target.map(|t| {
+        Path::new(t)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| t.to_string())
+    }) {
+        Some(name) => format!("Created before {} {}", verb, name),
+        None => format!("Created before {} (details not recorded)", verb),
+    }
+}
+
+/// Resolves a backup filename inside the backups directory, rejecting anything
+/// that isn't a plain existing .db file name (no separators or parent references)
+fn resolve_backup_file(app: &AppHandle, backup_filename: &str) -> Result<PathBuf, String> {
+    if !backup_filename.ends_with(".db") {
+        return Err(format!("Invalid backup filename: {}", backup_filename));
+    }
+    backup_file_in_dir(app, backup_filename)
+}
+
+/// resolve_backup_file that also accepts compressed .db.gz backups
+fn resolve_any_backup_file(app: &AppHandle, backup_filename: &str) -> Result<PathBuf, String> {
+    if !is_listed_backup(Path::new(backup_filename)) {
+        return Err(format!("Invalid backup filename: {}", backup_filename));
+    }
+    backup_file_in_dir(app, backup_filename)
+}
+
+/// Finds a caller-supplied filename in the backup folders (backup_search_dirs,
+/// first match wins). Only a single plain file name is accepted, and the file
+/// must still be inside its folder once symlinks are resolved.
+fn backup_file_in_dir(app: &AppHandle, backup_filename: &str) -> Result<PathBuf, String> {
+    find_backup_file(&backup_search_dirs(app)?, backup_filename)
+}
+
+/// backup_file_in_dir over an explicit list of folders
+fn find_backup_file(dirs: &[PathBuf], backup_filename: &str) -> Result<PathBuf, String> {
+    let mut components = Path::new(backup_filename).components();
+    let is_plain_name = matches!(
+        (components.next(), components.next()),
+        (Some(std::path::Component::Normal(_)), None)
+    ) && !backup_filename.contains(['/', '\\']);
+    if !is_plain_name {
+        return Err(format!("Invalid backup filename: {}", backup_filename));
+    }
+
+    for dir in dirs {
+        let backup_path = dir.join(backup_filename);
+        if !backup_path.is_file() {
+            continue;
+        }
+        let inside = match (fs::canonicalize(dir), fs::canonicalize(&backup_path)) {
+            (Ok(dir), Ok(file)) => file.starts_with(dir),
+            _ => false,
+        };
+        if !inside {
+            return Err(format!("Invalid backup filename: {}", backup_filename));
+        }
+        return Ok(backup_path);
+    }
+    Err(format!("Backup file not found: {}", backup_filename))
+}
+
+/// Combined size of a backup and its sidecars, i.e. what remove_backup_files frees
+fn backup_files_size(backup_path: &Path) -> u64 {
+    [
+        backup_path.to_path_buf(),
+        checksum_sidecar_path(backup_path),
+        manifest_sidecar_path(backup_path),
+    ]
+    .iter()
+    .filter_map(|p| fs::metadata(p).ok())
+    .map(|m| m.len())
+    .sum()
+}
+
+/// Deletes a backup along with its checksum and manifest sidecars
+fn remove_backup_files(backup_path: &Path) -> std::io::Result<()> {
+    fs::remove_file(backup_path)?;
+    let _ = fs::remove_file(checksum_sidecar_path(backup_path));
+    let _ = fs::remove_file(manifest_sidecar_path(backup_path));
+    Ok(())
+}
+
+fn is_compressed_backup(filename: &str) -> bool {
+    filename.ends_with(COMPRESSED_BACKUP_SUFFIX)
+}
+
+/// .db backups and their compressed .db.gz form, as list_backups shows them
+fn is_listed_backup(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.extension().is_some_and(|ext| ext == "db") || is_compressed_backup(&name)
+}
+
+/// Gzips a finished .db backup into <name>.db.gz beside it, carrying its
+/// manifest over and writing a checksum of the compressed file, then removes
+/// the .db. Returns the compressed path, size and checksum.
+fn compress_backup_file(backup_path: &Path) -> Result<(PathBuf, u64, String), String> {
+    use flate2::{write::GzEncoder, Compression};
+
+    let mut compressed = backup_path.as_os_str().to_owned();
+    compressed.push(".gz");
+    let compressed_path = PathBuf::from(compressed);
+    let partial_path = compressed_path.with_extension("gz.partial");
+
+    let written = (|| -> std::io::Result<()> {
+        let mut source = fs::File::open(backup_path)?;
+        let mut encoder = GzEncoder::new(fs::File::create(&partial_path)?, Compression::default());
+        std::io::copy(&mut source, &mut encoder)?;
+        encoder.finish()?.sync_all()?;
+        fs::rename(&partial_path, &compressed_path)
+    })();
+    if let Err(e) = written {
+        let _ = fs::remove_file(&partial_path);
+        return Err(format!("Failed to compress backup: {}", e));
+    }
+
+    let checksum = compute_file_sha256(&compressed_path)?;
+    write_checksum_sidecar(&compressed_path, &checksum)?;
+    write_backup_manifest(&compressed_path, &read_backup_manifest(backup_path))?;
+    let size = fs::metadata(&compressed_path)
+        .map_err(|e| format!("Failed to get backup metadata: {}", e))?
+        .len();
+    remove_backup_files(backup_path)
+        .map_err(|e| format!("Failed to remove uncompressed backup: {}", e))?;
+    Ok((compressed_path, size, checksum))
+}
+
+/// Removes a temp file when dropped, so every early return cleans up
+struct TempFileGuard(PathBuf);
+
+impl Drop for TempFileGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Gunzips a .db.gz backup into a temp file that must hold a SQLite database
+fn decompress_backup_to_temp(path: &Path) -> Result<TempFileGuard, String> {
+    use flate2::read::GzDecoder;
+
+    let temp = TempFileGuard(unique_temp_path("decompressed", "db"));
+    (|| -> std::io::Result<()> {
+        let mut decoder = GzDecoder::new(fs::File::open(path)?);
+        let mut out = fs::File::create(&temp.0)?;
+        std::io::copy(&mut decoder, &mut out)?;
+        out.sync_all()
+    })()
+    .map_err(|e| format!("Failed to decompress {}: {}", path.display(), e))?;
+    if !has_sqlite_header(&temp.0)? {
+        return Err(format!(
+            "{} does not contain a SQLite database",
+            path.display()
+        ));
+    }
+    Ok(temp)
+}
+
+fn has_sqlite_header(path: &Path) -> Result<bool, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut header = [0u8; 16];
+    let read =
+        read_full(&mut file, &mut header).map_err(|e| format!("Failed to read file: {}", e))?;
+    Ok(read == header.len() && &header == SQLITE_MAGIC)
+}
+
+/// Copies a file so it survives power loss: writes to <destination>.partial,
+/// fsyncs it, then renames it into place. The .partial file sits next to the
+/// destination, so the rename never crosses devices.
+fn copy_file_durable(source: &Path, destination: &Path) -> std::io::Result<u64> {
+    copy_durable_with(source, destination, |from, to| fs::copy(from, to))
+}
+
+/// copy_file_durable that also returns the SHA-256 of the bytes written,
+/// hashed as they are copied so the file isn't read a second time
+fn copy_with_sha256(source: &Path, destination: &Path) -> std::io::Result<(u64, String)> {
+    copy_durable_with(source, destination, |from, to| {
+        use sha2::Digest;
+
+        let mut reader = fs::File::open(from)?;
+        let mut writer = fs::File::create(to)?;
+        let mut hasher = sha2::Sha256::new();
+        let mut buf = vec![0u8; 1024 * 1024];
+        let mut total = 0u64;
+        loop {
+            let read = reader.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+            writer.write_all(&buf[..read])?;
+            total += read as u64;
+        }
+        Ok((total, format!("{:x}", hasher.finalize())))
+    })
+}
+
+fn copy_durable_with<T>(
+    source: &Path,
+    destination: &Path,
+    copy: impl Fn(&Path, &Path) -> std::io::Result<T>,
+) -> std::io::Result<T> {
+    let file_name = destination.file_name().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "destination has no file name",
+        )
+    })?;
+    let mut partial_name = file_name.to_os_string();
+    partial_name.push(".partial");
+    let partial = destination.with_file_name(partial_name);
+
+    let copied = copy(source, &partial).and_then(|copied| {
+        // Write access is needed for FlushFileBuffers on Windows
+        fs::OpenOptions::new()
+            .write(true)
+            .open(&partial)?
+            .sync_all()?;
+        Ok(copied)
+    });
+    let copied = match copied {
+        Ok(copied) => copied,
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+    };
+
+    if let Err(e) = fs::rename(&partial, destination) {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+
+    // Persist the directory entry as well (not supported on Windows)
+    #[cfg(unix)]
+    if let Some(parent) = destination.parent() {
+        if let Ok(dir) = fs::File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    Ok(copied)
+}
+
+/// True when `destination` would live on a different filesystem/drive than `source`
+fn on_different_device(source: &Path, destination: &Path) -> bool {
+    let destination_dir = if destination.is_dir() {
+        destination
+    } else {
+        match destination.parent() {
+            Some(parent) => parent,
+            None => return false,
+        }
+    };
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (fs::metadata(source), fs::metadata(destination_dir)) {
+            (Ok(a), Ok(b)) => a.dev() != b.dev(),
+            _ => false,
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        let prefix = |p: &Path| {
+            fs::canonicalize(p)
+                .ok()
+                .and_then(|p| match p.components().next() {
+                    Some(std::path::Component::Prefix(prefix)) => {
+                        Some(prefix.as_os_str().to_ascii_lowercase())
+                    }
+                    _ => None,
+                })
+        };
+        match (prefix(source), prefix(destination_dir)) {
+            (Some(a), Some(b)) => a != b,
+            _ => false,
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = (source, destination_dir);
+        false
+    }
+}
+
+/// Builds an error message that tells a cross-drive failure apart from a generic I/O error
+fn describe_copy_error(
+    context: &str,
+    error: &std::io::Error,
+    source: &Path,
+    destination: &Path,
+) -> String {
+    if error.kind() == std::io::ErrorKind::CrossesDevices {
+        return format!(
+            "{}: destination is on a different drive and the file could not be moved there ({})",
+            context, error
+        );
+    }
+    if on_different_device(source, destination) {
+        return format!(
+            "{}: copying to a different drive ({}) failed: {}",
+            context,
+            destination.display(),
+            error
+        );
+    }
+    format!("{}: {}", context, error)
+}
+
+/// The -wal and -shm sidecar files SQLite keeps next to a database
+fn sqlite_sidecar_paths(db_path: &Path) -> [PathBuf; 2] {
+    let db = db_path.to_string_lossy();
+    [
+        PathBuf::from(format!("{}-wal", db)),
+        PathBuf::from(format!("{}-shm", db)),
+    ]
+}
+
+/// Checks a -wal file against its database: header magic and checksum, page
+/// size agreement, and whether any frames carry the header's salt values
+fn inspect_wal(db_path: &Path) -> WalConsistency {
+    let [wal_path, shm_path] = sqlite_sidecar_paths(db_path);
+    let mut report = WalConsistency {
+        database_exists: db_path.exists(),
+        wal_present: wal_path.exists(),
+        wal_size: fs::metadata(&wal_path).map(|m| m.len()).unwrap_or(0),
+        shm_present: shm_path.exists(),
+        wal_header_valid: None,
+        wal_page_size: None,
+        database_page_size: read_db_page_size(db_path),
+        wal_checkpoint_seq: None,
+        index_matches_wal: None,
+        valid_frames: 0,
+        consistent: true,
+        stale: false,
+        issues: Vec::new(),
+    };
+
+    if !report.wal_present {
+        if report.shm_present {
+            // A live WAL-mode connection always keeps its -wal file around
+            report.stale = true;
+            report
+                .issues
+                .push("-shm file has no matching -wal file".to_string());
+        }
+        report.consistent = report.issues.is_empty();
+        return report;
+    }
+
+    if !report.database_exists {
+        report.stale = true;
+        report
+            .issues
+            .push("-wal file has no main database".to_string());
+    }
+
+    // An empty WAL is normal for an open connection that was just checkpointed
+    if report.wal_size == 0 {
+        report.consistent = report.issues.is_empty();
+        return report;
+    }
+
+    let header = fs::File::open(&wal_path).and_then(|mut file| {
+        let mut buf = [0u8; WAL_HEADER_LEN];
+        let read = read_full(&mut file, &mut buf)?;
+        Ok((file, buf, read))
+    });
+    let (mut file, header, read) = match header {
+        Ok(parts) => parts,
+        Err(e) => {
+            report.consistent = false;
+            report
+                .issues
+                .push(format!("Could not read -wal file: {}", e));
+            return report;
+        }
+    };
+
+    let word = |offset: usize| u32::from_be_bytes(header[offset..offset + 4].try_into().unwrap());
+    let header_valid = read == WAL_HEADER_LEN
+        && (word(0) == WAL_MAGIC_LE || word(0) == WAL_MAGIC_BE)
+        && wal_checksum(&header[..24], word(0) == WAL_MAGIC_BE) == (word(24), word(28));
+    report.wal_header_valid = Some(header_valid);
+
+    if !header_valid {
+        report.stale = true;
+        report
+            .issues
+            .push("-wal header is truncated or corrupt".to_string());
+    } else {
+        let page_size = word(8);
+        report.wal_page_size = Some(page_size);
+        report.wal_checkpoint_seq = Some(word(12));
+
+        if let Some(db_page_size) = report.database_page_size {
+            if db_page_size != page_size {
+                report.stale = true;
+                report.issues.push(format!(
+                    "-wal page size {} does not match database page size {}",
+                    page_size, db_page_size
+                ));
+            }
+        }
+
+        // Count leading frames written for this WAL generation (same salts as the header)
+        let salts = (word(16), word(20));
+        let mut frame_header = [0u8; 24];
+        let frame_len = 24 + page_size as u64;
+        let mut offset = WAL_HEADER_LEN as u64;
+        while offset + frame_len <= report.wal_size {
+            let ok = file
+                .seek(std::io::SeekFrom::Start(offset))
+                .and_then(|_| file.read_exact(&mut frame_header));
+            if ok.is_err() {
+                break;
+            }
+            let frame_word =
+                |o: usize| u32::from_be_bytes(frame_header[o..o + 4].try_into().unwrap());
+            if (frame_word(8), frame_word(12)) != salts {
+                break;
+            }
+            report.valid_frames += 1;
+            offset += frame_len;
+        }
+
+        // The index a live connection keeps in -shm carries the salts of the WAL
+        // generation it is reading, which change at every checkpoint restart. A
+        // -wal left from another database or an older generation won't match.
+        if let Some(index) = read_wal_index(&shm_path) {
+            let matches = index.salts == salts && index.page_size == page_size;
+            report.index_matches_wal = Some(matches);
+            if !matches {
+                report.stale = true;
+                report.issues.push(format!(
+                    "-wal (checkpoint {}) was not written for the database's -shm index",
+                    word(12)
+                ));
+            } else if index.max_frame as u64 > report.valid_frames {
+                report.issues.push(format!(
+                    "-shm index expects {} frames but the -wal only has {}",
+                    index.max_frame, report.valid_frames
+                ));
+            }
+        }
+    }
+
+    report.consistent = report.issues.is_empty();
+    report
+}
+
+/// The parts of a -shm wal-index header inspect_wal compares against the -wal
+struct WalIndexHeader {
+    page_size: u32,
+    max_frame: u32,
+    salts: (u32, u32),
+}
+
+/// Reads the -shm wal-index header. None unless both copies agree and the
+/// index is initialized, since SQLite rebuilds it from the -wal otherwise.
+fn read_wal_index(shm_path: &Path) -> Option<WalIndexHeader> {
+    let mut buf = [0u8; WAL_INDEX_HEADER_LEN * 2];
+    let mut file = fs::File::open(shm_path).ok()?;
+    if read_full(&mut file, &mut buf).ok()? < buf.len() {
+        return None;
+    }
+    let (first, second) = buf.split_at(WAL_INDEX_HEADER_LEN);
+    let word = |offset: usize| u32::from_ne_bytes(first[offset..offset + 4].try_into().unwrap());
+    if first != second || word(0) != WAL_INDEX_VERSION || first[12] != 1 {
+        return None;
+    }
+    let page_size = match u16::from_ne_bytes([first[14], first[15]]) {
+        1 => 65536,
+        size => size as u32,
+    };
+    Some(WalIndexHeader {
+        page_size,
+        max_frame: word(16),
+        salts: (
+            u32::from_be_bytes(first[32..36].try_into().unwrap()),
+            u32::from_be_bytes(first[36..40].try_into().unwrap()),
+        ),
+    })
+}
+
+/// SQLite's WAL checksum over 32-bit words, in the byte order the magic declares
+fn wal_checksum(data: &[u8], big_endian: bool) -> (u32, u32) {
+    let (mut s0, mut s1) = (0u32, 0u32);
+    for pair in data.chunks_exact(8) {
+        let read = |bytes: &[u8]| {
+            let bytes: [u8; 4] = bytes.try_into().unwrap();
+            if big_endian {
+                u32::from_be_bytes(bytes)
+            } else {
+                u32::from_le_bytes(bytes)
+            }
+        };
+        s0 = s0.wrapping_add(read(&pair[..4])).wrapping_add(s1);
+        s1 = s1.wrapping_add(read(&pair[4..])).wrapping_add(s0);
+    }
+    (s0, s1)
+}
+
+/// Page size from the database header (bytes 16-17; 1 means 65536)
+fn read_db_page_size(db_path: &Path) -> Option<u32> {
+    let mut header = [0u8; 18];
+    let mut file = fs::File::open(db_path).ok()?;
+    if read_full(&mut file, &mut header).ok()? < header.len() || &header[..16] != SQLITE_MAGIC {
+        return None;
+    }
+    match u16::from_be_bytes([header[16], header[17]]) {
+        1 => Some(65536),
+        size => Some(size as u32),
+    }
+}
+
+/// Whether tauri-plugin-sql still has an open pool on db_path. The frontend
+/// loads the database by get_database_url, so that string is the pool's key.
+fn sql_pool_open(app: &AppHandle, db_path: &Path) -> bool {
+    let Some(instances) = app.try_state::<tauri_plugin_sql::DbInstances>() else {
+        return false;
+    };
+    // The plugin holds the write lock while it loads a pool
+    let Ok(pools) = instances.0.try_read() else {
+        return true;
+    };
+    match pools.get(&database_url(db_path)) {
+        Some(tauri_plugin_sql::DbPool::Sqlite(pool)) => !pool.is_closed(),
+        None => false,
+    }
+}
+
+/// Removes -wal/-shm files that inspect_wal flags as stale. A WAL with a valid
+/// header and matching page size is never touched, since it may hold
+/// uncheckpointed transactions of a connection that is still open, and nothing
+/// is removed while the frontend's pool has the database open.
+fn remove_stale_sidecars(app: &AppHandle, db_path: &Path) -> Result<Vec<String>, String> {
+    let report = inspect_wal(db_path);
+    if !report.stale {
+        return Ok(Vec::new());
+    }
+    if sql_pool_open(app, db_path) {
+        return Err(format!(
+            "Not removing -wal/-shm files of {}: the database is still open",
+            db_path.display()
+        ));
+    }
+
+    let mut removed = Vec::new();
+    for sidecar in sqlite_sidecar_paths(db_path) {
+        if sidecar.exists() {
+            fs::remove_file(&sidecar)
+                .map_err(|e| format!("Failed to remove {}: {}", sidecar.display(), e))?;
+            removed.push(sidecar.to_string_lossy().to_string());
+        }
+    }
+    Ok(removed)
+}
+
+/// pre_<operation>_safety_<timestamp>.db, with milliseconds so two safety
+/// backups taken in the same second don't share a name
+fn safety_backup_filename(operation: &str) -> String {
+    format!(
+        "pre_{}_safety_{}.db",
+        operation,
+        Local::now().format("%Y-%m-%d_%H-%M-%S_%3f")
+    )
+}
+
+fn is_safety_backup(filename: &str) -> bool {
+    SAFETY_BACKUP_PREFIXES
+        .iter()
+        .any(|prefix| filename.starts_with(prefix))
+}
+
+fn table_exists(conn: &Connection, table_name: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='table' AND name=?1",
+        params![table_name],
+        |row| row.get(0),
+    )
+    .unwrap_or(false)
+}
+
+/// Approximate row counts per table from sqlite_stat1 (populated by ANALYZE).
+/// The first number of each stat entry is the row count when the table was analyzed.
+fn read_stat1_counts(conn: &Connection) -> HashMap<String, i64> {
+    let mut counts = HashMap::new();
+    if !table_exists(conn, "sqlite_stat1") {
+        return counts;
+    }
+
+    let Ok(mut stmt) = conn.prepare("SELECT tbl, stat FROM sqlite_stat1") else {
+        return counts;
+    };
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    });
+
+    if let Ok(rows) = rows {
+        for (table, stat) in rows.flatten() {
+            if let Some(count) = stat
+                .split_whitespace()
+                .next()
+                .and_then(|n| n.parse::<i64>().ok())
+            {
+                let entry = counts.entry(table).or_insert(count);
+                *entry = (*entry).max(count);
+            }
+        }
+    }
+
+    counts
+}
+
+/// Where a new backup should be written, after applying the weekday rotation
+struct BackupDestination {
+    dir: PathBuf,
+    warning: Option<String>,
+}
+
+/// Resolves the directory for a new backup.
+/// If a rotation drive is configured for today and is mounted, it is used;
+/// otherwise falls back to the default backups directory with a warning.
+fn resolve_backup_destination(app: &AppHandle) -> Result<BackupDestination, String> {
+    let default_dir = get_backups_dir(app)?;
+    let today = WEEKDAYS[Local::now().weekday().num_days_from_monday() as usize];
+
+    let rotated = get_backup_rotation_map(app)
+        .remove(today)
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty());
+
+    let Some(rotated) = rotated else {
+        return Ok(BackupDestination {
+            dir: default_dir,
+            warning: None,
+        });
+    };
+
+    let rotated_dir = PathBuf::from(&rotated);
+    let warning = if !rotated_dir.is_dir() {
+        format!(
+            "Backup drive for {} ({}) is not available. Backup saved to the default folder instead.",
+            today, rotated
+        )
+    } else if let Err(e) = check_backups_dir_location(app, &rotated_dir) {
+        format!(
+            "Backup folder for {} is not allowed: {} Backup saved to the default folder instead.",
+            today, e
+        )
+    } else {
+        return Ok(BackupDestination {
+            dir: rotated_dir,
+            warning: None,
+        });
+    };
+    log::warn!("{}", warning);
+
+    Ok(BackupDestination {
+        dir: default_dir,
+        warning: Some(warning),
+    })
+}
+
+/// Reads a value from the frontend-managed settings table.
+/// Returns None if the database, the table or the key doesn't exist yet.
+fn get_setting(app: &AppHandle, key: &str) -> Option<String> {
+    let db_path = get_db_path(app).ok()?;
+    if !db_path.exists() {
+        return None;
+    }
+
+    let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY).ok()?;
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        params![key],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .ok()
+    .flatten()
+}
+
+/// Records which backup the live database was restored from in app_meta
+fn stamp_restore_provenance(db_path: &Path, restored_from: &str) -> Result<(), String> {
+    let conn = open_database(db_path, OpenFlags::default())?;
+    conn.execute_batch("CREATE TABLE IF NOT EXISTS app_meta (key TEXT PRIMARY KEY, value TEXT)")
+        .map_err(|e| format!("Failed to create app_meta table: {}", e))?;
+    let restored_at = Local::now().to_rfc3339();
+    for (key, value) in [
+        (LAST_RESTORE_FROM_META_KEY, restored_from),
+        (LAST_RESTORE_AT_META_KEY, restored_at.as_str()),
+    ] {
+        conn.execute(
+            "INSERT OR REPLACE INTO app_meta (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        )
+        .map_err(|e| format!("Failed to record restore provenance: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Writes a value into the settings table (same format the frontend uses)
+fn set_setting(app: &AppHandle, key: &str, value: &str) -> Result<(), String> {
+    let db_path = get_db_path(app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let conn = open_database(&db_path, OpenFlags::default())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES (?1, ?2, CURRENT_TIMESTAMP)",
+        params![key, value],
+    )
+    .map_err(|e| format!("Failed to save setting {}: {}", key, e))?;
+
+    Ok(())
+}
+
+/// Loads the weekday -> directory rotation map, ignoring malformed settings
+fn get_backup_rotation_map(app: &AppHandle) -> HashMap<String, String> {
+    get_setting(app, BACKUP_ROTATION_KEY)
+        .and_then(|raw| serde_json::from_str::<HashMap<String, String>>(&raw).ok())
+        .map(|map| {
+            map.into_iter()
+                .map(|(day, path)| (day.to_lowercase(), path))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Reads up to buf.len() bytes, stopping early only at end of file
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct BackupFileInfo {
-    pub filename: String,
-    pub path: String,
-    pub file_size: u64,
-    pub modified_at: String,
+/// Runs a command with captured output, killing it if it exceeds `timeout`.
+/// A timeout is reported as an io::Error of kind TimedOut.
+#[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
+fn run_command_with_timeout(cmd: &mut Command, timeout: Duration) -> std::io::Result<Output> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Drain pipes on separate threads so a chatty child can't block on a full pipe
+    let mut stdout = child.stdout.take();
+    let mut stderr = child.stderr.take();
+    let stdout_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(out) = stdout.as_mut() {
+            let _ = out.read_to_end(&mut buf);
+        }
+        buf
+    });
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(err) = stderr.as_mut() {
+            let _ = err.read_to_end(&mut buf);
+        }
+        buf
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "command timed out",
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout_reader.join().unwrap_or_default(),
+        stderr: stderr_reader.join().unwrap_or_default(),
+    })
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct RestoreResult {
-    pub success: bool,
-    pub message: String,
-    pub records_imported: usize,
-    pub safety_backup: String,
+/// Minimum gap between unforced backups; zero disables the cooldown
+fn get_backup_cooldown(app: &AppHandle) -> Duration {
+    let secs = get_setting(app, BACKUP_COOLDOWN_KEY)
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_BACKUP_COOLDOWN_SECS);
+    Duration::from_secs(secs)
 }
 
-// Tables to restore in order (respecting foreign key dependencies)
-const DATA_TABLES: &[&str] = &[
-    "products",
-    "invoices", 
-    "invoice_items",
-    "settings",
-    "stock_adjustments",
-    "sales_returns",
-    "return_items",
-    "backup_log",
-    "users",
-];
+/// Registers a running operation for list_active_operations until the guard drops
+fn begin_operation(app: &AppHandle, operation: &str, cancellable: bool) -> OperationGuard {
+    let registry = app.state::<OperationRegistry>();
+    let id = registry.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+    let cancel = Arc::new(AtomicBool::new(false));
+    registry.active.lock().unwrap().insert(
+        id,
+        ActiveOperationEntry {
+            operation: operation.to_string(),
+            started_at: Local::now().to_rfc3339(),
+            progress_percent: None,
+            cancel: cancellable.then(|| cancel.clone()),
+        },
+    );
+    OperationGuard {
+        app: app.clone(),
+        id,
+        cancel,
+    }
+}
 
-// ============================================
-// HELPER FUNCTIONS
-// ============================================
+/// A temp file path no other operation or app instance will pick:
+/// motormods_<kind>_<pid>_<seq>.<ext>
+fn unique_temp_path(kind: &str, extension: &str) -> PathBuf {
+    static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+    let seq = SEQUENCE.fetch_add(1, Ordering::SeqCst);
+    std::env::temp_dir().join(format!(
+        "{}{}_{}_{}.{}",
+        TEMP_FILE_PREFIX,
+        kind,
+        std::process::id(),
+        seq,
+        extension
+    ))
+}
 
-fn get_db_path(app: &AppHandle) -> Result<PathBuf, String> {
-    // tauri-plugin-sql stores databases in the app config directory
-    let app_config_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
-    Ok(app_config_dir.join("motormods.db"))
+/// Creates a new temp file readable only by the current user (0600 on Unix;
+/// on Windows the per-user temp folder already restricts access). Fails
+/// rather than reuse a file that already exists at `path`.
+#[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
+fn write_private_temp_file(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(data)
 }
 
-fn get_backups_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let app_config_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
-    let backups_dir = app_config_dir.join("backups");
+/// The pid embedded by unique_temp_path, if `filename` is one of ours
+fn temp_file_owner_pid(filename: &str) -> Option<u32> {
+    let stem = Path::new(filename.strip_prefix(TEMP_FILE_PREFIX)?).file_stem()?;
+    let mut parts = stem.to_str()?.rsplit('_');
+    parts.next()?.parse::<u64>().ok()?;
+    parts.next()?.parse().ok()
+}
 
-    if !backups_dir.exists() {
-        fs::create_dir_all(&backups_dir).map_err(|e| e.to_string())?;
-    }
+/// Removes temp files left by earlier runs of the app.
+/// Files belonging to this process are kept unless `include_current` is set.
+fn remove_stale_temp_files(include_current: bool) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(std::env::temp_dir()) else {
+        return Vec::new();
+    };
 
-    Ok(backups_dir)
+    let current_pid = std::process::id();
+    let mut removed = Vec::new();
+    for entry in entries.flatten() {
+        let filename = entry.file_name().to_string_lossy().to_string();
+        let Some(pid) = temp_file_owner_pid(&filename) else {
+            continue;
+        };
+        if pid == current_pid && !include_current {
+            continue;
+        }
+        // Scratch folders, e.g. from an interrupted benchmark_backup_formats
+        if entry.path().is_dir() {
+            if fs::remove_dir_all(entry.path()).is_ok() {
+                removed.push(filename);
+            }
+            continue;
+        }
+
+        // Inspection copies are made read-only, which blocks deletion on Windows
+        if let Ok(metadata) = entry.metadata() {
+            let mut permissions = metadata.permissions();
+            if permissions.readonly() {
+                #[allow(clippy::permissions_set_readonly_false)]
+                permissions.set_readonly(false);
+                let _ = fs::set_permissions(entry.path(), permissions);
+            }
+        }
+        if fs::remove_file(entry.path()).is_ok() {
+            removed.push(filename);
+        }
+    }
+    removed.sort();
+    removed
 }
 
 /// Copy all data from one table to another using rusqlite
@@ -127,10 +2127,8 @@ fn copy_table_data(
         })
         .map_err(|e| format!("Failed to query rows: {}", e))?;
 
-    for row in rows {
-        if let Ok(values) = row {
-            rows_data.push(values);
-        }
+    for values in rows.flatten() {
+        rows_data.push(values);
     }
 
     // Insert into main database
@@ -166,491 +2164,1753 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
-/// Creates a backup of the database and returns detailed information
-/// Uses SQLite's backup API to ensure a consistent backup even with WAL mode
+/// Parses a query date bound; a bare date covers that whole local day
+fn parse_query_bound(value: &str, end_of_day: bool) -> Result<chrono::DateTime<Local>, String> {
+    let value = value.trim();
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let time = if end_of_day {
+            chrono::NaiveTime::from_hms_milli_opt(23, 59, 59, 999)
+        } else {
+            chrono::NaiveTime::from_hms_opt(0, 0, 0)
+        };
+        return time
+            .and_then(|t| date.and_time(t).and_local_timezone(Local).earliest())
+            .ok_or_else(|| format!("Invalid date: {}", value));
+    }
+
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Local))
+        .map_err(|_| format!("Invalid date: {}. Expected YYYY-MM-DD or RFC 3339", value))
+}
+
+/// Operation names written by log_operation, accepted by the history filters
+const OPERATION_TYPES: &[&str] = &[
+    "archival_backup",
+    "auto_backup",
+    "backup",
+    "backup_to_destinations",
+    "cleanup_orphan_sidecars",
+    "clear_stale_wal",
+    "delete_backups",
+    "enforce_max_backup_size",
+    "excluding_backup",
+    "export_csv",
+    "export_parquet",
+    "export_receipts_pdf",
+    "import",
+    "import_sql_dump",
+    "migration",
+    "prune_backups",
+    "redacted_backup",
+    "repair",
+    "reset",
+    "restore",
+    "restore_from_url",
+    "restore_to_new",
+    "retention",
+    "scheduled_backup",
+    "set_backup_protected",
+    "shutdown_backup",
+    "switch_database",
+    "sync_from",
+    "trial_restore",
+    "verified_backup",
+    "webhook",
+];
+
+/// Reads operation_history.jsonl newest first, keeping records that match the
+/// filters. Unparseable lines are skipped.
+fn read_operation_history(
+    app: &AppHandle,
+    operation: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+    limit: Option<usize>,
+) -> Result<Vec<OperationRecord>, String> {
+    let operation = operation.map(str::trim).filter(|op| !op.is_empty());
+    if let Some(op) = operation {
+        if !OPERATION_TYPES.contains(&op) {
+            return Err(format!(
+                "Unknown operation type: {}. Expected one of: {}",
+                op,
+                OPERATION_TYPES.join(", ")
+            ));
+        }
+    }
+    let from = from.map(|v| parse_query_bound(v, false)).transpose()?;
+    let to = to.map(|v| parse_query_bound(v, true)).transpose()?;
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err("The 'from' date is after the 'to' date".to_string());
+        }
+    }
+
+    let path = get_app_dir(app)?.join(OPERATION_HISTORY_FILE);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read operation history: {}", e)),
+    };
+
+    let records = contents
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<OperationRecord>(line).ok())
+        .filter(|record| operation.is_none_or(|op| record.operation == op))
+        .filter(|record| {
+            let Ok(at) = chrono::DateTime::parse_from_rfc3339(&record.timestamp) else {
+                return from.is_none() && to.is_none();
+            };
+            from.is_none_or(|from| at >= from) && to.is_none_or(|to| at <= to)
+        })
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+    Ok(records)
+}
+
+/// Database size over the last `days` days (default 30) from sizes recorded
+/// by backups, backup files older than those records, and the live file
+fn database_growth_trend(app: &AppHandle, days: u32) -> Result<DatabaseGrowthTrend, String> {
+    let now = Local::now();
+    let since = now - chrono::Duration::days(days.max(1) as i64);
+    let mut points: Vec<GrowthPoint> = Vec::new();
+    let mut recorded_files = Vec::new();
+
+    for record in read_operation_history(app, None, None, None, None)? {
+        let (Some(size), Ok(at)) = (
+            record.database_size,
+            chrono::DateTime::parse_from_rfc3339(&record.timestamp),
+        ) else {
+            continue;
+        };
+        recorded_files.extend(record.filename.clone());
+        if at >= since {
+            points.push(GrowthPoint {
+                timestamp: record.timestamp,
+                epoch_ms: at.timestamp_millis(),
+                size_bytes: size,
+                source: "history".to_string(),
+            });
+        }
+    }
+
+    // Redacted copies are compacted and smaller, so they'd read as shrinkage
+    for backup in list_backups(app.clone(), None)? {
+        let Some(epoch_ms) = backup.modified_epoch_ms else {
+            continue;
+        };
+        if backup.redacted
+            || recorded_files.contains(&backup.filename)
+            || epoch_ms < since.timestamp_millis()
+        {
+            continue;
+        }
+        points.push(GrowthPoint {
+            timestamp: backup.modified_at,
+            epoch_ms,
+            size_bytes: backup.file_size,
+            source: "backup".to_string(),
+        });
+    }
+
+    if let Ok(metadata) = fs::metadata(get_db_path(app)?) {
+        points.push(GrowthPoint {
+            timestamp: now.to_rfc3339(),
+            epoch_ms: now.timestamp_millis(),
+            size_bytes: metadata.len(),
+            source: "current".to_string(),
+        });
+    }
+    points.sort_by_key(|p| p.epoch_ms);
+
+    let window_start = (now - chrono::Duration::days(GROWTH_RATE_WINDOW_DAYS)).timestamp_millis();
+    let recent: Vec<&GrowthPoint> = points
+        .iter()
+        .filter(|p| p.epoch_ms >= window_start)
+        .collect();
+    let growth_bytes_per_day = match (recent.first(), recent.last()) {
+        // Under an hour apart the rate is mostly noise
+        (Some(first), Some(last)) if last.epoch_ms - first.epoch_ms >= 3_600_000 => {
+            let elapsed_days = (last.epoch_ms - first.epoch_ms) as f64 / 86_400_000.0;
+            Some((last.size_bytes as f64 - first.size_bytes as f64) / elapsed_days)
+        }
+        _ => None,
+    };
+
+    let threshold_bytes_per_day = get_setting(app, GROWTH_ALERT_MB_PER_DAY_KEY)
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_GROWTH_ALERT_MB_PER_DAY)
+        * 1_000_000;
+    Ok(DatabaseGrowthTrend {
+        points,
+        growth_bytes_per_day,
+        threshold_bytes_per_day,
+        alert: growth_bytes_per_day.is_some_and(|rate| rate > threshold_bytes_per_day as f64),
+    })
+}
+
+/// Size-over-time points for the growth chart, plus the recent growth rate
+#[tauri::command]
+fn get_database_growth_trend(
+    app: AppHandle,
+    days: Option<u32>,
+) -> Result<DatabaseGrowthTrend, String> {
+    database_growth_trend(&app, days.unwrap_or(30))
+}
+
+/// Emits "database-growth-alert" when the recent growth rate is over the threshold
+fn check_database_growth(app: &AppHandle) {
+    match database_growth_trend(app, GROWTH_RATE_WINDOW_DAYS as u32) {
+        Ok(trend) if trend.alert => {
+            log::warn!(
+                "Database growing {:.1} MB/day",
+                trend.growth_bytes_per_day.unwrap_or_default() / 1_000_000.0
+            );
+            let _ = app.emit("database-growth-alert", trend);
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("Failed to check database growth: {}", e),
+    }
+}
+
+/// Operation history matching the filters, newest first
+#[tauri::command]
+fn get_operation_history_filtered(
+    app: AppHandle,
+    operation: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    limit: usize,
+) -> Result<Vec<OperationRecord>, String> {
+    read_operation_history(
+        &app,
+        operation.as_deref(),
+        from.as_deref(),
+        to.as_deref(),
+        Some(limit),
+    )
+}
+
+/// Writes the filtered operation history to a CSV file; returns the row count
+#[tauri::command]
+fn export_operation_history_csv(
+    app: AppHandle,
+    destination_path: String,
+    operation: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<usize>,
+) -> Result<usize, String> {
+    let records = read_operation_history(
+        &app,
+        operation.as_deref(),
+        from.as_deref(),
+        to.as_deref(),
+        limit,
+    )?;
+
+    let destination_path = normalize_user_path(&app, &destination_path)?
+        .to_string_lossy()
+        .to_string();
+    let mut writer = csv::Writer::from_path(&destination_path)
+        .map_err(|e| format!("Failed to create {}: {}", destination_path, e))?;
+    writer
+        .write_record([
+            "timestamp",
+            "operation",
+            "success",
+            "details",
+            "filename",
+            "duration_ms",
+            "throughput_mbps",
+        ])
+        .map_err(|e| format!("Failed to write CSV: {}", e))?;
+    for record in &records {
+        writer
+            .write_record([
+                record.timestamp.clone(),
+                record.operation.clone(),
+                record.success.to_string(),
+                record.details.clone(),
+                record.filename.clone().unwrap_or_default(),
+                record
+                    .duration_ms
+                    .map(|v| v.to_string())
+                    .unwrap_or_default(),
+                record
+                    .throughput_mbps
+                    .map(|v| v.to_string())
+                    .unwrap_or_default(),
+            ])
+            .map_err(|e| format!("Failed to write CSV: {}", e))?;
+    }
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to write CSV: {}", e))?;
+
+    Ok(records.len())
+}
+
+// Operations that create a backup file, counted as runs in generate_backup_report
+const BACKUP_OPERATION_TYPES: &[&str] = &[
+    "archival_backup",
+    "auto_backup",
+    "backup",
+    "backup_to_destinations",
+    "excluding_backup",
+    "redacted_backup",
+    "scheduled_backup",
+    "shutdown_backup",
+    "verified_backup",
+];
+
+/// Wipes the database so the app recreates a fresh schema on next connect.
+/// Requires `confirm_token` to equal "RESET" and always takes a safety backup first.
+/// The frontend should close its SQL connection before calling this and reconnect afterwards.
+/// Returns the safety backup filename.
+#[tauri::command]
+fn reset_database(app: AppHandle, confirm_token: String) -> Result<String, String> {
+    require_primary_instance(&app)?;
+    if confirm_token != RESET_CONFIRM_TOKEN {
+        log_operation(
+            &app,
+            OperationRecord::new(
+                "reset",
+                false,
+                "Rejected: missing or wrong confirmation token",
+            ),
+        );
+        return Err(format!(
+            "Reset not confirmed. Pass the confirmation token \"{}\" to proceed.",
+            RESET_CONFIRM_TOKEN
+        ));
+    }
+
+    let db_state = app.state::<DatabaseState>();
+    let _busy = db_state.busy.try_lock().map_err(|_| {
+        "A database operation is in progress. Try again when it finishes.".to_string()
+    })?;
+    let _operation = begin_operation(&app, "reset", false);
+
+    let db_path = get_db_path(&app)?;
+    let backups_dir = get_backups_dir(&app)?;
+
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let safety_filename = safety_backup_filename("reset");
+    let safety_path = backups_dir.join(&safety_filename);
+
+    if let Err(e) = snapshot_database(&db_path, &safety_path) {
+        log_operation(&app, OperationRecord::new("reset", false, e.clone()));
+        return Err(format!("Failed to create safety backup: {}", e));
+    }
+    record_safety_origin(&safety_path, "reset", None);
+
+    if let Err(e) = set_aside_database_files(&db_path) {
+        let message = format!(
+            "Failed to delete database: {}. Nothing was reset; safety backup: {}",
+            e, safety_filename
+        );
+        log_operation(
+            &app,
+            OperationRecord::new("reset", false, message.clone()).with_filename(&safety_filename),
+        );
+        return Err(message);
+    }
+
+    log_operation(
+        &app,
+        OperationRecord::new(
+            "reset",
+            true,
+            "Database deleted; a fresh schema will be created on next connect",
+        )
+        .with_filename(&safety_filename),
+    );
+
+    Ok(safety_filename)
+}
+
+/// Moves the database and its -wal/-shm files out of the way together, then
+/// deletes them. If any rename fails the ones already moved are put back, so a
+/// failure never leaves the main file gone with stale sidecars behind or the
+/// other way round.
+fn set_aside_database_files(db_path: &Path) -> std::io::Result<()> {
+    let [wal, shm] = sqlite_sidecar_paths(db_path);
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for path in [db_path.to_path_buf(), wal, shm] {
+        if !path.exists() {
+            continue;
+        }
+        let aside = PathBuf::from(format!("{}.reset-old", path.to_string_lossy()));
+        if let Err(e) = fs::rename(&path, &aside) {
+            for (original, aside) in moved.iter().rev() {
+                let _ = fs::rename(aside, original);
+            }
+            return Err(e);
+        }
+        moved.push((path, aside));
+    }
+
+    for (_, aside) in moved {
+        if let Err(e) = fs::remove_file(&aside) {
+            log::warn!("Failed to remove {}: {}", aside.display(), e);
+        }
+    }
+    Ok(())
+}
+
+/// Row counts for the app's data tables, read-only.
+/// Counts are exact (COUNT(*)) unless accurate: false, which uses the statistics
+/// from the last refresh_table_statistics where they exist. Those are only as
+/// fresh as that refresh; COUNT(*) covers tables they don't include.
+#[tauri::command]
+fn get_table_row_counts(
+    app: AppHandle,
+    accurate: Option<bool>,
+) -> Result<Vec<TableRowCount>, String> {
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let stats = if accurate.unwrap_or(true) {
+        HashMap::new()
+    } else {
+        read_stat1_counts(&conn)
+    };
+
+    let mut counts = Vec::new();
+    for table in DATA_TABLES {
+        if !table_exists(&conn, table) {
+            continue;
+        }
+
+        if let Some(count) = stats.get(*table) {
+            counts.push(TableRowCount {
+                table: table.to_string(),
+                count: *count,
+                approximate: true,
+            });
+            continue;
+        }
+
+        let count: i64 = conn
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| format!("Failed to count rows in {}: {}", table, e))?;
+        counts.push(TableRowCount {
+            table: table.to_string(),
+            count,
+            approximate: false,
+        });
+    }
+
+    Ok(counts)
+}
+
+/// Re-gathers the query planner statistics (ANALYZE) that approximate row
+/// counts are read from. A maintenance write, so it waits for other database
+/// operations like the other maintenance commands.
+#[tauri::command]
+fn refresh_table_statistics(app: AppHandle) -> Result<(), String> {
+    require_primary_instance(&app)?;
+    let db_state = app.state::<DatabaseState>();
+    let _busy = db_state.busy.try_lock().map_err(|_| {
+        "A database operation is in progress. Try again when it finishes.".to_string()
+    })?;
+
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+    let conn = open_database(&db_path, OpenFlags::default())?;
+    conn.execute_batch("ANALYZE")
+        .map_err(|e| format!("Failed to refresh statistics: {}", e))
+}
+
+/// Backups, restores, imports and exports currently running, oldest first
+#[tauri::command]
+fn list_active_operations(registry: State<'_, OperationRegistry>) -> Vec<ActiveOperation> {
+    let mut operations: Vec<ActiveOperation> = registry
+        .active
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(id, entry)| ActiveOperation {
+            id: *id,
+            operation: entry.operation.clone(),
+            started_at: entry.started_at.clone(),
+            progress_percent: entry.progress_percent,
+            cancellable: entry.cancel.is_some(),
+        })
+        .collect();
+    operations.sort_by_key(|op| op.id);
+    operations
+}
+
+/// Asks a running operation from list_active_operations to stop. Only
+/// operations marked cancellable (currently backups) check for this.
+#[tauri::command]
+fn cancel_backup_operation(
+    registry: State<'_, OperationRegistry>,
+    operation_id: u64,
+) -> Result<(), String> {
+    let active = registry.active.lock().unwrap();
+    let entry = active
+        .get(&operation_id)
+        .ok_or_else(|| format!("No running operation with id {}", operation_id))?;
+    match &entry.cancel {
+        Some(cancel) => {
+            cancel.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+        None => Err(format!(
+            "The {} operation can't be cancelled",
+            entry.operation
+        )),
+    }
+}
+
+/// Gets the name of the database file the app is currently using
+#[tauri::command]
+fn get_active_database(app: AppHandle) -> Result<DatabaseSwitched, String> {
+    let path = get_db_path(&app)?;
+    let name = app
+        .state::<DatabaseState>()
+        .active_name
+        .lock()
+        .map_err(|_| "Database state is unavailable".to_string())?
+        .clone();
+
+    Ok(DatabaseSwitched {
+        connection: database_url(&path),
+        name,
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
+/// Switches the active database file (e.g. "motormods" <-> "training") without a restart.
+/// The choice is saved and used again on the next launch. Call it through
+/// backupService.switchDatabase, which closes the SQL connection first and
+/// reconnects afterwards.
+#[tauri::command]
+fn switch_database(
+    app: AppHandle,
+    db_name: String,
+    create_if_missing: Option<bool>,
+) -> Result<DatabaseSwitched, String> {
+    require_primary_instance(&app)?;
+    let name = validate_db_name(&db_name)?;
+    let db_state = app.state::<DatabaseState>();
+    if db_state.trial.lock().map(|t| t.is_some()).unwrap_or(false) {
+        return Err("A trial restore is active. Commit or abort it first.".to_string());
+    }
+
+    // Refuse to switch underneath a running restore/import transaction
+    let _busy = db_state.busy.try_lock().map_err(|_| {
+        "A database operation is in progress. Try again when it finishes.".to_string()
+    })?;
+
+    let app_config_dir = get_app_dir(&app)?;
+    let path = app_config_dir.join(format!("{}.db", name));
+
+    if !path.exists() {
+        if !create_if_missing.unwrap_or(false) {
+            return Err(format!("Database not found: {}.db", name));
+        }
+        // An empty file is a valid empty SQLite database; the frontend creates the schema
+        fs::File::create(&path).map_err(|e| format!("Failed to create database: {}", e))?;
+    }
+
+    let switched = activate_database(&app, &db_state, &name, &path)?;
+    let saved = app_config_dir.join(ACTIVE_DATABASE_FILE);
+    if let Err(e) = fs::write(&saved, &name) {
+        log::warn!("Failed to save the active database choice: {}", e);
+    }
+    log_operation(
+        &app,
+        OperationRecord::new("switch_database", true, format!("Switched to {}.db", name)),
+    );
+
+    Ok(switched)
+}
+
+/// Points the app's commands at `name` and emits "database-switched"
+fn activate_database(
+    app: &AppHandle,
+    db_state: &DatabaseState,
+    name: &str,
+    path: &Path,
+) -> Result<DatabaseSwitched, String> {
+    *db_state
+        .active_name
+        .lock()
+        .map_err(|_| "Database state is unavailable".to_string())? = name.to_string();
+
+    let switched = DatabaseSwitched {
+        connection: database_url(path),
+        name: name.to_string(),
+        path: path.to_string_lossy().to_string(),
+    };
+    let _ = app.emit("database-switched", switched.clone());
+    Ok(switched)
+}
+
+/// Runs PRAGMA integrity_check and returns its first line ("ok" when healthy)
+fn run_integrity_check(conn: &Connection) -> String {
+    conn.query_row("PRAGMA integrity_check", [], |row| row.get::<_, String>(0))
+        .unwrap_or_else(|e| e.to_string())
+}
+
+/// Reports whether the app's connections enforce foreign keys.
+/// The frontend's sql plugin connection has its own setting and must run
+/// PRAGMA foreign_keys = ON itself after connecting.
+#[tauri::command]
+fn get_foreign_keys_enabled(app: AppHandle) -> Result<bool, String> {
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Ok(FOREIGN_KEYS_ENABLED.load(Ordering::SeqCst));
+    }
+
+    let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    conn.query_row("PRAGMA foreign_keys", [], |row| row.get::<_, bool>(0))
+        .map_err(|e| format!("Failed to read foreign key setting: {}", e))
+}
+
+/// Turns foreign key enforcement on or off for every connection the backend opens,
+/// and remembers the choice in settings
+#[tauri::command]
+fn set_foreign_keys_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    set_setting(&app, FOREIGN_KEYS_KEY, if enabled { "1" } else { "0" })?;
+    FOREIGN_KEYS_ENABLED.store(enabled, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Reads back the synchronous and journal_mode pragmas as their names
+fn read_durability_pragmas(conn: &Connection) -> Result<(String, String), String> {
+    let synchronous: i64 = conn
+        .query_row("PRAGMA synchronous", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read synchronous: {}", e))?;
+    let journal_mode: String = conn
+        .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read journal_mode: {}", e))?;
+    let synchronous = match synchronous {
+        0 => "OFF".to_string(),
+        1 => "NORMAL".to_string(),
+        2 => "FULL".to_string(),
+        3 => "EXTRA".to_string(),
+        other => other.to_string(),
+    };
+    Ok((synchronous, journal_mode.to_ascii_uppercase()))
+}
+
+/// Reports the durability profile and the pragmas a new connection gets.
+/// The frontend's sql plugin connection applies synchronous itself after connecting.
+#[tauri::command]
+fn get_durability_profile(app: AppHandle) -> Result<DurabilityProfile, String> {
+    let preset = *DURABILITY_PROFILE.lock().unwrap();
+    let db_path = get_db_path(&app)?;
+    let (synchronous, journal_mode) = if db_path.exists() {
+        let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        read_durability_pragmas(&conn)?
+    } else {
+        match preset {
+            Some((_, synchronous, journal_mode)) => {
+                (synchronous.to_string(), journal_mode.to_string())
+            }
+            None => ("FULL".to_string(), "DELETE".to_string()),
+        }
+    };
+    Ok(DurabilityProfile {
+        profile: preset.map(|(name, _, _)| name.to_string()),
+        synchronous,
+        journal_mode,
+        warning: preset
+            .filter(|(name, _, _)| *name == "fast")
+            .map(|_| FAST_DURABILITY_WARNING.to_string()),
+    })
+}
+
+/// Switches the durability preset: "safe" (synchronous=FULL, WAL), "balanced"
+/// (synchronous=NORMAL, WAL) or "fast" (synchronous=OFF, WAL). The choice is saved
+/// in settings and applied to every connection the backend opens.
+#[tauri::command]
+fn set_durability_profile(app: AppHandle, profile: String) -> Result<DurabilityProfile, String> {
+    require_primary_instance(&app)?;
+    let name = profile.trim().to_ascii_lowercase();
+    let preset = DURABILITY_PRESETS
+        .iter()
+        .find(|(preset_name, _, _)| *preset_name == name)
+        .ok_or_else(|| {
+            format!(
+                "Unknown durability profile '{}'. Use one of: {}",
+                profile,
+                DURABILITY_PRESETS
+                    .iter()
+                    .map(|(n, _, _)| *n)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })?;
+    let (_, synchronous, journal_mode) = preset;
+
+    let db_state = app.state::<DatabaseState>();
+    let _busy = db_state.busy.try_lock().map_err(|_| {
+        "A database operation is in progress. Try again when it finishes.".to_string()
+    })?;
+
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let conn = open_database(&db_path, OpenFlags::default())?;
+    // journal_mode is stored in the database file, so setting it once persists it
+    let applied: String = conn
+        .query_row(
+            &format!("PRAGMA journal_mode = {}", journal_mode),
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to set journal_mode: {}", e))?;
+    if !applied.eq_ignore_ascii_case(journal_mode) {
+        return Err(format!(
+            "Could not switch journal_mode to {} (still {}); close other connections and retry",
+            journal_mode, applied
+        ));
+    }
+    conn.execute_batch(&format!("PRAGMA synchronous = {}", synchronous))
+        .map_err(|e| format!("Failed to set synchronous: {}", e))?;
+    let (synchronous, journal_mode) = read_durability_pragmas(&conn)?;
+
+    set_setting(&app, DURABILITY_PROFILE_KEY, &name)?;
+    *DURABILITY_PROFILE.lock().unwrap() = Some(preset);
+
+    let warning = (name == "fast").then(|| FAST_DURABILITY_WARNING.to_string());
+    if let Some(warning) = &warning {
+        log::warn!("{}", warning);
+    }
+
+    Ok(DurabilityProfile {
+        profile: Some(name),
+        synchronous,
+        journal_mode,
+        warning,
+    })
+}
+
+/// Reports the journal mode, the -wal file, the busy timeout and the last
+/// `limit` (default 20) lock retry runs, newest first. `clear` empties the
+/// recorded retries after reading them.
+#[tauri::command]
+fn get_lock_diagnostics(
+    app: AppHandle,
+    limit: Option<usize>,
+    clear: Option<bool>,
+) -> Result<LockDiagnostics, String> {
+    let db_path = get_db_path(&app)?;
+    let journal_mode = if db_path.exists() {
+        read_durability_pragmas(&open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?)?.1
+    } else {
+        "unknown".to_string()
+    };
+    let [wal_path, _] = sqlite_sidecar_paths(&db_path);
+    let wal_size = fs::metadata(&wal_path).ok().map(|m| m.len());
+
+    let mut events = LOCK_RETRY_EVENTS.lock().unwrap();
+    let recent_retries = events
+        .iter()
+        .rev()
+        .take(limit.unwrap_or(20))
+        .cloned()
+        .collect();
+    if clear.unwrap_or(false) {
+        events.clear();
+    }
+
+    Ok(LockDiagnostics {
+        journal_mode,
+        wal_present: wal_size.is_some(),
+        wal_size: wal_size.unwrap_or(0),
+        busy_timeout_ms: BUSY_TIMEOUT_MS.load(Ordering::SeqCst),
+        recent_retries,
+    })
+}
+
+/// Sets how long the app's connections wait on a locked database before
+/// failing with "database is locked". Saved in settings; connections opened
+/// from now on use it, and the frontend applies it when it reconnects.
+#[tauri::command]
+fn set_busy_timeout(app: AppHandle, ms: u32) -> Result<(), String> {
+    if ms > MAX_BUSY_TIMEOUT_MS {
+        return Err(format!(
+            "Busy timeout must be at most {} ms",
+            MAX_BUSY_TIMEOUT_MS
+        ));
+    }
+    set_setting(&app, BUSY_TIMEOUT_KEY, &ms.to_string())?;
+    BUSY_TIMEOUT_MS.store(ms as u64, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Runs integrity_check and foreign_key_check on the live database
 #[tauri::command]
-fn backup_database(app: AppHandle) -> Result<BackupResult, String> {
+fn check_database_integrity(app: AppHandle) -> Result<IntegrityReport, String> {
     let db_path = get_db_path(&app)?;
-    let backups_dir = get_backups_dir(&app)?;
-
-    // Verify source database exists
     if !db_path.exists() {
         return Err("Database file not found".to_string());
     }
 
-    // Generate backup filename with timestamp
-    let timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
-    let backup_filename = format!("motormods_backup_{}.db", timestamp);
-    let backup_path = backups_dir.join(&backup_filename);
+    let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let integrity = run_integrity_check(&conn);
 
-    // Use SQLite's backup API for a proper backup that handles WAL mode
-    // This ensures all data (including WAL) is included in the backup
-    let source_conn = Connection::open(&db_path)
-        .map_err(|e| format!("Failed to open source database: {}", e))?;
-    
-    let mut backup_conn = Connection::open(&backup_path)
-        .map_err(|e| format!("Failed to create backup database: {}", e))?;
+    let mut stmt = conn
+        .prepare("PRAGMA foreign_key_check")
+        .map_err(|e| format!("Failed to run foreign key check: {}", e))?;
+    let violations: Vec<ForeignKeyViolation> = stmt
+        .query_map([], |row| {
+            Ok(ForeignKeyViolation {
+                table: row.get(0)?,
+                rowid: row.get(1)?,
+                parent: row.get(2)?,
+            })
+        })
+        .map_err(|e| format!("Failed to run foreign key check: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
 
-    // Use SQLite's backup API
-    let backup = rusqlite::backup::Backup::new(&source_conn, &mut backup_conn)
-        .map_err(|e| format!("Failed to initialize backup: {}", e))?;
-    
-    // Run the backup (copy all pages, -1 means copy all at once)
-    backup.run_to_completion(100, std::time::Duration::from_millis(10), None)
-        .map_err(|e| format!("Failed to complete backup: {}", e))?;
-
-    // Get file size
-    let metadata =
-        fs::metadata(&backup_path).map_err(|e| format!("Failed to get backup metadata: {}", e))?;
-
-    Ok(BackupResult {
-        filename: backup_filename,
-        path: backup_path.to_string_lossy().to_string(),
-        file_size: metadata.len(),
-        created_at: Local::now().to_rfc3339(),
+    Ok(IntegrityReport {
+        integrity,
+        foreign_keys_enabled: FOREIGN_KEYS_ENABLED.load(Ordering::SeqCst),
+        foreign_key_violation_count: violations.len(),
+        foreign_key_violations: violations
+            .into_iter()
+            .take(MAX_REPORTED_FK_VIOLATIONS)
+            .collect(),
     })
 }
 
-/// Lists all backup files in the backups directory
+/// Read-only SQLite diagnostics for the live database. cache_size and
+/// synchronous are per connection, so they show what a fresh connection gets.
 #[tauri::command]
-fn list_backups(app: AppHandle) -> Result<Vec<BackupFileInfo>, String> {
-    let backups_dir = get_backups_dir(&app)?;
-
-    let mut backups: Vec<BackupFileInfo> = Vec::new();
-
-    if let Ok(entries) = fs::read_dir(&backups_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().map_or(false, |ext| ext == "db") {
-                if let Ok(metadata) = fs::metadata(&path) {
-                    let modified = metadata
-                        .modified()
-                        .map(|t| {
-                            let datetime: chrono::DateTime<Local> = t.into();
-                            datetime.to_rfc3339()
-                        })
-                        .unwrap_or_else(|_| "Unknown".to_string());
-
-                    backups.push(BackupFileInfo {
-                        filename: path
-                            .file_name()
-                            .map(|n| n.to_string_lossy().to_string())
-                            .unwrap_or_default(),
-                        path: path.to_string_lossy().to_string(),
-                        file_size: metadata.len(),
-                        modified_at: modified,
-                    });
-                }
-            }
-        }
+fn get_sqlite_runtime_stats(app: AppHandle) -> Result<SqliteRuntimeStats, String> {
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
     }
 
-    // Sort by modified date descending
-    backups.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+    let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let pragma_i64 = |name: &str| -> Result<i64, String> {
+        conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get(0))
+            .map_err(|e| format!("Failed to read {}: {}", name, e))
+    };
+
+    let mut stmt = conn
+        .prepare("PRAGMA compile_options")
+        .map_err(|e| format!("Failed to read compile options: {}", e))?;
+    let compile_options: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| format!("Failed to read compile options: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
 
-    Ok(backups)
+    Ok(SqliteRuntimeStats {
+        cache_size: pragma_i64("cache_size")?,
+        page_size: pragma_i64("page_size")?,
+        page_count: pragma_i64("page_count")?,
+        freelist_count: pragma_i64("freelist_count")?,
+        journal_mode: conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to read journal_mode: {}", e))?,
+        synchronous: pragma_i64("synchronous")?,
+        compile_options,
+    })
 }
 
-/// Restores the database from a backup file
+/// Checks the live database's -wal/-shm files for leftovers from a crash
 #[tauri::command]
-fn restore_database(app: AppHandle, backup_filename: String) -> Result<String, String> {
+fn check_wal_consistency(app: AppHandle) -> Result<WalConsistency, String> {
     let db_path = get_db_path(&app)?;
-    let backups_dir = get_backups_dir(&app)?;
-    let backup_path = backups_dir.join(&backup_filename);
+    Ok(inspect_wal(&db_path))
+}
 
-    // Verify backup exists
-    if !backup_path.exists() {
-        return Err(format!("Backup file not found: {}", backup_filename));
+/// Removes -wal/-shm files that can't belong to the current database.
+/// Returns the paths removed; a healthy WAL is left alone.
+#[tauri::command]
+fn clear_stale_wal(app: AppHandle) -> Result<Vec<String>, String> {
+    let db_state = app.state::<DatabaseState>();
+    let _busy = db_state.busy.try_lock().map_err(|_| {
+        "A database operation is in progress. Try again when it finishes.".to_string()
+    })?;
+    let db_path = get_db_path(&app)?;
+    let removed = remove_stale_sidecars(&app, &db_path)?;
+    if !removed.is_empty() {
+        log_operation(
+            &app,
+            OperationRecord::new(
+                "clear_stale_wal",
+                true,
+                format!("Removed {}", removed.join(", ")),
+            ),
+        );
     }
+    Ok(removed)
+}
 
-    // Create a safety backup of current database before restore
-    let safety_filename = format!(
-        "pre_restore_safety_{}.db",
-        Local::now().format("%Y-%m-%d_%H-%M-%S")
-    );
-    let safety_path = backups_dir.join(&safety_filename);
+/// Reads the tables and declared column types of a database
+fn read_live_schema(conn: &Connection) -> Result<SchemaDefinition, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+             ORDER BY name",
+        )
+        .map_err(|e| format!("Failed to read schema: {}", e))?;
+    let table_names: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| format!("Failed to read schema: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
 
-    if db_path.exists() {
-        fs::copy(&db_path, &safety_path)
-            .map_err(|e| format!("Failed to create safety backup: {}", e))?;
+    let mut tables = Vec::with_capacity(table_names.len());
+    for name in table_names {
+        let mut info = conn
+            .prepare(&format!(
+                "PRAGMA table_info(\"{}\")",
+                name.replace('"', "\"\"")
+            ))
+            .map_err(|e| format!("Failed to read columns of {}: {}", name, e))?;
+        let columns: Vec<ColumnDefinition> = info
+            .query_map([], |row| {
+                Ok(ColumnDefinition {
+                    name: row.get(1)?,
+                    column_type: row.get(2)?,
+                })
+            })
+            .map_err(|e| format!("Failed to read columns of {}: {}", name, e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        tables.push(TableDefinition { name, columns });
     }
 
-    // Perform the restore
-    fs::copy(&backup_path, &db_path).map_err(|e| format!("Failed to restore database: {}", e))?;
-
-    Ok(format!(
-        "Database restored from {}. Safety backup created: {}",
-        backup_filename, safety_filename
-    ))
+    Ok(SchemaDefinition { tables })
 }
 
-/// Restores from an external backup file path
-#[tauri::command]
-fn import_backup(app: AppHandle, source_path: String) -> Result<String, String> {
-    let db_path = get_db_path(&app)?;
-    let backups_dir = get_backups_dir(&app)?;
-    let source = PathBuf::from(&source_path);
+/// Compares an actual schema against the expected one. Types are compared
+/// case-insensitively since SQLite keeps whatever the CREATE statement used.
+fn diff_schema(expected: &SchemaDefinition, actual: &SchemaDefinition) -> SchemaDiff {
+    let mut diff = SchemaDiff::default();
 
-    // Verify source exists and is a .db file
-    if !source.exists() {
-        return Err("Source backup file not found".to_string());
-    }
+    for table in &expected.tables {
+        let Some(live) = actual.tables.iter().find(|t| t.name == table.name) else {
+            diff.missing_tables.push(table.name.clone());
+            continue;
+        };
 
-    if source.extension().map_or(true, |ext| ext != "db") {
-        return Err("Invalid backup file. Expected .db file".to_string());
-    }
+        for column in &table.columns {
+            match live.columns.iter().find(|c| c.name == column.name) {
+                None => diff.missing_columns.push(ColumnRef {
+                    table: table.name.clone(),
+                    column: column.name.clone(),
+                }),
+                Some(found)
+                    if !found
+                        .column_type
+                        .trim()
+                        .eq_ignore_ascii_case(column.column_type.trim()) =>
+                {
+                    diff.type_mismatches.push(ColumnTypeMismatch {
+                        table: table.name.clone(),
+                        column: column.name.clone(),
+                        expected: column.column_type.clone(),
+                        actual: found.column_type.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
 
-    // Create a safety backup first
-    let safety_filename = format!(
-        "pre_import_safety_{}.db",
-        Local::now().format("%Y-%m-%d_%H-%M-%S")
-    );
-    let safety_path = backups_dir.join(&safety_filename);
+        for column in &live.columns {
+            if !table.columns.iter().any(|c| c.name == column.name) {
+                diff.extra_columns.push(ColumnRef {
+                    table: table.name.clone(),
+                    column: column.name.clone(),
+                });
+            }
+        }
+    }
 
-    if db_path.exists() {
-        fs::copy(&db_path, &safety_path)
-            .map_err(|e| format!("Failed to create safety backup: {}", e))?;
+    for table in &actual.tables {
+        if !expected.tables.iter().any(|t| t.name == table.name) {
+            diff.extra_tables.push(table.name.clone());
+        }
     }
 
-    // Restore from external file
-    fs::copy(&source, &db_path).map_err(|e| format!("Failed to import backup: {}", e))?;
+    diff.matches = diff.missing_tables.is_empty()
+        && diff.extra_tables.is_empty()
+        && diff.missing_columns.is_empty()
+        && diff.extra_columns.is_empty()
+        && diff.type_mismatches.is_empty();
+    diff
+}
 
-    Ok(format!(
-        "Database imported from external backup. Safety backup created: {}",
-        safety_filename
-    ))
+fn embedded_expected_schema() -> Result<SchemaDefinition, String> {
+    serde_json::from_str(EXPECTED_SCHEMA_JSON)
+        .map_err(|e| format!("Embedded schema definition is invalid: {}", e))
 }
 
-/// Exports a backup to a specified destination
+/// SHA-256 per table over its columns and rows, so changed tables can be spotted
+/// without comparing files. Reads the live database, or a backup when named.
 #[tauri::command]
-fn export_backup(
+fn table_checksums(
     app: AppHandle,
-    backup_filename: String,
-    destination_path: String,
-) -> Result<String, String> {
-    let backups_dir = get_backups_dir(&app)?;
-    let backup_path = backups_dir.join(&backup_filename);
-    let destination = PathBuf::from(&destination_path);
-
-    if !backup_path.exists() {
-        return Err(format!("Backup file not found: {}", backup_filename));
+    backup_filename: Option<String>,
+) -> Result<HashMap<String, String>, String> {
+    let db_path = match backup_filename {
+        Some(filename) => resolve_backup_file(&app, &filename)?,
+        None => get_db_path(&app)?,
+    };
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
     }
 
-    fs::copy(&backup_path, &destination).map_err(|e| format!("Failed to export backup: {}", e))?;
-
-    Ok(format!("Backup exported to: {}", destination_path))
+    let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let schema = read_live_schema(&conn)?;
+    schema
+        .tables
+        .iter()
+        .map(|table| {
+            Ok((
+                table.name.clone(),
+                compute_table_checksum(&conn, &table.name)?,
+            ))
+        })
+        .collect()
 }
 
-/// Deletes a specific backup file
-#[tauri::command]
-fn delete_backup(app: AppHandle, backup_filename: String) -> Result<String, String> {
-    let backups_dir = get_backups_dir(&app)?;
-    let backup_path = backups_dir.join(&backup_filename);
-
-    if !backup_path.exists() {
-        return Err(format!("Backup file not found: {}", backup_filename));
-    }
-
-    // Safety check: don't allow deleting non-.db files
-    if backup_path.extension().map_or(true, |ext| ext != "db") {
-        return Err("Can only delete .db backup files".to_string());
-    }
-
-    fs::remove_file(&backup_path).map_err(|e| format!("Failed to delete backup: {}", e))?;
+/// Hashes a table's column names and then every row in primary-key order (all
+/// columns when there is no declared key), so physical row order doesn't matter.
+/// Values are type-tagged and length-prefixed to keep the encoding unambiguous.
+fn compute_table_checksum(conn: &Connection, table: &str) -> Result<String, String> {
+    use rusqlite::types::ValueRef;
+    use sha2::Digest;
 
-    Ok(format!("Backup deleted: {}", backup_filename))
-}
+    let quoted = format!("\"{}\"", table.replace('"', "\"\""));
+    let mut info = conn
+        .prepare(&format!("PRAGMA table_info({})", quoted))
+        .map_err(|e| format!("Failed to read columns of {}: {}", table, e))?;
+    let columns: Vec<(String, i64)> = info
+        .query_map([], |row| Ok((row.get(1)?, row.get(5)?)))
+        .map_err(|e| format!("Failed to read columns of {}: {}", table, e))?
+        .filter_map(|r| r.ok())
+        .collect();
 
-/// Gets the backups directory path for the file picker
-#[tauri::command]
-fn get_backups_path(app: AppHandle) -> Result<String, String> {
-    let backups_dir = get_backups_dir(&app)?;
-    Ok(backups_dir.to_string_lossy().to_string())
-}
+    let mut key_columns: Vec<&(String, i64)> = columns.iter().filter(|(_, pk)| *pk > 0).collect();
+    key_columns.sort_by_key(|(_, pk)| *pk);
+    let order_by = if key_columns.is_empty() {
+        (1..=columns.len())
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    } else {
+        key_columns
+            .iter()
+            .map(|(name, _)| format!("\"{}\"", name.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
 
-/// Gets the full path to a specific backup file
-#[tauri::command]
-fn get_backup_file_path(app: AppHandle, backup_filename: String) -> Result<String, String> {
-    let backups_dir = get_backups_dir(&app)?;
-    let backup_path = backups_dir.join(&backup_filename);
+    let mut hasher = sha2::Sha256::new();
+    for (name, _) in &columns {
+        hasher.update((name.len() as u64).to_le_bytes());
+        hasher.update(name.as_bytes());
+    }
 
-    if !backup_path.exists() {
-        return Err(format!("Backup file not found: {}", backup_filename));
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM {} ORDER BY {}", quoted, order_by))
+        .map_err(|e| format!("Failed to read {}: {}", table, e))?;
+    let column_count = stmt.column_count();
+    let mut rows = stmt
+        .query([])
+        .map_err(|e| format!("Failed to read {}: {}", table, e))?;
+    while let Some(row) = rows
+        .next()
+        .map_err(|e| format!("Failed to read {}: {}", table, e))?
+    {
+        for i in 0..column_count {
+            match row.get_ref(i).unwrap_or(ValueRef::Null) {
+                ValueRef::Null => hasher.update(b"n"),
+                ValueRef::Integer(v) => {
+                    hasher.update(b"i");
+                    hasher.update(v.to_le_bytes());
+                }
+                ValueRef::Real(v) => {
+                    hasher.update(b"r");
+                    hasher.update(v.to_bits().to_le_bytes());
+                }
+                ValueRef::Text(bytes) => {
+                    hasher.update(b"t");
+                    hasher.update((bytes.len() as u64).to_le_bytes());
+                    hasher.update(bytes);
+                }
+                ValueRef::Blob(bytes) => {
+                    hasher.update(b"b");
+                    hasher.update((bytes.len() as u64).to_le_bytes());
+                    hasher.update(bytes);
+                }
+            }
+        }
     }
 
-    Ok(backup_path.to_string_lossy().to_string())
+    Ok(format!("{:x}", hasher.finalize()))
 }
 
-/// Creates a safety backup of the current database before import operations
+/// Compares the live database schema against `expected`, or against the schema
+/// definition shipped with this app version when none is given
 #[tauri::command]
-fn create_safety_backup(app: AppHandle) -> Result<String, String> {
-    let db_path = get_db_path(&app)?;
-    let backups_dir = get_backups_dir(&app)?;
+fn validate_schema(
+    app: AppHandle,
+    expected: Option<SchemaDefinition>,
+) -> Result<SchemaDiff, String> {
+    let expected = match expected {
+        Some(expected) => expected,
+        None => embedded_expected_schema()?,
+    };
 
+    let db_path = get_db_path(&app)?;
     if !db_path.exists() {
         return Err("Database file not found".to_string());
     }
 
-    let safety_filename = format!(
-        "pre_import_safety_{}.db",
-        Local::now().format("%Y-%m-%d_%H-%M-%S")
-    );
-    let safety_path = backups_dir.join(&safety_filename);
+    let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let actual = read_live_schema(&conn)?;
+    Ok(diff_schema(&expected, &actual))
+}
 
-    fs::copy(&db_path, &safety_path)
-        .map_err(|e| format!("Failed to create safety backup: {}", e))?;
+/// Copies a table row by row, stopping at the first unreadable row.
+/// Returns the rows copied and the read error if the table was cut short.
+fn salvage_table_rows(
+    source: &Connection,
+    destination: &Connection,
+    table_name: &str,
+) -> Result<(i64, Option<String>), String> {
+    let quoted = format!("\"{}\"", table_name.replace('"', "\"\""));
+    let mut select = source
+        .prepare(&format!("SELECT * FROM {}", quoted))
+        .map_err(|e| format!("Failed to read {}: {}", table_name, e))?;
+    let column_count = select.column_count();
+    let placeholders = vec!["?"; column_count].join(", ");
+    let mut insert = destination
+        .prepare(&format!("INSERT INTO {} VALUES ({})", quoted, placeholders))
+        .map_err(|e| format!("Failed to prepare insert for {}: {}", table_name, e))?;
 
-    Ok(safety_filename)
-}
+    let mut rows = select
+        .query([])
+        .map_err(|e| format!("Failed to read {}: {}", table_name, e))?;
+    let mut copied = 0;
+    loop {
+        let row = match rows.next() {
+            Ok(Some(row)) => row,
+            Ok(None) => return Ok((copied, None)),
+            Err(e) => return Ok((copied, Some(e.to_string()))),
+        };
 
-/// Restores database by importing data from a backup file
-/// This uses rusqlite directly to handle the data import properly
-/// Much more robust than file replacement - works without app restart
-#[tauri::command]
-fn restore_data_from_backup(app: AppHandle, backup_path: String) -> Result<RestoreResult, String> {
-    let db_path = get_db_path(&app)?;
-    let backups_dir = get_backups_dir(&app)?;
-    let backup_file = PathBuf::from(&backup_path);
+        let values: Vec<rusqlite::types::Value> = match (0..column_count)
+            .map(|i| row.get(i))
+            .collect::<Result<_, _>>()
+        {
+            Ok(values) => values,
+            Err(e) => return Ok((copied, Some(e.to_string()))),
+        };
 
-    // Verify backup exists
-    if !backup_file.exists() {
-        return Err(format!("Backup file not found: {}", backup_path));
+        insert
+            .execute(rusqlite::params_from_iter(values.iter()))
+            .map_err(|e| format!("Failed to write {}: {}", table_name, e))?;
+        copied += 1;
     }
+}
 
-    // Create a safety backup first
-    let safety_filename = format!(
-        "pre_restore_safety_{}.db",
-        Local::now().format("%Y-%m-%d_%H-%M-%S")
-    );
-    let safety_path = backups_dir.join(&safety_filename);
+/// Dumps every readable object from `source` into a fresh database at `destination`.
+/// Anything that can't be carried over is reported in the result's `dropped` list.
+fn rebuild_database(
+    app: &AppHandle,
+    source: &Connection,
+    destination: &Path,
+) -> Result<(i64, Vec<RepairLoss>), String> {
+    let mut objects_stmt = source
+        .prepare(
+            "SELECT type, name, sql FROM sqlite_master
+             WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%'
+             ORDER BY CASE type WHEN 'table' THEN 0 WHEN 'index' THEN 1 ELSE 2 END",
+        )
+        .map_err(|e| format!("Failed to read schema: {}", e))?;
+    let objects: Vec<(String, String, String)> = objects_stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| format!("Failed to read schema: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut conn = Connection::open(destination)
+        .map_err(|e| format!("Failed to create repaired database: {}", e))?;
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
 
-    if db_path.exists() {
-        fs::copy(&db_path, &safety_path)
-            .map_err(|e| format!("Failed to create safety backup: {}", e))?;
+    let mut dropped = Vec::new();
+    let mut tables = Vec::new();
+    for (kind, name, sql) in &objects {
+        if kind != "table" {
+            continue;
+        }
+        match tx.execute_batch(sql) {
+            Ok(()) => tables.push(name.clone()),
+            Err(e) => dropped.push(RepairLoss {
+                object: name.clone(),
+                detail: format!("Table could not be recreated: {}", e),
+            }),
+        }
     }
 
-    // Open both databases
-    let backup_conn = Connection::open(&backup_file)
-        .map_err(|e| format!("Failed to open backup database: {}", e))?;
-    
-    let main_conn = Connection::open(&db_path)
-        .map_err(|e| format!("Failed to open main database: {}", e))?;
+    // AUTOINCREMENT counters live in sqlite_sequence, which SQLite creates on demand
+    if table_exists(source, "sqlite_sequence") && table_exists(&tx, "sqlite_sequence") {
+        tables.push("sqlite_sequence".to_string());
+    }
 
-    // Disable foreign keys for the import
-    main_conn
-        .execute("PRAGMA foreign_keys = OFF", [])
-        .map_err(|e| format!("Failed to disable foreign keys: {}", e))?;
+    let mut rows_recovered = 0;
+    for (index, table) in tables.iter().enumerate() {
+        let _ = app.emit(
+            "repair-progress",
+            RepairProgress {
+                current: index + 1,
+                total: tables.len(),
+                table: table.clone(),
+            },
+        );
 
-    // Start a transaction
-    main_conn
-        .execute("BEGIN TRANSACTION", [])
-        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let expected: Option<i64> = source
+            .query_row(
+                &format!("SELECT COUNT(*) FROM \"{}\"", table.replace('"', "\"\"")),
+                [],
+                |row| row.get(0),
+            )
+            .ok();
 
-    let mut total_imported = 0;
+        let (copied, read_error) = salvage_table_rows(source, &tx, table)?;
+        rows_recovered += copied;
 
-    // Clear and import each table
-    for table in DATA_TABLES.iter().rev() {
-        // Clear table first (reverse order for foreign keys)
-        if let Err(e) = main_conn.execute(&format!("DELETE FROM {}", table), []) {
-            eprintln!("Warning: Could not clear table {}: {}", table, e);
+        if let Some(error) = read_error {
+            let lost = expected
+                .map(|total| format!("{} of {} rows", (total - copied).max(0), total))
+                .unwrap_or_else(|| "remaining rows".to_string());
+            dropped.push(RepairLoss {
+                object: table.clone(),
+                detail: format!("Dropped {} after read error: {}", lost, error),
+            });
         }
     }
 
-    // Import data in forward order
-    for table in DATA_TABLES.iter() {
-        match copy_table_data(&backup_conn, &main_conn, table) {
-            Ok(count) => {
-                println!("[Restore] Imported {} rows into {}", count, table);
-                total_imported += count;
-            }
-            Err(e) => {
-                eprintln!("Warning: Error importing {}: {}", table, e);
-                // Continue with other tables
-            }
+    for (kind, name, sql) in &objects {
+        if kind == "table" {
+            continue;
+        }
+        if let Err(e) = tx.execute_batch(sql) {
+            dropped.push(RepairLoss {
+                object: name.clone(),
+                detail: format!("{} could not be recreated: {}", kind, e),
+            });
         }
     }
 
-    // Commit the transaction
-    if let Err(e) = main_conn.execute("COMMIT", []) {
-        // Try to rollback
-        let _ = main_conn.execute("ROLLBACK", []);
-        return Err(format!("Failed to commit transaction: {}", e));
-    }
+    let user_version: i64 = source
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .unwrap_or(0);
+    tx.execute_batch(&format!("PRAGMA user_version = {}", user_version))
+        .map_err(|e| format!("Failed to set schema version: {}", e))?;
 
-    // Re-enable foreign keys
-    let _ = main_conn.execute("PRAGMA foreign_keys = ON", []);
+    tx.commit()
+        .map_err(|e| format!("Failed to commit repaired database: {}", e))?;
 
-    Ok(RestoreResult {
-        success: true,
-        message: format!("Successfully restored {} records from backup", total_imported),
-        records_imported: total_imported,
-        safety_backup: safety_filename,
-    })
+    Ok((rows_recovered, dropped))
 }
 
-/// Restores database by importing data from a backup file in the backups directory
+/// Rebuilds a database that fails integrity_check by dumping it into a fresh file.
+/// The rebuilt copy replaces the original only if it passes integrity_check;
+/// the original is kept as a pre_repair_safety_ backup. Emits "repair-progress".
+/// The frontend must close its SQL connection first (backupService.repairDatabase
+/// does); the swap is refused while the -wal file still holds live changes.
 #[tauri::command]
-fn restore_data_from_backup_file(app: AppHandle, backup_filename: String) -> Result<RestoreResult, String> {
-    let backups_dir = get_backups_dir(&app)?;
-    let backup_path = backups_dir.join(&backup_filename);
-    
-    restore_data_from_backup(app, backup_path.to_string_lossy().to_string())
-}
+async fn repair_database(app: AppHandle) -> Result<RepairResult, String> {
+    let worker_app = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || run_database_repair(&worker_app))
+        .await
+        .map_err(|e| format!("Repair task failed: {}", e))
+        .and_then(|r| r);
 
-#[tauri::command]
-fn print_receipt(text: String) -> Result<(), String> {
-    #[cfg(target_os = "linux")]
+    let record = match &result {
+        Ok(r) => OperationRecord::new("repair", true, r.message.clone()),
+        Err(e) => OperationRecord::new("repair", false, e.clone()),
+    };
+    let record = match result
+        .as_ref()
+        .ok()
+        .and_then(|r| r.safety_backup.as_deref())
     {
-        let lpstat = Command::new("lpstat")
-            .arg("-p")
-            .output()
-            .map_err(|e| format!("Printing not available (lpstat not found): {e}"))?;
-
-        if !lpstat.status.success() {
-            let stderr = String::from_utf8_lossy(&lpstat.stderr);
-            return Err(format!("Printer status check failed: {stderr}"));
-        }
-
-        let stdout = String::from_utf8_lossy(&lpstat.stdout);
-        let has_printer = stdout
-            .lines()
-            .any(|l| l.starts_with("printer ") || l.contains(" printer "));
-        if !has_printer {
-            return Err(
-                "No printer configured. Please add/connect a printer in system settings (CUPS)."
-                    .to_string(),
-            );
-        }
+        Some(filename) => record.with_filename(filename),
+        None => record,
+    };
+    log_operation(&app, record);
 
-        let tmp_path = std::env::temp_dir().join("motormods_receipt.txt");
-        fs::write(&tmp_path, text).map_err(|e| format!("Failed to write receipt file: {e}"))?;
+    result
+}
 
-        let lp = Command::new("lp")
-            .arg(tmp_path.to_string_lossy().to_string())
-            .output()
-            .map_err(|e| format!("Printing not available (lp not found): {e}"))?;
+fn run_database_repair(app: &AppHandle) -> Result<RepairResult, String> {
+    require_primary_instance(app)?;
+    let db_state = app.state::<DatabaseState>();
+    let _busy = db_state.busy.try_lock().map_err(|_| {
+        "A database operation is in progress. Try again when it finishes.".to_string()
+    })?;
 
-        if !lp.status.success() {
-            let stderr = String::from_utf8_lossy(&lp.stderr);
-            return Err(format!("Print failed: {stderr}"));
-        }
+    let db_path = get_db_path(app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let integrity_before = {
+        let conn = open_database(&db_path, OpenFlags::default())?;
+        // Fold the WAL into the main file so the safety copy is complete
+        let _ = conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE)");
+        run_integrity_check(&conn)
+    };
 
-        Ok(())
+    if integrity_before == "ok" {
+        return Ok(RepairResult {
+            repaired: false,
+            integrity_before,
+            safety_backup: None,
+            rows_recovered: 0,
+            dropped: Vec::new(),
+            message: "Database passed integrity check; no repair needed".to_string(),
+        });
     }
 
-    #[cfg(target_os = "windows")]
-    {
-        // Windows thermal printer support using PowerShell
-        // Write receipt to a temp file
-        let tmp_path = std::env::temp_dir().join("motormods_receipt.txt");
-        fs::write(&tmp_path, &text).map_err(|e| format!("Failed to write receipt file: {e}"))?;
-
-        // Use PowerShell to print to the default printer
-        // For 80mm thermal printers, Windows uses the standard print spooler
-        let output = Command::new("powershell")
-            .args([
-                "-NoProfile",
-                "-Command",
-                &format!(
-                    "Get-Content -Path '{}' -Raw | Out-Printer",
-                    tmp_path.to_string_lossy()
-                ),
-            ])
-            .output()
-            .map_err(|e| format!("Failed to execute print command: {e}"))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            // Check if it's just a "no default printer" issue
-            if stderr.contains("printer") || stderr.contains("Printer") {
-                return Err("No default printer configured. Please set a default printer in Windows Settings.".to_string());
+    let repaired_path = db_path.with_extension("db.repair");
+    if repaired_path.exists() {
+        fs::remove_file(&repaired_path)
+            .map_err(|e| format!("Failed to remove stale repair file: {}", e))?;
+    }
+
+    let rebuilt = {
+        let source = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        rebuild_database(app, &source, &repaired_path).and_then(|outcome| {
+            let repaired = open_database(&repaired_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .map_err(|e| format!("Repaired database: {}", e))?;
+            let check = run_integrity_check(&repaired);
+            if check != "ok" {
+                return Err(format!(
+                    "Repaired database still fails integrity check: {}",
+                    check
+                ));
             }
-            return Err(format!("Print failed: {stderr}"));
+            Ok(outcome)
+        })
+    };
+
+    let (rows_recovered, dropped) = match rebuilt {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            let _ = fs::remove_file(&repaired_path);
+            return Err(e);
         }
+    };
 
-        // Clean up temp file
-        let _ = fs::remove_file(&tmp_path);
+    // Frames left in the WAL would be replayed onto the rebuilt file. After the
+    // checkpoint above it is only non-empty when another connection holds it.
+    let wal = inspect_wal(&db_path);
+    if wal.wal_size > 0 && !wal.stale {
+        let _ = fs::remove_file(&repaired_path);
+        return Err(
+            "The database is still open elsewhere (its -wal file holds changes). Close the database connection and try again."
+                .to_string(),
+        );
+    }
 
-        Ok(())
+    // Keep the original byte-for-byte; a page-level backup could choke on the corruption
+    let backups_dir = get_backups_dir(app)?;
+    let safety_filename = safety_backup_filename("repair");
+    let safety_path = backups_dir.join(&safety_filename);
+    // A raw copy on purpose: the backup API can refuse a damaged database,
+    // and the safety copy should keep it exactly as it was before repair
+    if let Err(e) = copy_file_durable(&db_path, &safety_path) {
+        let _ = fs::remove_file(&repaired_path);
+        return Err(describe_copy_error(
+            "Failed to create safety backup",
+            &e,
+            &db_path,
+            &safety_path,
+        ));
     }
+    record_safety_origin(&safety_path, "repair", None);
 
-    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
-    {
-        let _ = text;
-        Err("Printing is currently supported only on Windows and Linux builds.".to_string())
+    // An empty WAL and its index are safe next to the rebuilt file; only
+    // remove them when inspect_wal shows they are left over from a crash
+    let stale_sidecars = if wal.stale {
+        sqlite_sidecar_paths(&db_path).to_vec()
+    } else {
+        Vec::new()
+    };
+    for sidecar in stale_sidecars {
+        if sidecar.exists() {
+            fs::remove_file(&sidecar).map_err(|e| {
+                format!(
+                    "Failed to remove {}: {}. Original kept; safety backup: {}",
+                    sidecar.display(),
+                    e,
+                    safety_filename
+                )
+            })?;
+        }
     }
+    fs::rename(&repaired_path, &db_path).map_err(|e| {
+        format!(
+            "Failed to replace database: {}. Original kept; safety backup: {}",
+            e, safety_filename
+        )
+    })?;
+
+    let message = if dropped.is_empty() {
+        format!("Database repaired; {} rows recovered", rows_recovered)
+    } else {
+        format!(
+            "Database repaired; {} rows recovered, {} object(s) lost data",
+            rows_recovered,
+            dropped.len()
+        )
+    };
+
+    Ok(RepairResult {
+        repaired: true,
+        integrity_before,
+        safety_backup: Some(safety_filename),
+        rows_recovered,
+        dropped,
+        message,
+    })
 }
 
-// ============================================
-// SILENT PDF PRINTING (Windows only, using SumatraPDF)
-// ============================================
+/// Statements a migration may not contain; the script already runs inside a transaction
+const MIGRATION_FORBIDDEN_KEYWORDS: &[&str] = &[
+    "BEGIN",
+    "COMMIT",
+    "END",
+    "ROLLBACK",
+    "SAVEPOINT",
+    "RELEASE",
+    "VACUUM",
+    "ATTACH",
+    "DETACH",
+];
+
+/// Returns the first keyword of a statement, skipping leading whitespace and comments
+fn leading_sql_keyword(sql: &str) -> String {
+    let mut rest = sql.trim_start();
+    loop {
+        if let Some(tail) = rest.strip_prefix("--") {
+            rest = tail
+                .split_once('\n')
+                .map(|(_, t)| t)
+                .unwrap_or("")
+                .trim_start();
+        } else if let Some(tail) = rest.strip_prefix("/*") {
+            rest = tail
+                .split_once("*/")
+                .map(|(_, t)| t)
+                .unwrap_or("")
+                .trim_start();
+        } else {
+            break;
+        }
+    }
+    rest.chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
 
+/// Applies a schema migration script, moving user_version from expected_from_version
+/// to set_to_version. A pre_migration_safety_ backup is taken first and the script
+/// runs in one transaction, so any failing statement leaves the database untouched.
 #[tauri::command]
-fn print_pdf_silent(
+fn apply_migration(
     app: AppHandle,
-    pdf_path: String,
-    printer_name: Option<String>,
-) -> Result<(), String> {
-    #[cfg(target_os = "windows")]
-    {
-        // Look for SumatraPDF.exe in the resources folder
-        let resource_dir = app.path().resource_dir().map_err(|e| e.to_string())?;
-
-        // Try multiple possible locations for SumatraPDF.exe
-        let possible_paths = vec![
-            resource_dir.join("SumatraPDF.exe"),
-            resource_dir.join("resources").join("SumatraPDF.exe"),
-        ];
-
-        let sumatra_exe = possible_paths.iter().find(|p| p.exists()).ok_or_else(|| {
-            "SumatraPDF.exe not found. Please place SumatraPDF.exe in src-tauri/resources/"
-                .to_string()
-        })?;
+    sql: String,
+    expected_from_version: i64,
+    set_to_version: i64,
+) -> Result<String, String> {
+    let result = run_migration(&app, &sql, expected_from_version, set_to_version);
+    let record = match &result {
+        Ok(message) => OperationRecord::new("migration", true, message.clone()),
+        Err(e) => OperationRecord::new(
+            "migration",
+            false,
+            format!(
+                "user_version {} -> {}: {}",
+                expected_from_version, set_to_version, e
+            ),
+        ),
+    };
+    log_operation(&app, record);
+    result
+}
 
-        // Verify PDF file exists
-        let pdf_file = std::path::Path::new(&pdf_path);
-        if !pdf_file.exists() {
-            return Err(format!("PDF file not found: {}", pdf_path));
-        }
+fn run_migration(
+    app: &AppHandle,
+    sql: &str,
+    expected_from_version: i64,
+    set_to_version: i64,
+) -> Result<String, String> {
+    require_primary_instance(app)?;
+    if set_to_version <= expected_from_version {
+        return Err(format!(
+            "Target version {} must be greater than the starting version {}",
+            set_to_version, expected_from_version
+        ));
+    }
+    if sql.trim().is_empty() {
+        return Err("Migration script is empty".to_string());
+    }
 
-        // Build SumatraPDF command
-        let mut cmd = Command::new(sumatra_exe);
+    let db_state = app.state::<DatabaseState>();
+    let _busy = db_state.busy.try_lock().map_err(|_| {
+        "A database operation is in progress. Try again when it finishes.".to_string()
+    })?;
 
-        if let Some(printer) = printer_name {
-            cmd.args(["-print-to", &printer]);
-        } else {
-            cmd.arg("-print-to-default");
-        }
+    let db_path = get_db_path(app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let mut conn = open_database(&db_path, OpenFlags::default())?;
+    let current_version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read user_version: {}", e))?;
+    if current_version != expected_from_version {
+        return Err(format!(
+            "Database is at user_version {}, but the migration expects {}",
+            current_version, expected_from_version
+        ));
+    }
+
+    let backups_dir = get_backups_dir(app)?;
+    let safety_filename = safety_backup_filename("migration");
+    let safety_path = backups_dir.join(&safety_filename);
+    snapshot_database(&db_path, &safety_path)
+        .map_err(|e| format!("Failed to create safety backup: {}", e))?;
+    record_safety_origin(
+        &safety_path,
+        "migration",
+        Some(&format!(
+            "user_version {} -> {}",
+            expected_from_version, set_to_version
+        )),
+    );
 
-        cmd.arg("-silent");
-        cmd.arg(&pdf_path);
+    let statements = run_migration_script(&mut conn, sql, set_to_version)?;
 
-        let output = cmd
-            .output()
-            .map_err(|e| format!("Failed to execute SumatraPDF: {}", e))?;
+    Ok(format!(
+        "Migrated user_version {} -> {} ({} statement(s)); safety backup: {}",
+        expected_from_version, set_to_version, statements, safety_filename
+    ))
+}
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            if stderr.is_empty() {
-                // SumatraPDF often doesn't output errors, check if print started
-                return Ok(());
+/// Runs a migration script and sets user_version to `set_to_version` in one
+/// transaction, returning the number of statements run. Transaction control
+/// and other forbidden statements reject the script.
+fn run_migration_script(
+    conn: &mut Connection,
+    sql: &str,
+    set_to_version: i64,
+) -> Result<usize, String> {
+    // Dropping the transaction on any error rolls the whole script back
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let mut statements = 0usize;
+    {
+        let mut batch = rusqlite::Batch::new(&tx, sql);
+        while let Some(mut stmt) = batch
+            .next()
+            .map_err(|e| format!("Migration failed at statement {}: {}", statements + 1, e))?
+        {
+            statements += 1;
+            let keyword = leading_sql_keyword(stmt.expanded_sql().as_deref().unwrap_or(""));
+            if MIGRATION_FORBIDDEN_KEYWORDS.contains(&keyword.as_str()) {
+                return Err(format!(
+                    "Migration statement {} uses {}, which is not allowed inside a migration",
+                    statements, keyword
+                ));
             }
-            return Err(format!("Print failed: {}", stderr));
+            let mut rows = stmt.raw_query();
+            while rows
+                .next()
+                .map_err(|e| format!("Migration failed at statement {}: {}", statements, e))?
+                .is_some()
+            {}
         }
-
-        Ok(())
     }
+    if statements == 0 {
+        return Err("Migration script contains no statements".to_string());
+    }
+    tx.execute_batch(&format!("PRAGMA user_version = {}", set_to_version))
+        .map_err(|e| format!("Failed to set user_version: {}", e))?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit migration: {}", e))?;
+    Ok(statements)
+}
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        let _ = (app, pdf_path, printer_name);
-        Err("Silent PDF printing is only available on Windows.".to_string())
+fn setting_is_true(value: &str) -> bool {
+    matches!(value.trim(), "1" | "true")
+}
+
+/// A setting's value, or `default` when it isn't set
+fn setting_or_default(app: &AppHandle, key: &str, default: &str) -> ConfigValue {
+    match get_setting(app, key).filter(|v| !v.trim().is_empty()) {
+        Some(value) => ConfigValue {
+            value,
+            source: "settings".to_string(),
+        },
+        None => ConfigValue {
+            value: default.to_string(),
+            source: "default".to_string(),
+        },
     }
 }
 
+/// Reports the paths and settings the app is actually using and the source of each
+#[tauri::command]
+fn get_effective_config(app: AppHandle) -> Result<EffectiveConfig, String> {
+    let db_path = get_db_path(&app)?;
+    let app_dir_source = match APP_DIR.get().map(|(_, label)| *label) {
+        Some(APP_DIR_OVERRIDE_ENV) => "env",
+        _ => "platform",
+    };
+
+    let destination = resolve_backup_destination(&app)?;
+    let rotation_used = destination.warning.is_none() && destination.dir != get_backups_dir(&app)?;
+    let custom_dir = get_setting(&app, BACKUPS_DIR_KEY).is_some_and(|v| !v.trim().is_empty());
+    let backups_dir = ConfigValue {
+        value: destination.dir.to_string_lossy().to_string(),
+        source: if rotation_used || custom_dir {
+            "settings"
+        } else {
+            app_dir_source
+        }
+        .to_string(),
+    };
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    let printer = match default_cups_printer(get_print_timeout(&app)) {
+        Ok(name) => ConfigValue {
+            value: name,
+            source: "platform".to_string(),
+        },
+        Err(e) => ConfigValue {
+            value: format!("unavailable ({})", e),
+            source: "platform".to_string(),
+        },
+    };
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    let printer = ConfigValue {
+        value: "system default printer".to_string(),
+        source: "platform".to_string(),
+    };
+
+    Ok(EffectiveConfig {
+        database_path: ConfigValue {
+            value: db_path.to_string_lossy().to_string(),
+            source: app_dir_source.to_string(),
+        },
+        backups_dir,
+        printer,
+        print_timeout_seconds: setting_or_default(
+            &app,
+            PRINT_TIMEOUT_KEY,
+            &DEFAULT_PRINT_TIMEOUT_SECS.to_string(),
+        ),
+        backup_retention_days: setting_or_default(
+            &app,
+            BACKUP_RETENTION_DAYS_KEY,
+            &DEFAULT_BACKUP_RETENTION_DAYS.to_string(),
+        ),
+        backup_max_dir_bytes: setting_or_default(&app, BACKUP_MAX_DIR_BYTES_KEY, "unlimited"),
+        backup_keep_count: setting_or_default(&app, BACKUP_KEEP_COUNT_KEY, "unlimited"),
+        backup_cooldown_seconds: setting_or_default(
+            &app,
+            BACKUP_COOLDOWN_KEY,
+            &DEFAULT_BACKUP_COOLDOWN_SECS.to_string(),
+        ),
+        auto_backup_enabled: setting_or_default(&app, AUTO_BACKUP_ENABLED_KEY, "1"),
+        auto_backup_paused: setting_or_default(&app, AUTO_BACKUP_PAUSED_KEY, "0"),
+        auto_backup_time: setting_or_default(&app, AUTO_BACKUP_TIME_KEY, DEFAULT_AUTO_BACKUP_TIME),
+        foreign_keys_enabled: ConfigValue {
+            value: if FOREIGN_KEYS_ENABLED.load(Ordering::SeqCst) {
+                "1"
+            } else {
+                "0"
+            }
+            .to_string(),
+            source: if get_setting(&app, FOREIGN_KEYS_KEY).is_some() {
+                "settings"
+            } else {
+                "default"
+            }
+            .to_string(),
+        },
+    })
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    if log::set_logger(&STDERR_LOGGER).is_ok() {
+        log::set_max_level(log::LevelFilter::Info);
+    }
     tauri::Builder::default()
+        .manage(DatabaseState::default())
+        .manage(VerifyState::default())
+        .manage(ScheduleState::default())
+        .manage(BackupState::default())
+        .manage(PrinterCache::default())
+        .manage(PrinterMonitor::default())
+        .manage(BackupDeltaCache::default())
+        .manage(OperationRegistry::default())
+        .manage(InstanceLock::default())
+        .setup(|app| {
+            acquire_instance_lock(app.handle());
+            // Before the settings below, which are read from the active database
+            load_active_database(app.handle());
+            load_foreign_keys_setting(app.handle());
+            load_durability_setting(app.handle());
+            load_busy_timeout_setting(app.handle());
+            // The primary instance already runs the schedule and owns the backups folders
+            if is_primary_instance(app.handle().clone()) {
+                remove_abandoned_partials(app.handle());
+                resume_scheduled_backups(app.handle());
+            }
+            prewarm_printer_cache(app.handle());
+            start_backups_watch(app.handle());
+            start_automation_api(app.handle());
+            // Opportunistic; earlier runs can't be using their temp files any more
+            std::thread::spawn(|| remove_stale_temp_files(false));
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                handle_close_for_backup(window, api);
+            }
+        })
         .plugin(tauri_plugin_sql::Builder::default().build())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
@@ -660,18 +3920,367 @@ pub fn run() {
             greet,
             backup_database,
             restore_database,
+            undo_last_restore,
+            probe_restored_database,
             import_backup,
+            import_backup_interactive,
+            detect_portable_backup,
+            export_portable_backup,
+            check_backup_version,
             export_backup,
             list_backups,
+            list_safety_backups,
+            list_backups_with_delta,
+            set_backups_watch_debounce,
+            list_backups_filtered,
+            backup_to_destinations,
+            get_backups_volume_space,
+            create_verified_backup,
+            create_redacted_backup,
+            create_backup_excluding,
+            list_all_backups,
+            restore_from_any,
+            get_backup_freshness,
             delete_backup,
+            set_backup_protected,
+            open_backup_external,
+            get_last_restore_info,
+            delete_backups,
+            restore_to_new,
+            begin_trial_restore,
+            commit_trial,
+            abort_trial,
+            create_backup_deterministic,
+            backup_to_bytes,
+            cleanup_orphan_sidecars,
+            cleanup_temp_files,
+            apply_retention_policy,
+            enforce_max_backup_size,
+            prune_backups,
             get_backups_path,
+            set_backups_directory,
             get_backup_file_path,
             create_safety_backup,
+            get_backup_rotation,
+            set_backup_rotation,
+            reset_database,
+            get_active_database,
+            switch_database,
+            repair_database,
+            apply_migration,
+            get_foreign_keys_enabled,
+            set_foreign_keys_enabled,
+            get_durability_profile,
+            set_durability_profile,
+            get_lock_diagnostics,
+            set_busy_timeout,
+            check_database_integrity,
+            get_sqlite_runtime_stats,
+            benchmark_backup_formats,
+            validate_schema,
+            table_checksums,
+            get_operation_history_filtered,
+            get_database_growth_trend,
+            export_operation_history_csv,
+            generate_backup_report,
+            import_csv_bundle,
+            test_webhook,
+            restore_from_url,
+            create_archival_backup,
+            restore_archival_backup,
+            export_sql_dump,
+            import_sql_dump,
+            export_subset_db,
+            sync_from,
+            check_wal_consistency,
+            clear_stale_wal,
+            get_table_row_counts,
+            verify_all_backups,
+            schedule_backup_at,
+            list_scheduled_backups,
+            cancel_scheduled_backup,
+            pause_auto_backup,
+            resume_auto_backup,
+            get_backup_on_exit,
+            set_backup_on_exit,
+            get_auto_backup_status,
+            get_effective_config,
+            cancel_verify_backups,
+            list_active_operations,
+            is_primary_instance,
+            cancel_backup_operation,
+            verify_encrypted_backup,
             restore_data_from_backup,
             restore_data_from_backup_file,
             print_receipt,
-            print_pdf_silent
+            print_zreport,
+            validate_receipt,
+            parse_receipt_text,
+            validate_template,
+            save_template,
+            get_template,
+            print_worksheet,
+            set_worksheet_printer,
+            print_network,
+            get_printer_supplies,
+            check_printing_status,
+            list_printers,
+            refresh_printer_cache,
+            start_printer_monitor,
+            stop_printer_monitor,
+            export_receipts_pdf,
+            export_table_parquet,
+            export_table_csv,
+            get_receipt_decorations,
+            set_receipt_decorations,
+            render_receipt_image,
+            generate_qr,
+            generate_qr_escpos,
+            print_barcode,
+            print_pdf_silent,
+            get_database_url,
+            refresh_table_statistics
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod test_support {
+    use super::*;
+
+    pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+        use sha2::Digest;
+        format!("{:x}", sha2::Sha256::digest(bytes))
+    }
+
+    /// A small database with a table and a few rows, removed when dropped
+    pub(crate) fn sample_database() -> TempFileGuard {
+        let path = TempFileGuard(unique_temp_path("test_source", "db"));
+        let conn = Connection::open(&path.0).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE products (id TEXT PRIMARY KEY, name TEXT NOT NULL);
+             INSERT INTO products VALUES ('a', 'Brake pad'), ('b', 'Chain');",
+        )
+        .unwrap();
+        path
+    }
+
+    /// A directory removed with everything in it when dropped
+    pub(crate) struct TempDir(pub(crate) PathBuf);
+
+    impl TempDir {
+        pub(crate) fn new(kind: &str) -> Self {
+            let dir = TempDir(unique_temp_path(kind, "dir"));
+            fs::create_dir_all(&dir.0).unwrap();
+            dir
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{sample_database, TempDir};
+
+    #[test]
+    fn wal_of_an_open_connection_is_consistent() {
+        let db = sample_database();
+        let [wal_path, shm_path] = sqlite_sidecar_paths(&db.0);
+        let _sidecars = (TempFileGuard(wal_path), TempFileGuard(shm_path));
+        let conn = Connection::open(&db.0).unwrap();
+        conn.query_row("PRAGMA journal_mode=WAL", [], |_| Ok(()))
+            .unwrap();
+        conn.execute("INSERT INTO products VALUES ('c', 'Clutch plate')", [])
+            .unwrap();
+
+        let report = inspect_wal(&db.0);
+
+        assert!(report.wal_present && report.wal_size > 0);
+        assert_eq!(report.wal_header_valid, Some(true));
+        assert_eq!(report.wal_page_size, report.database_page_size);
+        assert!(report.valid_frames > 0);
+        assert_eq!(report.index_matches_wal, Some(true));
+        assert!(report.consistent && !report.stale, "{:?}", report.issues);
+    }
+
+    #[test]
+    fn wal_from_another_database_does_not_match_the_index() {
+        let (db, other) = (sample_database(), sample_database());
+        let [wal_path, shm_path] = sqlite_sidecar_paths(&db.0);
+        let [other_wal, other_shm] = sqlite_sidecar_paths(&other.0);
+        let _sidecars = (
+            TempFileGuard(wal_path.clone()),
+            TempFileGuard(shm_path),
+            TempFileGuard(other_wal.clone()),
+            TempFileGuard(other_shm),
+        );
+        let open_with_wal = |path: &Path| {
+            let conn = Connection::open(path).unwrap();
+            conn.query_row("PRAGMA journal_mode=WAL", [], |_| Ok(()))
+                .unwrap();
+            conn.execute("INSERT INTO products VALUES ('c', 'Clutch plate')", [])
+                .unwrap();
+            conn
+        };
+        let (_conn, _other_conn) = (open_with_wal(&db.0), open_with_wal(&other.0));
+
+        fs::copy(&other_wal, &wal_path).unwrap();
+        let report = inspect_wal(&db.0);
+
+        assert_eq!(report.wal_header_valid, Some(true));
+        assert_eq!(report.index_matches_wal, Some(false));
+        assert!(report.stale && !report.consistent);
+    }
+
+    #[test]
+    fn wal_leftovers_are_reported_stale() {
+        let db = sample_database();
+        let [wal_path, shm_path] = sqlite_sidecar_paths(&db.0);
+        let (wal, shm) = (TempFileGuard(wal_path), TempFileGuard(shm_path));
+
+        // -shm without -wal: no connection can still be using it
+        fs::write(&shm.0, [0u8; 32]).unwrap();
+        let report = inspect_wal(&db.0);
+        assert!(!report.wal_present && report.stale && !report.consistent);
+
+        // A -wal whose header doesn't check out
+        fs::write(&wal.0, [0xabu8; 64]).unwrap();
+        let report = inspect_wal(&db.0);
+        assert_eq!(report.wal_header_valid, Some(false));
+        assert!(report.stale && !report.consistent);
+    }
+
+    #[test]
+    fn snapshot_never_overwrites_an_existing_file() {
+        let db = sample_database();
+        let dir = TempDir::new("test_snapshot");
+        let destination = dir.0.join(safety_backup_filename("reset"));
+
+        snapshot_database(&db.0, &destination).unwrap();
+        assert!(has_sqlite_header(&destination).unwrap());
+
+        fs::write(&destination, b"earlier safety backup").unwrap();
+        assert!(snapshot_database(&db.0, &destination).is_err());
+        assert_eq!(fs::read(&destination).unwrap(), b"earlier safety backup");
+    }
+
+    fn user_version(conn: &Connection) -> i64 {
+        conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn migration_script_runs_and_sets_user_version() {
+        let db = sample_database();
+        let mut conn = Connection::open(&db.0).unwrap();
+        let sql = "-- add stock tracking
+            ALTER TABLE products ADD COLUMN stock INTEGER NOT NULL DEFAULT 0;
+            UPDATE products SET stock = 3 WHERE id = 'a';";
+
+        assert_eq!(run_migration_script(&mut conn, sql, 2), Ok(2));
+
+        assert_eq!(user_version(&conn), 2);
+        let stock: i64 = conn
+            .query_row("SELECT stock FROM products WHERE id = 'a'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(stock, 3);
+    }
+
+    #[test]
+    fn failed_migration_script_changes_nothing() {
+        let db = sample_database();
+        let mut conn = Connection::open(&db.0).unwrap();
+        let sql = "CREATE TABLE suppliers (id TEXT PRIMARY KEY);
+            INSERT INTO missing_table VALUES (1);";
+
+        let error = run_migration_script(&mut conn, sql, 2).unwrap_err();
+
+        assert!(
+            error.starts_with("Migration failed at statement 2"),
+            "{}",
+            error
+        );
+        assert_eq!(user_version(&conn), 0);
+        assert!(!table_exists(&conn, "suppliers"));
+    }
+
+    #[test]
+    fn migration_script_rejects_transaction_control_and_empty_scripts() {
+        let db = sample_database();
+        let mut conn = Connection::open(&db.0).unwrap();
+
+        let sql = "CREATE TABLE suppliers (id TEXT); /* done */ COMMIT;";
+        assert_eq!(
+            run_migration_script(&mut conn, sql, 2),
+            Err(
+                "Migration statement 2 uses COMMIT, which is not allowed inside a migration"
+                    .to_string()
+            )
+        );
+        assert!(!table_exists(&conn, "suppliers"));
+
+        assert_eq!(
+            run_migration_script(&mut conn, "-- nothing yet", 2),
+            Err("Migration script contains no statements".to_string())
+        );
+        assert_eq!(user_version(&conn), 0);
+    }
+
+    #[test]
+    fn backup_lookup_accepts_only_plain_names_inside_the_folders() {
+        let (first, second) = (TempDir::new("test_backups"), TempDir::new("test_rotation"));
+        fs::write(first.0.join("both.db"), b"first").unwrap();
+        fs::write(second.0.join("both.db"), b"second").unwrap();
+        fs::write(second.0.join("rotated.db"), b"rotated").unwrap();
+        let dirs = [first.0.clone(), second.0.clone()];
+
+        assert_eq!(
+            find_backup_file(&dirs, "both.db").unwrap(),
+            first.0.join("both.db")
+        );
+        assert_eq!(
+            find_backup_file(&dirs, "rotated.db").unwrap(),
+            second.0.join("rotated.db")
+        );
+        assert_eq!(
+            find_backup_file(&dirs, "missing.db").unwrap_err(),
+            "Backup file not found: missing.db"
+        );
+        for name in [
+            "",
+            ".",
+            "..",
+            "../both.db",
+            "sub/both.db",
+            "sub\\both.db",
+            "/tmp/both.db",
+        ] {
+            assert_eq!(
+                find_backup_file(&dirs, name).unwrap_err(),
+                format!("Invalid backup filename: {}", name)
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn backup_lookup_rejects_a_symlink_out_of_the_folder() {
+        let (backups, outside) = (TempDir::new("test_backups"), TempDir::new("test_outside"));
+        fs::write(outside.0.join("secret.db"), b"outside").unwrap();
+        std::os::unix::fs::symlink(outside.0.join("secret.db"), backups.0.join("link.db")).unwrap();
+
+        assert_eq!(
+            find_backup_file(std::slice::from_ref(&backups.0), "link.db").unwrap_err(),
+            "Invalid backup filename: link.db"
+        );
+    }
+}
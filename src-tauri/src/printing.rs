@@ -0,0 +1,3589 @@
+use super::*;
+
+// ============================================
+// PRINTING TYPES
+// ============================================
+
+/// Shop header/footer printed around every receipt
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiptDecorations {
+    pub header: String,
+    pub footer: String,
+}
+
+/// Optional behaviour flags for print_receipt
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrintOptions {
+    /// Print the text exactly as given: no wrapping, header or footer
+    pub raw: bool,
+    /// Refuse to print if the final text breaks the printer profile's limits
+    pub enforce_limits: bool,
+    /// Printer codepage such as "cp437" or "cp1252"; overrides receipt_encoding
+    pub encoding: Option<String>,
+    /// Printed for characters the codepage lacks; overrides the setting
+    pub substitute: Option<String>,
+    /// Wait for the spooler to report the job finished instead of returning
+    /// once it is accepted
+    pub confirm: bool,
+    /// How long `confirm` waits before reporting "timeout"; default 30 seconds
+    pub confirm_timeout_secs: Option<u64>,
+}
+
+/// Response from print_receipt
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrintJob {
+    /// Spooler job id from lp ("request id is HP-123"); None where the
+    /// platform reports none (Windows, network printers)
+    pub job_id: Option<String>,
+    /// Printer the receipt was sent to
+    pub printer: String,
+    /// "submitted" without confirm; otherwise "printed", "failed", "timeout",
+    /// or "unconfirmed" when the platform gives no job id to follow
+    pub status: String,
+}
+
+/// A printer from list_printers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrinterInfo {
+    pub name: String,
+    pub is_default: bool,
+    /// "idle", "printing", "disabled" or "offline"
+    pub state: String,
+}
+
+/// One element of a structured receipt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReceiptBlock {
+    /// Centered heading, e.g. the shop name
+    Header {
+        text: String,
+    },
+    Text {
+        text: String,
+        bold: bool,
+    },
+    /// Label on the left, value right-aligned
+    Row {
+        label: String,
+        value: String,
+        bold: bool,
+    },
+    Divider,
+    Blank,
+}
+
+/// A receipt described as blocks rather than pre-formatted text
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReceiptDocument {
+    pub blocks: Vec<ReceiptBlock>,
+}
+
+/// A receipt line wider than the printer allows
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiptLineIssue {
+    /// 1-based line number in the checked text
+    pub line_number: usize,
+    pub width: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiptValidation {
+    pub valid: bool,
+    /// Lines the printer will feed once long lines are wrapped
+    pub line_count: usize,
+    pub max_lines: Option<usize>,
+    pub max_width: usize,
+    pub exceeds_max_lines: bool,
+    pub overlong_lines: Vec<ReceiptLineIssue>,
+}
+
+/// One consumable (ink, toner, ribbon...) as reported by CUPS marker attributes
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrinterSupply {
+    pub name: String,
+    /// marker-types keyword, e.g. "toner" or "ink-cartridge"
+    pub kind: Option<String>,
+    pub level_percent: Option<u8>,
+    /// "ok", "low", "empty" or "not reported"
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrinterSupplies {
+    pub printer: Option<String>,
+    /// "ok", "low", "empty" or "not reported"; CUPS only reports paper via state reasons
+    pub paper: String,
+    pub supplies: Vec<PrinterSupply>,
+    /// False when the printer (or platform) exposes no supply information
+    pub reported: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrintingStatus {
+    pub available: bool,
+    pub printer: Option<String>,
+    /// True when the printer came from the cache rather than a fresh check
+    pub cached: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiptExportFailure {
+    pub invoice_id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiptExportResult {
+    pub exported: usize,
+    pub failures: Vec<ReceiptExportFailure>,
+    pub destination_dir: String,
+    /// Set when some characters had no glyph and were replaced (₹ as "Rs.")
+    pub warning: Option<String>,
+}
+
+/// Default printer detected by check_printing_status, reused until it expires
+#[derive(Default)]
+pub(crate) struct PrinterCache {
+    pub(crate) default_printer: Mutex<Option<(Instant, String)>>,
+}
+
+/// Stop flag of the running printer monitor, if any
+#[derive(Default)]
+pub(crate) struct PrinterMonitor {
+    pub(crate) stop: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+// Settings key for how long lp/lpstat/PowerShell may run before being killed
+pub(crate) const PRINT_TIMEOUT_KEY: &str = "print_timeout_seconds";
+pub(crate) const DEFAULT_PRINT_TIMEOUT_SECS: u64 = 10;
+
+// Settings key to skip the startup printer check; the cached name expires after the TTL
+pub(crate) const PRINTER_PREWARM_KEY: &str = "printer_prewarm_enabled";
+pub(crate) const PRINTER_CACHE_TTL: Duration = Duration::from_secs(60);
+
+// Printer monitor polling interval bounds, in seconds
+pub(crate) const DEFAULT_PRINTER_MONITOR_INTERVAL_SECS: u64 = 15;
+pub(crate) const MIN_PRINTER_MONITOR_INTERVAL_SECS: u64 = 2;
+pub(crate) const MAX_PRINTER_MONITOR_INTERVAL_SECS: u64 = 3600;
+
+// print_receipt confirm: default wait and how often the completed queue is read
+pub(crate) const DEFAULT_PRINT_CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) const PRINT_CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Characters per line on an 80mm receipt printer
+pub(crate) const RECEIPT_WIDTH: usize = 42;
+
+// PDF receipts: Courier at this size fits RECEIPT_WIDTH columns on an 80mm page
+pub(crate) const PDF_FONT_SIZE: f32 = 9.0;
+pub(crate) const PDF_LINE_HEIGHT: f32 = 11.0;
+pub(crate) const PDF_MARGIN: f32 = 18.0;
+// Lines per PDF page, about an A4 page at PDF_LINE_HEIGHT
+pub(crate) const PDF_PAGE_LINES: usize = 70;
+
+// Settings key for a .ttf/.ttc used for receipt PDF characters Courier lacks
+pub(crate) const RECEIPT_PDF_FONT_KEY: &str = "receipt_pdf_font";
+// Tried in order when the setting is unset; the first covering ₹ and Tamil wins
+#[cfg(target_os = "windows")]
+pub(crate) const PDF_FONT_CANDIDATES: &[&str] = &[
+    r"C:\Windows\Fonts\Nirmala.ttf",
+    r"C:\Windows\Fonts\Nirmala.ttc",
+    r"C:\Windows\Fonts\latha.ttf",
+];
+#[cfg(target_os = "macos")]
+pub(crate) const PDF_FONT_CANDIDATES: &[&str] = &[
+    "/System/Library/Fonts/Supplemental/Tamil Sangam MN.ttc",
+    "/System/Library/Fonts/Supplemental/Tamil MN.ttc",
+    "/Library/Fonts/NotoSansTamil-Regular.ttf",
+];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) const PDF_FONT_CANDIDATES: &[&str] = &[
+    "/usr/share/fonts/truetype/noto/NotoSansTamil-Regular.ttf",
+    "/usr/share/fonts/noto/NotoSansTamil-Regular.ttf",
+    "/usr/share/fonts/google-noto/NotoSansTamil-Regular.ttf",
+    "/usr/share/fonts/truetype/lohit-tamil/Lohit-Tamil.ttf",
+    "/usr/share/fonts/truetype/freefont/FreeSerif.ttf",
+];
+
+// Printer profile limits; no max lines means the printer takes any length
+pub(crate) const RECEIPT_MAX_LINES_KEY: &str = "receipt_max_lines";
+pub(crate) const RECEIPT_MAX_WIDTH_KEY: &str = "receipt_max_width";
+
+// Settings keys for the shop header/footer added to every receipt
+pub(crate) const RECEIPT_HEADER_KEY: &str = "receipt_header";
+pub(crate) const RECEIPT_FOOTER_KEY: &str = "receipt_footer";
+
+// Percent of tax included in sale prices (e.g. 18 for GST), shown on the Z-report
+pub(crate) const TAX_RATE_PERCENT_KEY: &str = "tax_rate_percent";
+
+// Settings keys for the printer's codepage (e.g. cp437) and the text printed
+// in place of characters it can't show; unset encoding prints UTF-8 as before
+pub(crate) const RECEIPT_ENCODING_KEY: &str = "receipt_encoding";
+pub(crate) const RECEIPT_SUBSTITUTE_KEY: &str = "receipt_unmappable_substitute";
+pub(crate) const DEFAULT_RECEIPT_SUBSTITUTE: &str = "?";
+
+// Parts-pull worksheets: saved template name, the settings key for their own
+// printer (CUPS name or host:port), and the fields stripped so no prices print
+pub(crate) const WORKSHEET_TEMPLATE_NAME: &str = "worksheet";
+pub(crate) const WORKSHEET_PRINTER_KEY: &str = "worksheet_printer";
+pub(crate) const WORKSHEET_HIDDEN_FIELDS: &[&str] = &[
+    "price",
+    "purchase_price",
+    "cost_price",
+    "rate",
+    "line_total",
+    "discount_amount",
+    "total_amount",
+    "amount",
+    "total",
+];
+// Saved template name for print_zreport, and the layout used when none is saved
+pub(crate) const ZREPORT_TEMPLATE_NAME: &str = "zreport";
+pub(crate) const DEFAULT_ZREPORT_TEMPLATE: &str = "{{#store_name}}{{store_name}}
+{{/store_name}}Z-REPORT / DAILY CASH-UP
+{{date}}
+{{divider}}
+Transactions: {{transactions}}
+Items sold: {{items_sold}}
+Discounts: {{discounts}}
+Gross sales: {{gross_sales}}
+{{divider}}
+PAYMENT METHODS
+{{#payment_methods}}  {{mode}} ({{count}}): {{total}}
+{{/payment_methods}}{{#no_sales}}  (no sales)
+{{/no_sales}}{{divider}}
+Returns ({{return_count}}): -{{returns_total}}
+NET TOTAL: {{net_total}}
+Tax included ({{tax_rate}}%): {{tax}}
+{{divider}}
+Printed {{printed_at}}";
+pub(crate) const DEFAULT_WORKSHEET_TEMPLATE: &str = "PARTS PULL
+{{#invoice_no}}Invoice: {{invoice_no}}
+{{/invoice_no}}{{#customer_name}}Customer: {{customer_name}}
+{{/customer_name}}{{#vehicle}}Vehicle: {{vehicle}}
+{{/vehicle}}Printed: {{printed_at}}
+{{#groups}}
+== {{category}} ({{item_count}}) ==
+{{#items}}[ ] {{quantity}} x {{name}}
+{{/items}}{{/groups}}";
+// Saved template name for export_receipts_pdf, and the layout used when none is saved
+pub(crate) const RECEIPT_PDF_TEMPLATE_NAME: &str = "receipt";
+pub(crate) const DEFAULT_RECEIPT_PDF_TEMPLATE: &str = "{{title}}
+Receipt #: {{receipt_number}}
+Date: {{date}}
+Customer: {{customer_name}}
+{{#customer_phone}}Phone: {{customer_phone}}
+{{/customer_phone}}{{divider}}
+{{#items}}{{name}}
+  {{quantity}} x {{price}} = {{line_total}}
+{{/items}}{{divider}}
+Subtotal: {{subtotal}}
+{{#discount}}Discount: -{{discount}}
+{{/discount}}TOTAL: {{total}}
+Payment: {{payment_mode}}
+{{divider}}";
+
+/// CP437 characters for bytes 0x80..=0xFF, in order
+pub(crate) const CP437_HIGH: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»░▒▓│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{a0}";
+
+// QR code limits: keep payloads scannable on an 80mm receipt
+pub(crate) const QR_MAX_DATA_LEN: usize = 1024;
+pub(crate) const QR_MIN_SIZE: u32 = 64;
+pub(crate) const QR_MAX_SIZE: u32 = 2048;
+pub(crate) const QR_MAX_MODULE_SIZE: u8 = 16;
+
+// Barcode label raster: printable width of an 80mm head, bar height, quiet zone
+pub(crate) const BARCODE_MAX_WIDTH_DOTS: usize = 576;
+pub(crate) const BARCODE_HEIGHT_DOTS: u16 = 100;
+pub(crate) const BARCODE_QUIET_ZONE_MODULES: usize = 10;
+pub(crate) const CODE128_MAX_LEN: usize = 40;
+
+// ============================================
+// PRINTING COMMANDS
+// ============================================
+
+/// Timeout for shelled-out print commands, configurable via settings
+pub(crate) fn get_print_timeout(app: &AppHandle) -> Duration {
+    let secs = get_setting(app, PRINT_TIMEOUT_KEY)
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_PRINT_TIMEOUT_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Prints a receipt and returns once the spooler accepts it, or with `confirm`
+/// once the job shows up in the completed queue (or the wait runs out).
+/// `printer_name` picks a printer from list_printers (or "host:port" for a
+/// network printer); None uses the default printer.
+#[tauri::command]
+pub(crate) async fn print_receipt(
+    app: AppHandle,
+    text: String,
+    options: Option<PrintOptions>,
+    printer_name: Option<String>,
+) -> Result<PrintJob, String> {
+    let printer_name = printer_name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let confirm = options.confirm.then(|| {
+            options
+                .confirm_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_PRINT_CONFIRM_TIMEOUT)
+        });
+        let printer = match printer_name {
+            Some(name) => name,
+            None => cached_default_printer(&app, get_print_timeout(&app))?.0,
+        };
+        let job_id = submit_receipt(&app, text, options, &printer)?;
+        let status = match (confirm, &job_id) {
+            (None, _) => "submitted",
+            (Some(_), None) => "unconfirmed",
+            (Some(timeout), Some(job_id)) => wait_for_print_job(&app, job_id, timeout),
+        };
+        Ok(PrintJob {
+            job_id,
+            printer,
+            status: status.to_string(),
+        })
+    })
+    .await
+    .map_err(|e| format!("Print task failed: {}", e))?
+}
+
+pub(crate) fn submit_receipt(
+    app: &AppHandle,
+    text: String,
+    options: PrintOptions,
+    printer: &str,
+) -> Result<Option<String>, String> {
+    let text = if options.raw {
+        text
+    } else {
+        decorate_receipt(app, &text)
+    };
+
+    if options.enforce_limits {
+        let (max_lines, max_width) = receipt_profile_limits(app);
+        let validation = check_receipt_limits(&text, max_lines, max_width);
+        if !validation.valid {
+            return Err(describe_receipt_violation(&validation));
+        }
+    }
+
+    if let Some((encoding, substitute)) =
+        receipt_codepage(app, options.encoding, options.substitute)
+    {
+        let data = encode_receipt_for_codepage(&text, &encoding, &substitute)?;
+        return send_to_named_printer(
+            app,
+            Some(printer.to_string()),
+            &data,
+            get_print_timeout(app),
+        );
+    }
+    if network_printer_address(printer).is_some() {
+        return send_to_named_printer(
+            app,
+            Some(printer.to_string()),
+            text.as_bytes(),
+            get_print_timeout(app),
+        );
+    }
+
+    send_to_printer(app, Some(printer), &text)
+}
+
+/// Polls `lpstat -W completed` until the job appears there: "printed" when it
+/// completed normally, "failed" when CUPS aborted or cancelled it, and
+/// "timeout" when it hasn't finished within `timeout`
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn wait_for_print_job(app: &AppHandle, job_id: &str, timeout: Duration) -> &'static str {
+    let started = Instant::now();
+    loop {
+        let completed = run_command_with_timeout(
+            Command::new("lpstat").args(["-W", "completed", "-l", "-o"]),
+            get_print_timeout(app),
+        );
+        if let Some(status) = completed
+            .ok()
+            .and_then(|out| completed_job_status(&String::from_utf8_lossy(&out.stdout), job_id))
+        {
+            return status;
+        }
+        if started.elapsed() + PRINT_CONFIRM_POLL_INTERVAL > timeout {
+            return "timeout";
+        }
+        std::thread::sleep(PRINT_CONFIRM_POLL_INTERVAL);
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(crate) fn wait_for_print_job(
+    _app: &AppHandle,
+    _job_id: &str,
+    _timeout: Duration,
+) -> &'static str {
+    "unconfirmed"
+}
+
+/// Outcome of `job_id` in `lpstat -l -W completed` output, None when it isn't listed.
+/// Each job is a line starting with its id followed by indented detail lines.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn completed_job_status(stdout: &str, job_id: &str) -> Option<&'static str> {
+    let mut lines = stdout.lines();
+    lines.find(|line| line.split_whitespace().next() == Some(job_id))?;
+    let details = lines
+        .take_while(|line| line.starts_with(char::is_whitespace))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    if ["aborted", "canceled", "cancelled", "stopped"]
+        .iter()
+        .any(|word| details.contains(word))
+    {
+        Some("failed")
+    } else {
+        Some("printed")
+    }
+}
+
+/// Codepage and substitute for a receipt: the given overrides, else the settings.
+/// None when no codepage is configured and the text goes out as UTF-8.
+pub(crate) fn receipt_codepage(
+    app: &AppHandle,
+    encoding: Option<String>,
+    substitute: Option<String>,
+) -> Option<(String, String)> {
+    let encoding = encoding
+        .or_else(|| get_setting(app, RECEIPT_ENCODING_KEY))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())?;
+    let substitute = substitute
+        .or_else(|| get_setting(app, RECEIPT_SUBSTITUTE_KEY).filter(|v| !v.is_empty()))
+        .unwrap_or_else(|| DEFAULT_RECEIPT_SUBSTITUTE.to_string());
+    Some((encoding, substitute))
+}
+
+/// ESC/POS code table number (for ESC t n) and encoder for a codepage name.
+/// encoding_rs has no CP437, so None means the built-in CP437_HIGH table.
+pub(crate) fn resolve_receipt_codepage(
+    name: &str,
+) -> Result<(u8, Option<&'static encoding_rs::Encoding>), String> {
+    match name
+        .to_ascii_lowercase()
+        .replace(['-', '_', ' '], "")
+        .as_str()
+    {
+        "cp437" | "pc437" | "ibm437" => Ok((0, None)),
+        "cp1252" | "windows1252" => Ok((16, Some(encoding_rs::WINDOWS_1252))),
+        "cp866" | "pc866" | "ibm866" => Ok((17, Some(encoding_rs::IBM866))),
+        _ => Err(format!(
+            "Unsupported receipt encoding: {}. Use cp437, cp1252 or cp866",
+            name
+        )),
+    }
+}
+
+/// Selects the codepage with ESC t n and transcodes `text` into it, writing
+/// `substitute` for each character the codepage can't represent
+pub(crate) fn encode_receipt_for_codepage(
+    text: &str,
+    encoding: &str,
+    substitute: &str,
+) -> Result<Vec<u8>, String> {
+    if !substitute.is_ascii() {
+        return Err("The substitute for unprintable characters must be ASCII".to_string());
+    }
+    let (table, encoder) = resolve_receipt_codepage(encoding)?;
+
+    let mut bytes = vec![0x1b, 0x74, table];
+    match encoder {
+        None => {
+            for c in text.chars() {
+                if c.is_ascii() {
+                    bytes.push(c as u8);
+                } else if let Some(index) = CP437_HIGH.chars().position(|h| h == c) {
+                    bytes.push(0x80 + index as u8);
+                } else {
+                    bytes.extend_from_slice(substitute.as_bytes());
+                }
+            }
+        }
+        Some(encoding) => {
+            let mut encoder = encoding.new_encoder();
+            let mut input = text;
+            loop {
+                bytes.reserve(input.len() + 16);
+                let (result, read) =
+                    encoder.encode_from_utf8_to_vec_without_replacement(input, &mut bytes, true);
+                input = &input[read..];
+                match result {
+                    encoding_rs::EncoderResult::InputEmpty => break,
+                    encoding_rs::EncoderResult::OutputFull => {}
+                    encoding_rs::EncoderResult::Unmappable(_) => {
+                        bytes.extend_from_slice(substitute.as_bytes())
+                    }
+                }
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+/// Reads the printer profile's line and width limits from settings
+pub(crate) fn receipt_profile_limits(app: &AppHandle) -> (Option<usize>, usize) {
+    let read = |key| {
+        get_setting(app, key)
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|v| *v > 0)
+    };
+    (
+        read(RECEIPT_MAX_LINES_KEY),
+        read(RECEIPT_MAX_WIDTH_KEY).unwrap_or(RECEIPT_WIDTH),
+    )
+}
+
+/// Checks text against line/width limits; line_count is measured after wrapping
+pub(crate) fn check_receipt_limits(
+    text: &str,
+    max_lines: Option<usize>,
+    max_width: usize,
+) -> ReceiptValidation {
+    let overlong_lines: Vec<ReceiptLineIssue> = text
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let width = line.trim_end().chars().count();
+            (width > max_width).then(|| ReceiptLineIssue {
+                line_number: index + 1,
+                width,
+                text: line.to_string(),
+            })
+        })
+        .collect();
+
+    let line_count = wrap_receipt_text(text.trim_end(), max_width)
+        .lines()
+        .count();
+    let exceeds_max_lines = max_lines.is_some_and(|max| line_count > max);
+
+    ReceiptValidation {
+        valid: !exceeds_max_lines && overlong_lines.is_empty(),
+        line_count,
+        max_lines,
+        max_width,
+        exceeds_max_lines,
+        overlong_lines,
+    }
+}
+
+pub(crate) fn describe_receipt_violation(validation: &ReceiptValidation) -> String {
+    let mut problems = Vec::new();
+    if validation.exceeds_max_lines {
+        problems.push(format!(
+            "{} lines (printer limit {})",
+            validation.line_count,
+            validation.max_lines.unwrap_or_default()
+        ));
+    }
+    if !validation.overlong_lines.is_empty() {
+        problems.push(format!(
+            "{} line(s) wider than {} characters",
+            validation.overlong_lines.len(),
+            validation.max_width
+        ));
+    }
+    format!("Receipt exceeds printer limits: {}", problems.join(", "))
+}
+
+/// Checks receipt text against printer limits without printing.
+/// Limits not passed in come from the printer profile settings.
+#[tauri::command]
+pub(crate) fn validate_receipt(
+    app: AppHandle,
+    text: String,
+    max_lines: Option<usize>,
+    max_width: Option<usize>,
+) -> Result<ReceiptValidation, String> {
+    if max_width == Some(0) {
+        return Err("Maximum width must be at least 1".to_string());
+    }
+
+    let (profile_lines, profile_width) = receipt_profile_limits(&app);
+    Ok(check_receipt_limits(
+        &text,
+        max_lines.or(profile_lines),
+        max_width.unwrap_or(profile_width),
+    ))
+}
+
+/// Wraps the receipt to the printer width, then adds the configured header and footer
+pub(crate) fn decorate_receipt(app: &AppHandle, text: &str) -> String {
+    let header = get_setting(app, RECEIPT_HEADER_KEY).unwrap_or_default();
+    let footer = get_setting(app, RECEIPT_FOOTER_KEY).unwrap_or_default();
+
+    let mut parts = Vec::new();
+    if !header.trim().is_empty() {
+        parts.push(wrap_receipt_text(header.trim_end(), RECEIPT_WIDTH));
+    }
+    parts.push(wrap_receipt_text(text.trim_end(), RECEIPT_WIDTH));
+    if !footer.trim().is_empty() {
+        parts.push(wrap_receipt_text(footer.trim_end(), RECEIPT_WIDTH));
+    }
+
+    parts.join("\n") + "\n"
+}
+
+/// Wraps each line to `width` characters, breaking at spaces where possible
+pub(crate) fn wrap_receipt_text(text: &str, width: usize) -> String {
+    let mut wrapped: Vec<String> = Vec::new();
+
+    for line in text.lines() {
+        let mut rest: Vec<char> = line.trim_end().chars().collect();
+        if rest.is_empty() {
+            wrapped.push(String::new());
+            continue;
+        }
+
+        while rest.len() > width {
+            // Break at the last space inside the width, or hard-break long words
+            let split = rest[..=width]
+                .iter()
+                .rposition(|c| *c == ' ')
+                .filter(|pos| *pos > 0)
+                .unwrap_or(width);
+            let head: String = rest[..split].iter().collect();
+            wrapped.push(head.trim_end().to_string());
+            rest = rest[split..].to_vec();
+            while rest.first() == Some(&' ') {
+                rest.remove(0);
+            }
+        }
+
+        wrapped.push(rest.into_iter().collect());
+    }
+
+    wrapped.join("\n")
+}
+
+/// Gets the shop header/footer printed on every receipt
+#[tauri::command]
+pub(crate) fn get_receipt_decorations(app: AppHandle) -> Result<ReceiptDecorations, String> {
+    Ok(ReceiptDecorations {
+        header: get_setting(&app, RECEIPT_HEADER_KEY).unwrap_or_default(),
+        footer: get_setting(&app, RECEIPT_FOOTER_KEY).unwrap_or_default(),
+    })
+}
+
+/// Saves the shop header/footer printed on every receipt
+#[tauri::command]
+pub(crate) fn set_receipt_decorations(
+    app: AppHandle,
+    header: String,
+    footer: String,
+) -> Result<(), String> {
+    set_setting(&app, RECEIPT_HEADER_KEY, &header)?;
+    set_setting(&app, RECEIPT_FOOTER_KEY, &footer)
+}
+
+/// Sends plain text to the named local printer, else the default printer.
+/// Returns the spooler job id when the platform reports one (CUPS).
+pub(crate) fn send_to_printer(
+    app: &AppHandle,
+    printer: Option<&str>,
+    text: &str,
+) -> Result<Option<String>, String> {
+    let timeout = get_print_timeout(app);
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        // Cached so the hot path doesn't run lpstat before every receipt
+        let printer = match printer {
+            Some(name) => name.to_string(),
+            None => cached_default_printer(app, timeout)?.0,
+        };
+
+        let tmp_path = unique_temp_path("receipt", "txt");
+        write_private_temp_file(&tmp_path, text.as_bytes())
+            .map_err(|e| format!("Failed to write receipt file: {e}"))?;
+
+        // lp hands the file to the spooler before exiting, so it can go right away
+        let lp = run_command_with_timeout(
+            Command::new("lp")
+                .args(["-d", &printer])
+                .arg(tmp_path.to_string_lossy().to_string()),
+            timeout,
+        );
+        if !lp.as_ref().is_ok_and(|out| out.status.success()) {
+            // The printer may have been removed; detect it again next time
+            invalidate_printer_cache(app);
+        }
+        let _ = fs::remove_file(&tmp_path);
+        let lp = lp.map_err(|e| match e.kind() {
+            std::io::ErrorKind::TimedOut => {
+                "Print command timed out (lp did not respond)".to_string()
+            }
+            _ => format!("Printing not available (lp not found): {e}"),
+        })?;
+
+        if !lp.status.success() {
+            let stderr = String::from_utf8_lossy(&lp.stderr);
+            return Err(format!("Print failed: {stderr}"));
+        }
+
+        Ok(parse_lp_job_id(&String::from_utf8_lossy(&lp.stdout)))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // Windows thermal printer support using PowerShell
+        // Write receipt to a temp file
+        let tmp_path = unique_temp_path("receipt", "txt");
+        write_private_temp_file(&tmp_path, text.as_bytes())
+            .map_err(|e| format!("Failed to write receipt file: {e}"))?;
+
+        // Use PowerShell to print to the named or default printer
+        // For 80mm thermal printers, Windows uses the standard print spooler
+        let target = printer
+            .map(|name| format!(" -Name '{}'", name.replace('\'', "''")))
+            .unwrap_or_default();
+        let output = run_command_with_timeout(
+            Command::new("powershell").args([
+                "-NoProfile",
+                "-Command",
+                &format!(
+                    "Get-Content -Path '{}' -Raw | Out-Printer{}",
+                    tmp_path.to_string_lossy(),
+                    target
+                ),
+            ]),
+            timeout,
+        );
+        // Clean up temp file
+        let _ = fs::remove_file(&tmp_path);
+        let output = output.map_err(|e| match e.kind() {
+            std::io::ErrorKind::TimedOut => "Print command timed out".to_string(),
+            _ => format!("Failed to execute print command: {e}"),
+        })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            // Check if it's just a "no default printer" issue
+            if stderr.contains("printer") || stderr.contains("Printer") {
+                return Err("No default printer configured. Please set a default printer in Windows Settings.".to_string());
+            }
+            return Err(format!("Print failed: {stderr}"));
+        }
+
+        Ok(None)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+    {
+        let _ = (printer, text, timeout);
+        Err("Printing is currently supported only on Windows, macOS and Linux builds.".to_string())
+    }
+}
+
+/// Local printers with their state, for a printer picker.
+/// CUPS (`lpstat -p -d`) on Linux and macOS, Win32_Printer on Windows.
+#[tauri::command]
+pub(crate) async fn list_printers(app: AppHandle) -> Result<Vec<PrinterInfo>, String> {
+    let timeout = get_print_timeout(&app);
+    tauri::async_runtime::spawn_blocking(move || query_printers(timeout))
+        .await
+        .map_err(|e| format!("Printer query failed: {}", e))?
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn query_printers(timeout: Duration) -> Result<Vec<PrinterInfo>, String> {
+    let output = run_command_with_timeout(Command::new("lpstat").args(["-p", "-d"]), timeout)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::TimedOut => {
+                "Printer query timed out (lpstat did not respond)".to_string()
+            }
+            _ => format!("Printing not available (lpstat not found): {e}"),
+        })?;
+    // lpstat exits non-zero when there are no printers at all
+    Ok(parse_lpstat_printers(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Parses `lpstat -p -d`: "printer NAME is idle.  enabled since ...",
+/// "printer NAME disabled since ...", indented detail lines, and
+/// "system default destination: NAME"
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn parse_lpstat_printers(stdout: &str) -> Vec<PrinterInfo> {
+    let default = stdout
+        .lines()
+        .find_map(|line| line.split("system default destination:").nth(1))
+        .map(|name| name.trim().to_string());
+
+    let mut printers: Vec<PrinterInfo> = Vec::new();
+    for line in stdout.lines() {
+        if line.starts_with(char::is_whitespace) {
+            // Detail lines belong to the printer above, e.g. "Alerts: offline-report"
+            let lower = line.to_lowercase();
+            if let Some(printer) = printers.last_mut() {
+                if lower.trim_start().starts_with("alerts:") && lower.contains("offline") {
+                    printer.state = "offline".to_string();
+                }
+            }
+            continue;
+        }
+        let Some(rest) = line.strip_prefix("printer ") else {
+            continue;
+        };
+        let Some(name) = rest.split_whitespace().next() else {
+            continue;
+        };
+        let status = rest[name.len()..].to_lowercase();
+        let state = if status.contains("disabled") {
+            "disabled"
+        } else if status.contains("now printing") {
+            "printing"
+        } else {
+            "idle"
+        };
+        printers.push(PrinterInfo {
+            name: name.to_string(),
+            is_default: default.as_deref() == Some(name),
+            state: state.to_string(),
+        });
+    }
+    printers
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn query_printers(timeout: Duration) -> Result<Vec<PrinterInfo>, String> {
+    let output = run_command_with_timeout(
+        Command::new("powershell").args([
+            "-NoProfile",
+            "-Command",
+            "Get-CimInstance Win32_Printer | ForEach-Object { \"$($_.Name)`t$($_.Default)`t$($_.WorkOffline)`t$($_.PrinterStatus)\" }",
+        ]),
+        timeout,
+    )
+    .map_err(|e| match e.kind() {
+        std::io::ErrorKind::TimedOut => "Printer query timed out".to_string(),
+        _ => format!("Failed to query printers: {e}"),
+    })?;
+
+    // PrinterStatus 4 is "printing"
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.trim_end_matches(['\r', '\n']).split('\t').collect();
+            let [name, is_default, offline, status] = fields[..] else {
+                return None;
+            };
+            let state = if offline.eq_ignore_ascii_case("true") {
+                "offline"
+            } else if status == "4" {
+                "printing"
+            } else {
+                "idle"
+            };
+            Some(PrinterInfo {
+                name: name.to_string(),
+                is_default: is_default.eq_ignore_ascii_case("true"),
+                state: state.to_string(),
+            })
+        })
+        .filter(|printer| !printer.name.is_empty())
+        .collect())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+pub(crate) fn query_printers(_timeout: Duration) -> Result<Vec<PrinterInfo>, String> {
+    Err("Printing is currently supported only on Windows, macOS and Linux builds.".to_string())
+}
+
+/// Current printer availability, using the cached default printer when fresh
+#[tauri::command]
+pub(crate) fn check_printing_status(app: AppHandle) -> PrintingStatus {
+    printing_status(&app, false)
+}
+
+/// Re-detects the default printer, e.g. after the user changes printers
+#[tauri::command]
+pub(crate) fn refresh_printer_cache(app: AppHandle) -> PrintingStatus {
+    printing_status(&app, true)
+}
+
+pub(crate) fn printing_status(app: &AppHandle, refresh: bool) -> PrintingStatus {
+    if refresh {
+        invalidate_printer_cache(app);
+    }
+    match cached_default_printer(app, get_print_timeout(app)) {
+        Ok((printer, cached)) => PrintingStatus {
+            available: true,
+            message: format!("Printing to {}", printer),
+            printer: Some(printer),
+            cached,
+        },
+        Err(message) => PrintingStatus {
+            available: false,
+            printer: None,
+            cached: false,
+            message,
+        },
+    }
+}
+
+/// The default printer name and whether it came from the cache
+pub(crate) fn cached_default_printer(
+    app: &AppHandle,
+    timeout: Duration,
+) -> Result<(String, bool), String> {
+    let cache = app.state::<PrinterCache>();
+    if let Ok(entry) = cache.default_printer.lock() {
+        if let Some((detected_at, name)) = entry.as_ref() {
+            if detected_at.elapsed() < PRINTER_CACHE_TTL {
+                return Ok((name.clone(), true));
+            }
+        }
+    }
+
+    let name = detect_default_printer(timeout)?;
+    if let Ok(mut entry) = cache.default_printer.lock() {
+        *entry = Some((Instant::now(), name.clone()));
+    }
+    Ok((name, false))
+}
+
+pub(crate) fn invalidate_printer_cache(app: &AppHandle) {
+    if let Ok(mut entry) = app.state::<PrinterCache>().default_printer.lock() {
+        *entry = None;
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn detect_default_printer(timeout: Duration) -> Result<String, String> {
+    default_cups_printer(timeout)
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn detect_default_printer(timeout: Duration) -> Result<String, String> {
+    let output = run_command_with_timeout(
+        Command::new("powershell").args([
+            "-NoProfile",
+            "-Command",
+            "(Get-CimInstance Win32_Printer -Filter 'Default=true').Name",
+        ]),
+        timeout,
+    )
+    .map_err(|e| match e.kind() {
+        std::io::ErrorKind::TimedOut => "Printer query timed out".to_string(),
+        _ => format!("Failed to query printers: {e}"),
+    })?;
+
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if name.is_empty() {
+        return Err(
+            "No default printer configured. Please set a default printer in Windows Settings."
+                .to_string(),
+        );
+    }
+    Ok(name)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+pub(crate) fn detect_default_printer(_timeout: Duration) -> Result<String, String> {
+    Err("Printing is currently supported only on Windows, macOS and Linux builds.".to_string())
+}
+
+/// Default printer status with the printer itself queried, not just configured:
+/// unavailable when CUPS has it disabled or reporting offline, or Windows has it
+/// set to work offline
+pub(crate) fn live_printer_status(app: &AppHandle) -> PrintingStatus {
+    let status = printing_status(app, true);
+    let Some(printer) = status.printer.clone().filter(|_| status.available) else {
+        return status;
+    };
+    match query_printer_online(&printer, get_print_timeout(app)) {
+        Ok(()) => status,
+        Err(message) => PrintingStatus {
+            available: false,
+            cached: false,
+            message,
+            ..status
+        },
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn query_printer_online(printer: &str, timeout: Duration) -> Result<(), String> {
+    let output =
+        run_command_with_timeout(Command::new("lpstat").args(["-l", "-p", printer]), timeout)
+            .map_err(|e| format!("Printer query failed: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("{} is not available", printer));
+    }
+    // "printer X disabled since ..." and an "Alerts: offline-report" line
+    let report = String::from_utf8_lossy(&output.stdout).to_lowercase();
+    if report.contains("disabled since") {
+        return Err(format!("{} is disabled", printer));
+    }
+    if report
+        .lines()
+        .any(|line| line.trim_start().starts_with("alerts:") && line.contains("offline"))
+    {
+        return Err(format!("{} is offline", printer));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn query_printer_online(printer: &str, timeout: Duration) -> Result<(), String> {
+    let output = run_command_with_timeout(
+        Command::new("powershell").args([
+            "-NoProfile",
+            "-Command",
+            &format!(
+                "(Get-CimInstance Win32_Printer | Where-Object Name -eq '{}').WorkOffline",
+                printer.replace('\'', "''")
+            ),
+        ]),
+        timeout,
+    )
+    .map_err(|e| format!("Printer query failed: {}", e))?;
+    if String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .eq_ignore_ascii_case("true")
+    {
+        return Err(format!("{} is offline", printer));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+pub(crate) fn query_printer_online(_printer: &str, _timeout: Duration) -> Result<(), String> {
+    Ok(())
+}
+
+/// Checks the default printer every `interval_secs` (default 15) in the
+/// background and emits "printer-status-changed" with the new status whenever
+/// it goes between available and unavailable; the first check always emits.
+/// Starting again replaces the running monitor.
+#[tauri::command]
+pub(crate) fn start_printer_monitor(
+    app: AppHandle,
+    interval_secs: Option<u64>,
+) -> Result<(), String> {
+    let interval = interval_secs.unwrap_or(DEFAULT_PRINTER_MONITOR_INTERVAL_SECS);
+    if !(MIN_PRINTER_MONITOR_INTERVAL_SECS..=MAX_PRINTER_MONITOR_INTERVAL_SECS).contains(&interval)
+    {
+        return Err(format!(
+            "Interval must be {} to {} seconds",
+            MIN_PRINTER_MONITOR_INTERVAL_SECS, MAX_PRINTER_MONITOR_INTERVAL_SECS
+        ));
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let previous = app
+        .state::<PrinterMonitor>()
+        .stop
+        .lock()
+        .map_err(|_| "Printer monitor is unavailable".to_string())?
+        .replace(stop.clone());
+    if let Some(previous) = previous {
+        previous.store(true, Ordering::SeqCst);
+    }
+
+    std::thread::spawn(move || {
+        let mut last_available: Option<bool> = None;
+        while !stop.load(Ordering::SeqCst) {
+            let status = live_printer_status(&app);
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+            if last_available != Some(status.available) {
+                last_available = Some(status.available);
+                let _ = app.emit("printer-status-changed", status);
+            }
+            // Sleep in slices so stop_printer_monitor takes effect quickly
+            let wake_at = Instant::now() + Duration::from_secs(interval);
+            while Instant::now() < wake_at && !stop.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(250));
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Stops the printer monitor; returns false when none was running
+#[tauri::command]
+pub(crate) fn stop_printer_monitor(app: AppHandle) -> Result<bool, String> {
+    let stop = app
+        .state::<PrinterMonitor>()
+        .stop
+        .lock()
+        .map_err(|_| "Printer monitor is unavailable".to_string())?
+        .take();
+    Ok(stop
+        .map(|stop| stop.store(true, Ordering::SeqCst))
+        .is_some())
+}
+
+/// Runs a printer check in the background at startup unless disabled in settings
+pub(crate) fn prewarm_printer_cache(app: &AppHandle) {
+    let enabled = get_setting(app, PRINTER_PREWARM_KEY)
+        .map(|v| setting_is_true(&v))
+        .unwrap_or(true);
+    if !enabled {
+        return;
+    }
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let status = printing_status(&app, true);
+        if !status.available {
+            log::warn!("{}", status.message);
+        }
+    });
+}
+
+/// Extracts the job id from lp output like "request id is HP-123 (1 file(s))"
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn parse_lp_job_id(stdout: &str) -> Option<String> {
+    stdout
+        .split("request id is ")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .map(|id| id.to_string())
+}
+
+/// Paper and ink/toner levels for a printer (default when none is named).
+/// Printers that don't report supplies come back with `reported: false`.
+#[tauri::command]
+pub(crate) fn get_printer_supplies(
+    app: AppHandle,
+    printer_name: Option<String>,
+) -> Result<PrinterSupplies, String> {
+    let printer_name = printer_name.filter(|name| !name.trim().is_empty());
+
+    #[cfg(target_os = "linux")]
+    {
+        let timeout = get_print_timeout(&app);
+        let printer = match printer_name {
+            Some(name) => name,
+            None => default_cups_printer(timeout)?,
+        };
+
+        let uri = format!("ipp://localhost/printers/{}", printer);
+        let output = match run_command_with_timeout(
+            Command::new("ipptool").args(["-t", "-v", &uri, "get-printer-attributes.test"]),
+            timeout,
+        ) {
+            Ok(output) if output.status.success() => output,
+            Ok(output) => {
+                return Ok(supplies_not_reported(
+                    Some(printer),
+                    format!(
+                        "Printer did not answer the supply query: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ),
+                ))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                return Ok(supplies_not_reported(
+                    Some(printer),
+                    "Supply query timed out".to_string(),
+                ))
+            }
+            Err(e) => {
+                return Ok(supplies_not_reported(
+                    Some(printer),
+                    format!("Supply levels unavailable (ipptool not found): {}", e),
+                ))
+            }
+        };
+
+        let attributes = parse_ipp_attributes(&String::from_utf8_lossy(&output.stdout));
+        Ok(build_printer_supplies(printer, &attributes))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = &app;
+        Ok(supplies_not_reported(
+            printer_name,
+            "Supply levels are only available through CUPS on Linux".to_string(),
+        ))
+    }
+}
+
+pub(crate) fn supplies_not_reported(printer: Option<String>, message: String) -> PrinterSupplies {
+    PrinterSupplies {
+        printer,
+        paper: "not reported".to_string(),
+        supplies: Vec::new(),
+        reported: false,
+        message,
+    }
+}
+
+/// Name of the CUPS default destination from `lpstat -d`
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn default_cups_printer(timeout: Duration) -> Result<String, String> {
+    let output =
+        run_command_with_timeout(Command::new("lpstat").arg("-d"), timeout).map_err(|e| match e
+            .kind()
+        {
+            std::io::ErrorKind::TimedOut => {
+                "Printer query timed out (lpstat did not respond)".to_string()
+            }
+            _ => format!("Printing not available (lpstat not found): {e}"),
+        })?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.split("system default destination:").nth(1))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| {
+            "No default printer configured. Please set one in system settings (CUPS).".to_string()
+        })
+}
+
+/// Collects "name (type) = a,b,c" lines from verbose ipptool output
+#[cfg(target_os = "linux")]
+pub(crate) fn parse_ipp_attributes(stdout: &str) -> HashMap<String, Vec<String>> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (lhs, values) = line.trim().split_once(" = ")?;
+            let name = lhs.split_whitespace().next()?;
+            let values = values.split(',').map(|v| v.trim().to_string()).collect();
+            Some((name.to_string(), values))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn build_printer_supplies(
+    printer: String,
+    attributes: &HashMap<String, Vec<String>>,
+) -> PrinterSupplies {
+    let list = |key: &str| attributes.get(key).cloned().unwrap_or_default();
+    let names = list("marker-names");
+    let kinds = list("marker-types");
+    let levels = list("marker-levels");
+    let low_levels = list("marker-low-levels");
+    let reasons = list("printer-state-reasons");
+
+    let supplies: Vec<PrinterSupply> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            // IPP uses negative levels for "unknown"; -3 means "some remaining"
+            let raw = levels.get(i).and_then(|l| l.parse::<i32>().ok());
+            let low = low_levels.get(i).and_then(|l| l.parse::<i32>().ok());
+            let status = match raw {
+                Some(0) => "empty",
+                Some(level) if level > 0 && low.is_some_and(|low| level <= low) => "low",
+                Some(level) if level > 0 || level == -3 => "ok",
+                _ => "not reported",
+            };
+            PrinterSupply {
+                name: name.clone(),
+                kind: kinds.get(i).cloned(),
+                level_percent: raw.filter(|l| *l >= 0).map(|l| l.min(100) as u8),
+                status: status.to_string(),
+            }
+        })
+        .collect();
+
+    let has_reason = |needle: &str| reasons.iter().any(|r| r.starts_with(needle));
+    let paper = if has_reason("media-empty") || has_reason("media-needed") {
+        "empty"
+    } else if has_reason("media-low") {
+        "low"
+    } else {
+        "not reported"
+    };
+
+    let reported = !supplies.is_empty() || paper != "not reported";
+    let message = if reported {
+        format!("{} supply level(s) reported", supplies.len())
+    } else {
+        "Printer does not report supply levels".to_string()
+    };
+
+    PrinterSupplies {
+        printer: Some(printer),
+        paper: paper.to_string(),
+        supplies,
+        reported,
+        message,
+    }
+}
+
+/// End-of-day (Z) report figures for a YYYY-MM-DD local date, as template
+/// context. Timestamps are stored in UTC, so they are compared as local dates.
+pub(crate) fn zreport_context(
+    conn: &Connection,
+    date: &str,
+    tax_rate: f64,
+) -> Result<serde_json::Value, String> {
+    let (sale_count, gross, discounts): (i64, f64, f64) = conn
+        .query_row(
+            "SELECT COUNT(*), COALESCE(SUM(total_amount), 0), COALESCE(SUM(discount_amount), 0)
+             FROM invoices WHERE date(created_at, 'localtime') = date(?1) AND COALESCE(is_return, 0) = 0",
+            params![date],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| format!("Failed to query sales: {}", e))?;
+
+    let items_sold: i64 = conn
+        .query_row(
+            "SELECT COALESCE(SUM(ii.quantity), 0) FROM invoice_items ii
+             JOIN invoices i ON i.id = ii.invoice_id
+             WHERE date(i.created_at, 'localtime') = date(?1) AND COALESCE(i.is_return, 0) = 0",
+            params![date],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to query items sold: {}", e))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(payment_mode, 'cash'), COUNT(*), COALESCE(SUM(total_amount), 0)
+             FROM invoices WHERE date(created_at, 'localtime') = date(?1) AND COALESCE(is_return, 0) = 0
+             GROUP BY COALESCE(payment_mode, 'cash') ORDER BY 1",
+        )
+        .map_err(|e| format!("Failed to query payment modes: {}", e))?;
+    let payment_modes: Vec<(String, i64, f64)> = stmt
+        .query_map(params![date], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| format!("Failed to query payment modes: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    // Older databases may not have the returns table yet
+    let (return_count, returns_total): (i64, f64) = conn
+        .query_row(
+            "SELECT COUNT(*), COALESCE(SUM(total_amount), 0)
+             FROM sales_returns WHERE date(return_date, 'localtime') = date(?1)",
+            params![date],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap_or((0, 0.0));
+
+    let net_total = gross - returns_total;
+    // Prices include tax, so the tax is the part of the net above its pre-tax value
+    let tax = net_total - net_total / (1.0 + tax_rate / 100.0);
+
+    Ok(serde_json::json!({
+        "date": date,
+        "divider": "-".repeat(RECEIPT_WIDTH),
+        "transactions": sale_count,
+        "items_sold": items_sold,
+        "discounts": format!("{:.2}", discounts),
+        "gross_sales": format!("{:.2}", gross),
+        "payment_methods": payment_modes
+            .iter()
+            .map(|(mode, count, total)| {
+                serde_json::json!({
+                    "mode": mode.to_uppercase(),
+                    "count": count,
+                    "total": format!("{:.2}", total),
+                })
+            })
+            .collect::<Vec<_>>(),
+        "no_sales": payment_modes.is_empty(),
+        "return_count": return_count,
+        "returns_total": format!("{:.2}", returns_total),
+        "net_total": format!("{:.2}", net_total),
+        "tax_rate": format!("{}", tax_rate),
+        "tax": format!("{:.2}", tax),
+        "printed_at": Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    }))
+}
+
+/// Prints the end-of-day totals summary for a local date (YYYY-MM-DD), from the
+/// saved "zreport" template or the built-in one, with the receipt header and
+/// footer ({{store_name}} is only set without a header). Tax uses the tax_rate_percent setting (0 when unset).
+/// Returns the print job id when the spooler reports one.
+#[tauri::command]
+pub(crate) fn print_zreport(app: AppHandle, date: String) -> Result<Option<String>, String> {
+    chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date: {}. Expected YYYY-MM-DD", date))?;
+
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let tax_rate = get_setting(&app, TAX_RATE_PERCENT_KEY)
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|rate| rate.is_finite() && *rate >= 0.0)
+        .unwrap_or(0.0);
+    let mut context = {
+        let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        zreport_context(&conn, &date, tax_rate)?
+    };
+    // The receipt header usually names the shop already
+    if get_setting(&app, RECEIPT_HEADER_KEY).is_none_or(|h| h.trim().is_empty()) {
+        let store_name = get_setting(&app, "store_name").unwrap_or_else(|| "MotorMods".to_string());
+        context["store_name"] = store_name.into();
+    }
+
+    let template = get_setting(&app, &template_setting_key(ZREPORT_TEMPLATE_NAME)?)
+        .unwrap_or_else(|| DEFAULT_ZREPORT_TEMPLATE.to_string());
+    let tokens = parse_template(&template)
+        .map_err(|errors| format!("Z-report template is invalid: {}", errors.join("; ")))?;
+    let mut rendered = String::new();
+    render_template_tokens(&tokens, &mut vec![&context], &mut rendered, &mut Vec::new());
+
+    send_to_printer(&app, None, &decorate_receipt(&app, &rendered))
+}
+
+/// Sends raw bytes (usually ESC/POS) to a network printer, e.g. port 9100.
+/// `timeout_seconds` overrides the print_timeout_seconds setting for this call.
+#[tauri::command]
+pub(crate) async fn print_network(
+    app: AppHandle,
+    host: String,
+    port: u16,
+    data: Vec<u8>,
+    timeout_seconds: Option<u64>,
+) -> Result<(), String> {
+    let host = host.trim().to_string();
+    if host.is_empty() || host.len() > 253 || host.chars().any(|c| c.is_whitespace()) {
+        return Err(format!("Invalid printer host: {}", host));
+    }
+    if port == 0 {
+        return Err("Invalid printer port: 0".to_string());
+    }
+    if data.is_empty() {
+        return Err("Nothing to print".to_string());
+    }
+
+    let timeout = match timeout_seconds {
+        Some(0) => return Err("Timeout must be at least 1 second".to_string()),
+        Some(secs) => Duration::from_secs(secs),
+        None => get_print_timeout(&app),
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        send_to_network_printer(&host, port, &data, timeout)
+    })
+    .await
+    .map_err(|e| format!("Print task failed: {}", e))?
+}
+
+pub(crate) fn send_to_network_printer(
+    host: &str,
+    port: u16,
+    data: &[u8],
+    timeout: Duration,
+) -> Result<(), String> {
+    let addrs: Vec<_> = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Could not resolve printer {}: {}", host, e))?
+        .collect();
+
+    let mut last_error = None;
+    let mut stream = None;
+    for addr in &addrs {
+        match TcpStream::connect_timeout(addr, timeout) {
+            Ok(s) => {
+                stream = Some(s);
+                break;
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    let mut stream = stream.ok_or_else(|| match last_error {
+        Some(e) if e.kind() == std::io::ErrorKind::TimedOut => format!(
+            "Timed out connecting to printer {}:{} after {}s",
+            host,
+            port,
+            timeout.as_secs()
+        ),
+        Some(e) => format!("Could not connect to printer {}:{}: {}", host, port, e),
+        None => format!("Could not resolve printer {}", host),
+    })?;
+
+    stream
+        .set_write_timeout(Some(timeout))
+        .map_err(|e| format!("Failed to configure printer connection: {}", e))?;
+    stream
+        .write_all(data)
+        .and_then(|_| stream.flush())
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => format!(
+                "Timed out sending to printer {}:{} after {}s",
+                host,
+                port,
+                timeout.as_secs()
+            ),
+            _ => format!("Failed to send to printer {}:{}: {}", host, port, e),
+        })?;
+
+    let _ = stream.shutdown(Shutdown::Write);
+    Ok(())
+}
+
+// ============================================
+// STRUCTURED RECEIPTS
+// ============================================
+
+/// Splits "label      value" at the first run of 3+ spaces; None unless
+/// exactly two non-empty columns remain
+pub(crate) fn split_receipt_columns(line: &str) -> Option<(String, String)> {
+    let trimmed = line.trim();
+    let gap_start = trimmed.find("   ")?;
+    let label = trimmed[..gap_start].trim();
+    let value = trimmed[gap_start..].trim();
+    if label.is_empty() || value.is_empty() || value.contains("   ") {
+        return None;
+    }
+    Some((label.to_string(), value.to_string()))
+}
+
+pub(crate) fn is_divider_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.len() >= 3 && trimmed.chars().all(|c| matches!(c, '-' | '=' | '*' | '_'))
+}
+
+/// Best-effort conversion of a flat text receipt into a ReceiptDocument.
+///
+/// Heuristics only: leading indented (centered) lines become headers, runs of
+/// dashes become dividers, lines with two columns split by 3+ spaces become
+/// rows, and any line containing "TOTAL" is bold. Anything else stays plain
+/// text, so the result should be reviewed before relying on it.
+#[tauri::command]
+pub(crate) fn parse_receipt_text(text: String) -> ReceiptDocument {
+    let mut blocks = Vec::new();
+    let mut in_heading = true;
+
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            blocks.push(ReceiptBlock::Blank);
+            continue;
+        }
+        if is_divider_line(line) {
+            in_heading = false;
+            blocks.push(ReceiptBlock::Divider);
+            continue;
+        }
+
+        let centered = line.starts_with("  ");
+        if in_heading && centered {
+            blocks.push(ReceiptBlock::Header {
+                text: line.trim().to_string(),
+            });
+            continue;
+        }
+        in_heading = false;
+
+        let bold = line.contains("TOTAL");
+        blocks.push(match split_receipt_columns(line) {
+            Some((label, value)) => ReceiptBlock::Row { label, value, bold },
+            None => ReceiptBlock::Text {
+                text: line.to_string(),
+                bold,
+            },
+        });
+    }
+
+    ReceiptDocument { blocks }
+}
+
+// ============================================
+// RECEIPT TEMPLATES
+// ============================================
+
+// Templates are stored in settings as receipt_template_<name>
+pub(crate) const RECEIPT_TEMPLATE_KEY_PREFIX: &str = "receipt_template_";
+
+/// Result of validate_template: errors block saving, warnings don't
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateValidation {
+    pub valid: bool,
+    /// The template rendered against the sample data; None when it has errors
+    pub rendered: Option<String>,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+    /// Every placeholder and section name used, in first-use order
+    pub placeholders: Vec<String>,
+}
+
+/// Piece of a parsed template. Syntax: {{field}}, {{a.b}} for nested fields,
+/// {{#items}}...{{/items}} repeats for each array element (or shows the block
+/// once for a true/non-empty value), and {{.}} is the current element.
+pub(crate) enum TemplateToken {
+    Text(String),
+    Field(String),
+    Section(String, Vec<TemplateToken>),
+}
+
+/// Parses a template into tokens; errors name the line they occur on
+pub(crate) fn parse_template(template: &str) -> Result<Vec<TemplateToken>, Vec<String>> {
+    let mut errors = Vec::new();
+    // Open sections: (name, line, tokens collected before it opened)
+    let mut stack: Vec<(String, usize, Vec<TemplateToken>)> = Vec::new();
+    let mut tokens = Vec::new();
+    let mut rest = template;
+    let line_of = |rest: &str| {
+        template[..template.len() - rest.len()]
+            .matches('\n')
+            .count()
+            + 1
+    };
+
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            tokens.push(TemplateToken::Text(rest[..start].to_string()));
+        }
+        let line = line_of(&rest[start..]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            errors.push(format!("Line {}: unclosed placeholder, missing }}}}", line));
+            rest = "";
+            break;
+        };
+        // "{{name ... {{" : report the first one and carry on from the second
+        if let Some(next) = after[..end].find("{{") {
+            errors.push(format!(
+                "Line {}: unclosed placeholder {{{{{}",
+                line,
+                after[..next].trim_end()
+            ));
+            rest = &after[next..];
+            continue;
+        }
+        let tag = after[..end].trim();
+        rest = &after[end + 2..];
+
+        if let Some(name) = tag.strip_prefix('#') {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                errors.push(format!("Line {}: section has no name", line));
+                continue;
+            }
+            stack.push((name, line, std::mem::take(&mut tokens)));
+        } else if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            match stack.pop() {
+                Some((open, _, outer)) if open == name => {
+                    let body = std::mem::replace(&mut tokens, outer);
+                    tokens.push(TemplateToken::Section(open, body));
+                }
+                Some((open, open_line, outer)) => {
+                    errors.push(format!(
+                        "Line {}: {{{{/{}}}}} closes {{{{#{}}}}} opened on line {}",
+                        line, name, open, open_line
+                    ));
+                    let body = std::mem::replace(&mut tokens, outer);
+                    tokens.push(TemplateToken::Section(open, body));
+                }
+                None => errors.push(format!(
+                    "Line {}: {{{{/{}}}}} has no matching {{{{#{}}}}}",
+                    line, name, name
+                )),
+            }
+        } else if tag.is_empty() {
+            errors.push(format!("Line {}: empty placeholder {{{{}}}}", line));
+        } else if tag != "."
+            && !tag.split('.').all(|part| {
+                !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+            })
+        {
+            errors.push(format!(
+                "Line {}: invalid placeholder {{{{{}}}}}",
+                line, tag
+            ));
+        } else {
+            tokens.push(TemplateToken::Field(tag.to_string()));
+        }
+    }
+    if !rest.is_empty() {
+        tokens.push(TemplateToken::Text(rest.to_string()));
+    }
+    for (name, line, _) in stack.iter().rev() {
+        errors.push(format!("Line {}: {{{{#{}}}}} is never closed", line, name));
+    }
+
+    if errors.is_empty() {
+        Ok(tokens)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Looks a dotted path up from the innermost context outwards
+pub(crate) fn lookup_template_value<'a>(
+    contexts: &[&'a serde_json::Value],
+    path: &str,
+) -> Option<&'a serde_json::Value> {
+    if path == "." {
+        return contexts.last().copied();
+    }
+    let mut parts = path.split('.');
+    let first = parts.next()?;
+    let mut value = contexts.iter().rev().find_map(|c| c.get(first))?;
+    for part in parts {
+        value = value.get(part)?;
+    }
+    Some(value)
+}
+
+pub(crate) fn push_template_warning(warnings: &mut Vec<String>, message: String) {
+    if !warnings.contains(&message) {
+        warnings.push(message);
+    }
+}
+
+pub(crate) fn render_template_tokens(
+    tokens: &[TemplateToken],
+    contexts: &mut Vec<&serde_json::Value>,
+    out: &mut String,
+    warnings: &mut Vec<String>,
+) {
+    for token in tokens {
+        match token {
+            TemplateToken::Text(text) => out.push_str(text),
+            TemplateToken::Field(path) => match lookup_template_value(contexts, path) {
+                Some(serde_json::Value::String(v)) => out.push_str(v),
+                Some(serde_json::Value::Null) => {}
+                Some(v @ (serde_json::Value::Number(_) | serde_json::Value::Bool(_))) => {
+                    out.push_str(&v.to_string())
+                }
+                Some(_) => push_template_warning(
+                    warnings,
+                    format!(
+                        "{{{{{}}}}} is a list or object; use {{{{#{}}}}}...{{{{/{}}}}}",
+                        path, path, path
+                    ),
+                ),
+                None => {
+                    push_template_warning(warnings, format!("Unknown placeholder {{{{{}}}}}", path))
+                }
+            },
+            TemplateToken::Section(path, body) => {
+                let Some(value) = lookup_template_value(contexts, path) else {
+                    push_template_warning(warnings, format!("Unknown section {{{{#{}}}}}", path));
+                    continue;
+                };
+                let items: Vec<&serde_json::Value> = match value {
+                    serde_json::Value::Array(items) => items.iter().collect(),
+                    serde_json::Value::Null | serde_json::Value::Bool(false) => Vec::new(),
+                    serde_json::Value::String(v) if v.is_empty() => Vec::new(),
+                    other => vec![other],
+                };
+                for item in items {
+                    contexts.push(item);
+                    render_template_tokens(body, contexts, out, warnings);
+                    contexts.pop();
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn collect_template_names(tokens: &[TemplateToken], names: &mut Vec<String>) {
+    for token in tokens {
+        let (TemplateToken::Field(name) | TemplateToken::Section(name, _)) = token else {
+            continue;
+        };
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+        if let TemplateToken::Section(_, body) = token {
+            collect_template_names(body, names);
+        }
+    }
+}
+
+/// Checks a receipt template for malformed or unclosed placeholders and renders it
+/// against `sample_data`; placeholders the sample doesn't provide become warnings
+#[tauri::command]
+pub(crate) fn validate_template(
+    template: String,
+    sample_data: serde_json::Value,
+) -> TemplateValidation {
+    let tokens = match parse_template(&template) {
+        Ok(tokens) => tokens,
+        Err(errors) => {
+            return TemplateValidation {
+                valid: false,
+                rendered: None,
+                errors,
+                warnings: Vec::new(),
+                placeholders: Vec::new(),
+            }
+        }
+    };
+
+    let mut placeholders = Vec::new();
+    collect_template_names(&tokens, &mut placeholders);
+    let mut rendered = String::new();
+    let mut warnings = Vec::new();
+    render_template_tokens(
+        &tokens,
+        &mut vec![&sample_data],
+        &mut rendered,
+        &mut warnings,
+    );
+
+    TemplateValidation {
+        valid: true,
+        rendered: Some(rendered),
+        errors: Vec::new(),
+        warnings,
+        placeholders,
+    }
+}
+
+pub(crate) fn template_setting_key(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty()
+        || name.len() > 64
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!(
+            "Invalid template name: {}. Use letters, numbers, '-' and '_' only",
+            name
+        ));
+    }
+    Ok(format!("{}{}", RECEIPT_TEMPLATE_KEY_PREFIX, name))
+}
+
+/// Saves a receipt template under `name`. Unless validate is false it is checked
+/// first and refused when it has errors; the validation is returned either way.
+#[tauri::command]
+pub(crate) fn save_template(
+    app: AppHandle,
+    name: String,
+    template: String,
+    validate: Option<bool>,
+    sample_data: Option<serde_json::Value>,
+) -> Result<Option<TemplateValidation>, String> {
+    let key = template_setting_key(&name)?;
+    let validation = validate.unwrap_or(true).then(|| {
+        validate_template(
+            template.clone(),
+            sample_data.unwrap_or(serde_json::Value::Null),
+        )
+    });
+    if let Some(validation) = validation.as_ref().filter(|v| !v.valid) {
+        return Err(format!(
+            "Template not saved: {}",
+            validation.errors.join("; ")
+        ));
+    }
+    set_setting(&app, &key, &template)?;
+    Ok(validation)
+}
+
+/// Loads a saved receipt template; None when there is none under `name`
+#[tauri::command]
+pub(crate) fn get_template(app: AppHandle, name: String) -> Result<Option<String>, String> {
+    Ok(get_setting(&app, &template_setting_key(&name)?))
+}
+
+/// Drops price fields and groups `items` by category (first-seen order) into
+/// `groups: [{category, item_count, items}]` for the worksheet template
+pub(crate) fn worksheet_context(data: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let serde_json::Value::Object(fields) = data else {
+        return Err("Worksheet data must be an object with an items list".to_string());
+    };
+    let strip = |object: &serde_json::Map<String, serde_json::Value>| {
+        let mut object = object.clone();
+        object.retain(|key, _| !WORKSHEET_HIDDEN_FIELDS.contains(&key.as_str()));
+        object
+    };
+
+    let mut groups: Vec<(String, Vec<serde_json::Value>)> = Vec::new();
+    let items = fields
+        .get("items")
+        .and_then(|v| v.as_array())
+        .ok_or("Worksheet data has no items list")?;
+    for item in items {
+        let serde_json::Value::Object(item) = item else {
+            return Err("Each worksheet item must be an object".to_string());
+        };
+        let category = item
+            .get("category")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .unwrap_or("Uncategorised")
+            .to_string();
+        let item = serde_json::Value::Object(strip(item));
+        match groups.iter_mut().find(|(name, _)| *name == category) {
+            Some((_, items)) => items.push(item),
+            None => groups.push((category, vec![item])),
+        }
+    }
+
+    let mut context = strip(fields);
+    context.remove("items");
+    context
+        .entry("printed_at")
+        .or_insert_with(|| Local::now().format("%Y-%m-%d %H:%M").to_string().into());
+    context.insert(
+        "groups".to_string(),
+        groups
+            .into_iter()
+            .map(|(category, items)| {
+                serde_json::json!({
+                    "category": category,
+                    "item_count": items.len(),
+                    "items": items,
+                })
+            })
+            .collect(),
+    );
+    Ok(serde_json::Value::Object(context))
+}
+
+/// Prints an internal parts-pull worksheet: items grouped by category with no
+/// prices, from the saved "worksheet" template or the built-in one. Goes to
+/// `printer_name`, else the worksheet printer setting, else the default printer.
+/// Returns the spooler job id when the platform reports one.
+#[tauri::command]
+pub(crate) async fn print_worksheet(
+    app: AppHandle,
+    data: serde_json::Value,
+    printer_name: Option<String>,
+) -> Result<Option<String>, String> {
+    let template = get_setting(&app, &template_setting_key(WORKSHEET_TEMPLATE_NAME)?)
+        .unwrap_or_else(|| DEFAULT_WORKSHEET_TEMPLATE.to_string());
+    let tokens = parse_template(&template)
+        .map_err(|errors| format!("Worksheet template is invalid: {}", errors.join("; ")))?;
+    let context = worksheet_context(&data)?;
+    let mut rendered = String::new();
+    render_template_tokens(&tokens, &mut vec![&context], &mut rendered, &mut Vec::new());
+
+    let (_, max_width) = receipt_profile_limits(&app);
+    let text = wrap_receipt_text(rendered.trim_end(), max_width) + "\n\n\n";
+    let printer_name = printer_name
+        .or_else(|| get_setting(&app, WORKSHEET_PRINTER_KEY))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+    let codepage = receipt_codepage(&app, None, None);
+    let timeout = get_print_timeout(&app);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let data = match codepage {
+            Some((encoding, substitute)) => {
+                encode_receipt_for_codepage(&text, &encoding, &substitute)?
+            }
+            None if printer_name.is_none() => return send_to_printer(&app, None, &text),
+            None => text.into_bytes(),
+        };
+        send_to_named_printer(&app, printer_name, &data, timeout)
+    })
+    .await
+    .map_err(|e| format!("Print task failed: {}", e))?
+}
+
+/// Sets the printer worksheets go to; None or empty uses the default printer
+#[tauri::command]
+pub(crate) fn set_worksheet_printer(
+    app: AppHandle,
+    printer_name: Option<String>,
+) -> Result<(), String> {
+    set_setting(
+        &app,
+        WORKSHEET_PRINTER_KEY,
+        printer_name.as_deref().map(str::trim).unwrap_or_default(),
+    )
+}
+
+// ============================================
+// RECEIPT PDF EXPORT
+// ============================================
+
+/// One invoice as template context for the receipt PDF template
+pub(crate) fn invoice_receipt_context(
+    conn: &Connection,
+    invoice_id: &str,
+) -> Result<serde_json::Value, String> {
+    let (customer_name, customer_phone, discount, total, payment_mode, is_return, created_at): (
+        String,
+        Option<String>,
+        f64,
+        f64,
+        Option<String>,
+        i64,
+        String,
+    ) = conn
+        .query_row(
+            "SELECT customer_name, customer_phone, COALESCE(discount_amount, 0), total_amount,
+                    payment_mode, COALESCE(is_return, 0), COALESCE(created_at, '')
+             FROM invoices WHERE id = ?1",
+            params![invoice_id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                ))
+            },
+        )
+        .map_err(|e| format!("Failed to load invoice: {}", e))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(p.name, ii.product_id), ii.quantity, ii.price
+             FROM invoice_items ii LEFT JOIN products p ON p.id = ii.product_id
+             WHERE ii.invoice_id = ?1 ORDER BY ii.created_at, ii.id",
+        )
+        .map_err(|e| format!("Failed to query invoice items: {}", e))?;
+    let items: Vec<(String, i64, f64)> = stmt
+        .query_map(params![invoice_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .and_then(|rows| rows.collect::<Result<_, _>>())
+        .map_err(|e| format!("Failed to query invoice items: {}", e))?;
+
+    let mut subtotal = 0.0;
+    let items: Vec<serde_json::Value> = items
+        .iter()
+        .map(|(name, quantity, price)| {
+            let line_total = *quantity as f64 * price;
+            subtotal += line_total;
+            serde_json::json!({
+                "name": name,
+                "quantity": quantity,
+                "price": format!("{:.2}", price),
+                "line_total": format!("{:.2}", line_total),
+            })
+        })
+        .collect();
+
+    Ok(serde_json::json!({
+        "title": if is_return != 0 { "RETURN RECEIPT" } else { "RECEIPT" },
+        "receipt_number": invoice_id,
+        "date": created_at,
+        "customer_name": customer_name,
+        "customer_phone": customer_phone.filter(|p| !p.trim().is_empty()),
+        "divider": "-".repeat(RECEIPT_WIDTH),
+        "items": items,
+        "subtotal": format!("{:.2}", subtotal),
+        "discount": (discount > 0.0).then(|| format!("{:.2}", discount)),
+        "total": format!("{:.2}", total),
+        "payment_mode": payment_mode
+            .unwrap_or_else(|| "cash".to_string())
+            .to_uppercase(),
+    }))
+}
+
+/// A TrueType font embedded in receipt PDFs for characters Courier's WinAnsi
+/// set lacks, such as ₹ and Tamil. Glyph ids index `advances`.
+///
+/// Glyphs are drawn one per character without shaping, so Tamil vowel signs
+/// that render before their consonant (ெ, ே, ை and the two-part ொ, ோ, ௌ) and
+/// conjuncts come out in logical order rather than as they'd be written.
+pub(crate) struct PdfFont {
+    /// Standalone sfnt data, also when it came out of a .ttc collection
+    pub(crate) data: Vec<u8>,
+    pub(crate) units_per_em: u16,
+    pub(crate) advances: Vec<u16>,
+    pub(crate) cmap: HashMap<u32, u16>,
+    pub(crate) bbox: [i16; 4],
+    pub(crate) ascent: i16,
+    pub(crate) descent: i16,
+}
+
+pub(crate) fn be_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+pub(crate) fn be_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Tag -> table bytes of the first font in a .ttf or .ttc file
+pub(crate) fn sfnt_tables(data: &[u8]) -> Option<Vec<([u8; 4], &[u8])>> {
+    let base = if data.get(..4)? == b"ttcf" {
+        be_u32(data, 12)? as usize
+    } else {
+        0
+    };
+    // glyf outlines only; CFF-flavoured OpenType can't go in a FontFile2
+    if be_u32(data, base)? != 0x0001_0000 && data.get(base..base + 4)? != b"true" {
+        return None;
+    }
+    let count = be_u16(data, base + 4)? as usize;
+    (0..count)
+        .map(|i| {
+            let record = base + 12 + i * 16;
+            let tag: [u8; 4] = data.get(record..record + 4)?.try_into().ok()?;
+            let offset = be_u32(data, record + 8)? as usize;
+            let length = be_u32(data, record + 12)? as usize;
+            Some((tag, data.get(offset..offset.checked_add(length)?)?))
+        })
+        .collect()
+}
+
+/// Reads a format 4 or format 12 cmap subtable into codepoint -> glyph id
+pub(crate) fn parse_cmap_subtable(table: &[u8], map: &mut HashMap<u32, u16>) -> Option<()> {
+    match be_u16(table, 0)? {
+        4 => {
+            let seg_x2 = be_u16(table, 6)? as usize;
+            let ends = 14;
+            let starts = ends + seg_x2 + 2;
+            let deltas = starts + seg_x2;
+            let range_offsets = deltas + seg_x2;
+            for seg in (0..seg_x2).step_by(2) {
+                let end = be_u16(table, ends + seg)?;
+                let start = be_u16(table, starts + seg)?;
+                let delta = be_u16(table, deltas + seg)?;
+                let range_offset = be_u16(table, range_offsets + seg)? as usize;
+                for code in start..=end {
+                    if code == 0xFFFF {
+                        break;
+                    }
+                    let glyph = if range_offset == 0 {
+                        code.wrapping_add(delta)
+                    } else {
+                        let at = range_offsets + seg + range_offset + 2 * (code - start) as usize;
+                        match be_u16(table, at)? {
+                            0 => 0,
+                            glyph => glyph.wrapping_add(delta),
+                        }
+                    };
+                    if glyph != 0 {
+                        map.insert(code as u32, glyph);
+                    }
+                }
+            }
+        }
+        12 => {
+            let groups = be_u32(table, 12)? as usize;
+            for group in 0..groups {
+                let at = 16 + group * 12;
+                let (start, end, first) = (
+                    be_u32(table, at)?,
+                    be_u32(table, at + 4)?,
+                    be_u32(table, at + 8)?,
+                );
+                // Guard against a corrupt group claiming billions of codepoints
+                if end < start || end - start > 0x10_FFFF {
+                    return None;
+                }
+                for code in start..=end {
+                    map.insert(code, (first + code - start) as u16);
+                }
+            }
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+impl PdfFont {
+    pub(crate) fn parse(data: &[u8]) -> Option<Self> {
+        let tables = sfnt_tables(data)?;
+        let table = |tag: &[u8; 4]| tables.iter().find(|(t, _)| t == tag).map(|(_, d)| *d);
+        let (head, hhea, hmtx, maxp, cmap) = (
+            table(b"head")?,
+            table(b"hhea")?,
+            table(b"hmtx")?,
+            table(b"maxp")?,
+            table(b"cmap")?,
+        );
+        table(b"glyf")?;
+        table(b"loca")?;
+
+        let glyph_count = be_u16(maxp, 4)? as usize;
+        let metric_count = (be_u16(hhea, 34)? as usize).clamp(1, glyph_count.max(1));
+        let mut advances = Vec::with_capacity(glyph_count);
+        for glyph in 0..glyph_count {
+            advances.push(be_u16(hmtx, glyph.min(metric_count - 1) * 4)?);
+        }
+
+        // Prefer full-Unicode subtables, then the BMP ones
+        let mut map = HashMap::new();
+        let records: Vec<(u16, u16, usize)> = (0..be_u16(cmap, 2)? as usize)
+            .filter_map(|i| {
+                let at = 4 + i * 8;
+                Some((
+                    be_u16(cmap, at)?,
+                    be_u16(cmap, at + 2)?,
+                    be_u32(cmap, at + 4)? as usize,
+                ))
+            })
+            .collect();
+        let parsed = [(3, 10), (0, 4), (0, 6), (3, 1), (0, 3)]
+            .iter()
+            .any(|wanted| {
+                records
+                    .iter()
+                    .filter(|(platform, encoding, _)| (*platform, *encoding) == *wanted)
+                    .any(|(_, _, offset)| {
+                        cmap.get(*offset..)
+                            .and_then(|sub| parse_cmap_subtable(sub, &mut map))
+                            .is_some()
+                    })
+            });
+        if !parsed {
+            return None;
+        }
+
+        let signed = |data: &[u8], offset: usize| be_u16(data, offset).map(|v| v as i16);
+        Some(PdfFont {
+            data: data.to_vec(),
+            units_per_em: be_u16(head, 18)?.max(1),
+            advances,
+            cmap: map,
+            bbox: [
+                signed(head, 36)?,
+                signed(head, 38)?,
+                signed(head, 40)?,
+                signed(head, 42)?,
+            ],
+            ascent: signed(hhea, 4)?,
+            descent: signed(hhea, 6)?,
+        })
+    }
+
+    pub(crate) fn glyph(&self, c: char) -> Option<u16> {
+        self.cmap.get(&(c as u32)).copied()
+    }
+
+    /// Glyph advance in PDF text space units (1/1000 em)
+    pub(crate) fn width(&self, glyph: u16) -> u32 {
+        let advance = self.advances.get(glyph as usize).copied().unwrap_or(0) as u32;
+        advance * 1000 / self.units_per_em as u32
+    }
+
+    /// A standalone font with only `used` glyphs (and their components) keeping
+    /// outlines. Glyph ids are unchanged so the PDF can map CIDs straight to them.
+    pub(crate) fn subset(&self, used: &std::collections::BTreeSet<u16>) -> Option<Vec<u8>> {
+        let tables = sfnt_tables(&self.data)?;
+        let table = |tag: &[u8; 4]| tables.iter().find(|(t, _)| t == tag).map(|(_, d)| *d);
+        let (head, loca, glyf) = (table(b"head")?, table(b"loca")?, table(b"glyf")?);
+        let long_loca = be_u16(head, 50)? == 1;
+        let glyph_range = |glyph: usize| -> Option<(usize, usize)> {
+            if long_loca {
+                Some((
+                    be_u32(loca, glyph * 4)? as usize,
+                    be_u32(loca, glyph * 4 + 4)? as usize,
+                ))
+            } else {
+                Some((
+                    be_u16(loca, glyph * 2)? as usize * 2,
+                    be_u16(loca, glyph * 2 + 2)? as usize * 2,
+                ))
+            }
+        };
+
+        // Composite glyphs draw other glyphs, which need their outlines too
+        let mut keep: std::collections::BTreeSet<u16> = used.clone();
+        keep.insert(0);
+        let mut pending: Vec<u16> = keep.iter().copied().collect();
+        while let Some(glyph) = pending.pop() {
+            let (start, end) = glyph_range(glyph as usize)?;
+            let outline = glyf.get(start..end)?;
+            if outline.len() < 10 || be_u16(outline, 0)? as i16 >= 0 {
+                continue;
+            }
+            let mut at = 10;
+            loop {
+                let flags = be_u16(outline, at)?;
+                let component = be_u16(outline, at + 2)?;
+                if keep.insert(component) {
+                    pending.push(component);
+                }
+                at += 4 + if flags & 0x0001 != 0 { 4 } else { 2 };
+                at += match flags {
+                    f if f & 0x0080 != 0 => 8,
+                    f if f & 0x0040 != 0 => 4,
+                    f if f & 0x0008 != 0 => 2,
+                    _ => 0,
+                };
+                if flags & 0x0020 == 0 {
+                    break;
+                }
+            }
+        }
+
+        let mut new_glyf = Vec::new();
+        let mut new_loca = Vec::with_capacity((self.advances.len() + 1) * 4);
+        for glyph in 0..self.advances.len() {
+            new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+            if keep.contains(&(glyph as u16)) {
+                let (start, end) = glyph_range(glyph)?;
+                new_glyf.extend_from_slice(glyf.get(start..end)?);
+                while new_glyf.len() % 4 != 0 {
+                    new_glyf.push(0);
+                }
+            }
+        }
+        new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+        let mut new_head = head.to_vec();
+        new_head.get_mut(8..12)?.copy_from_slice(&[0; 4]);
+        new_head
+            .get_mut(50..52)?
+            .copy_from_slice(&1u16.to_be_bytes());
+
+        // What a PDF viewer needs to draw glyphs by id; layout tables are dropped
+        let mut out_tables: Vec<([u8; 4], Vec<u8>)> = Vec::new();
+        for tag in [
+            b"cvt ", b"fpgm", b"glyf", b"head", b"hhea", b"hmtx", b"loca", b"maxp", b"prep",
+        ] {
+            let data = match tag {
+                b"glyf" => new_glyf.clone(),
+                b"loca" => new_loca.clone(),
+                b"head" => new_head.clone(),
+                _ => match table(tag) {
+                    Some(data) => data.to_vec(),
+                    None => continue,
+                },
+            };
+            out_tables.push((*tag, data));
+        }
+
+        let count = out_tables.len() as u16;
+        let entry_selector = 15 - count.leading_zeros() as u16;
+        let search_range = (1u16 << entry_selector) * 16;
+        let mut font = Vec::new();
+        font.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        for value in [
+            count,
+            search_range,
+            entry_selector,
+            count * 16 - search_range,
+        ] {
+            font.extend_from_slice(&value.to_be_bytes());
+        }
+        let mut offset = 12 + out_tables.len() * 16;
+        for (tag, data) in &out_tables {
+            let mut padded = data.clone();
+            padded.resize(data.len().div_ceil(4) * 4, 0);
+            let checksum = padded.chunks(4).fold(0u32, |sum, word| {
+                sum.wrapping_add(u32::from_be_bytes(word.try_into().unwrap()))
+            });
+            font.extend_from_slice(tag);
+            font.extend_from_slice(&checksum.to_be_bytes());
+            font.extend_from_slice(&(offset as u32).to_be_bytes());
+            font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            offset += padded.len();
+        }
+        for (_, data) in &out_tables {
+            font.extend_from_slice(data);
+            font.resize(font.len().div_ceil(4) * 4, 0);
+        }
+        Some(font)
+    }
+}
+
+/// Loads the font for non-Latin receipt text: the receipt_pdf_font setting,
+/// else the first system font found that covers ₹ and Tamil
+pub(crate) fn load_pdf_font(app: &AppHandle) -> Option<PdfFont> {
+    let configured = get_setting(app, RECEIPT_PDF_FONT_KEY)
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty());
+    if let Some(path) = &configured {
+        match fs::read(path).ok().and_then(|data| PdfFont::parse(&data)) {
+            Some(font) => return Some(font),
+            None => log::warn!("{} is not a usable TrueType font", path),
+        }
+    }
+    PDF_FONT_CANDIDATES
+        .iter()
+        .filter_map(|path| fs::read(path).ok())
+        .filter_map(|data| PdfFont::parse(&data))
+        .find(|font| font.glyph('₹').is_some() && font.glyph('\u{0B95}').is_some())
+}
+
+/// One line of PDF text as runs of Courier bytes (WinAnsi) and embedded-font glyphs
+pub(crate) enum PdfRun {
+    Courier(Vec<u8>),
+    Glyphs(Vec<u16>),
+}
+
+/// Splits a line into font runs. Without a usable glyph, ₹ becomes "Rs." and
+/// anything else '?'; `substituted` is set when that happens.
+pub(crate) fn pdf_line_runs(
+    line: &str,
+    font: Option<&PdfFont>,
+    substituted: &mut bool,
+) -> Vec<PdfRun> {
+    let mut runs: Vec<PdfRun> = Vec::new();
+    for c in line.chars() {
+        let mut buf = [0u8; 4];
+        let (bytes, _, unmappable) = encoding_rs::WINDOWS_1252.encode(c.encode_utf8(&mut buf));
+        if !unmappable && bytes.len() == 1 && bytes[0] >= 0x20 && bytes[0] != 0x7F {
+            match runs.last_mut() {
+                Some(PdfRun::Courier(run)) => run.push(bytes[0]),
+                _ => runs.push(PdfRun::Courier(vec![bytes[0]])),
+            }
+            continue;
+        }
+        if let Some(glyph) = font.and_then(|f| f.glyph(c)) {
+            match runs.last_mut() {
+                Some(PdfRun::Glyphs(run)) => run.push(glyph),
+                _ => runs.push(PdfRun::Glyphs(vec![glyph])),
+            }
+            continue;
+        }
+        *substituted = true;
+        let replacement: &[u8] = if c == '₹' { b"Rs." } else { b"?" };
+        match runs.last_mut() {
+            Some(PdfRun::Courier(run)) => run.extend_from_slice(replacement),
+            _ => runs.push(PdfRun::Courier(replacement.to_vec())),
+        }
+    }
+    runs
+}
+
+/// Renders monospaced text as a PDF, PDF_PAGE_LINES lines per page. Text is set
+/// in the built-in Courier; characters outside its WinAnsi set come from `font`
+/// (subset and embedded). Returns the PDF and whether any character had to be
+/// replaced because no glyph for it was available.
+pub(crate) fn render_text_pdf(text: &str, font: Option<&PdfFont>) -> (Vec<u8>, bool) {
+    use flate2::{write::ZlibEncoder, Compression};
+
+    let lines: Vec<&str> = text.lines().collect();
+    let char_width = PDF_FONT_SIZE * 0.6;
+    let page_width = RECEIPT_WIDTH as f32 * char_width + PDF_MARGIN * 2.0;
+
+    let mut substituted = false;
+    let mut used = std::collections::BTreeSet::new();
+    let mut pages = Vec::new();
+    for page_lines in lines
+        .chunks(PDF_PAGE_LINES)
+        .chain(lines.is_empty().then_some(&[][..]))
+    {
+        let page_height = page_lines.len().max(1) as f32 * PDF_LINE_HEIGHT + PDF_MARGIN * 2.0;
+        let mut content = String::from("BT\n");
+        for (index, line) in page_lines.iter().enumerate() {
+            let y = page_height - PDF_MARGIN - PDF_FONT_SIZE - index as f32 * PDF_LINE_HEIGHT;
+            content.push_str(&format!("1 0 0 1 {} {} Tm\n", PDF_MARGIN, y));
+            for run in pdf_line_runs(line, font, &mut substituted) {
+                match run {
+                    PdfRun::Courier(bytes) => {
+                        let escaped: String = bytes
+                            .iter()
+                            .map(|b| match b {
+                                b'(' | b')' | b'\\' => format!("\\{}", *b as char),
+                                b' '..=b'~' => (*b as char).to_string(),
+                                _ => format!("\\{:03o}", b),
+                            })
+                            .collect();
+                        content.push_str(&format!("/F1 {} Tf ({}) Tj\n", PDF_FONT_SIZE, escaped));
+                    }
+                    PdfRun::Glyphs(glyphs) => {
+                        let hex: String = glyphs.iter().map(|g| format!("{:04X}", g)).collect();
+                        used.extend(glyphs);
+                        content.push_str(&format!("/F2 {} Tf <{}> Tj\n", PDF_FONT_SIZE, hex));
+                    }
+                }
+            }
+        }
+        content.push_str("ET\n");
+        pages.push((page_height, content));
+    }
+
+    // Objects 1 and 2 are the catalog and page tree, then font objects, then
+    // a page and content stream per page
+    let embedded = font
+        .filter(|_| !used.is_empty())
+        .and_then(|font| Some((font, font.subset(&used)?)));
+    let font_objects = if embedded.is_some() { 6 } else { 1 };
+    let first_page = 3 + font_objects;
+    let font_resources = if embedded.is_some() {
+        "/F1 3 0 R /F2 4 0 R"
+    } else {
+        "/F1 3 0 R"
+    };
+
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            (0..pages.len())
+                .map(|i| format!("{} 0 R", first_page + i * 2))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        )
+        .into_bytes(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>".to_vec(),
+    ];
+    if let Some((font, subset)) = &embedded {
+        let widths: String = used
+            .iter()
+            .map(|g| format!("{} [{}]", g, font.width(*g)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let scale = |v: i16| v as i32 * 1000 / font.units_per_em as i32;
+        let unicode: HashMap<u16, char> = font
+            .cmap
+            .iter()
+            .filter(|(_, g)| used.contains(g))
+            .filter_map(|(c, g)| Some((*g, char::from_u32(*c)?)))
+            .collect();
+        let mut to_unicode = String::from(
+            "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+             /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+             /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+             1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+        );
+        let mapped: Vec<(u16, char)> = used
+            .iter()
+            .filter_map(|g| Some((*g, *unicode.get(g)?)))
+            .collect();
+        for chunk in mapped.chunks(100) {
+            to_unicode.push_str(&format!("{} beginbfchar\n", chunk.len()));
+            for (glyph, c) in chunk {
+                let mut units = [0u16; 2];
+                let hex: String = c
+                    .encode_utf16(&mut units)
+                    .iter()
+                    .map(|u| format!("{:04X}", u))
+                    .collect();
+                to_unicode.push_str(&format!("<{:04X}> <{}>\n", glyph, hex));
+            }
+            to_unicode.push_str("endbfchar\n");
+        }
+        to_unicode.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+
+        let mut compressed = ZlibEncoder::new(Vec::new(), Compression::default());
+        compressed
+            .write_all(subset)
+            .expect("writing to a Vec can't fail");
+        let compressed = compressed.finish().expect("writing to a Vec can't fail");
+
+        objects.push(b"<< /Type /Font /Subtype /Type0 /BaseFont /ReceiptFont /Encoding /Identity-H /DescendantFonts [5 0 R] /ToUnicode 8 0 R >>".to_vec());
+        objects.push(
+            format!(
+                "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /ReceiptFont \
+                 /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+                 /FontDescriptor 6 0 R /DW 0 /W [{}] /CIDToGIDMap /Identity >>",
+                widths
+            )
+            .into_bytes(),
+        );
+        objects.push(
+            format!(
+                "<< /Type /FontDescriptor /FontName /ReceiptFont /Flags 4 /FontBBox [{} {} {} {}] \
+                 /ItalicAngle 0 /Ascent {} /Descent {} /CapHeight {} /StemV 80 /FontFile2 7 0 R >>",
+                scale(font.bbox[0]),
+                scale(font.bbox[1]),
+                scale(font.bbox[2]),
+                scale(font.bbox[3]),
+                scale(font.ascent),
+                scale(font.descent),
+                scale(font.ascent)
+            )
+            .into_bytes(),
+        );
+        let mut stream = format!(
+            "<< /Length {} /Length1 {} /Filter /FlateDecode >>\nstream\n",
+            compressed.len(),
+            subset.len()
+        )
+        .into_bytes();
+        stream.extend_from_slice(&compressed);
+        stream.extend_from_slice(b"\nendstream");
+        objects.push(stream);
+        objects.push(
+            format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                to_unicode.len(),
+                to_unicode
+            )
+            .into_bytes(),
+        );
+    }
+    for (index, (page_height, content)) in pages.iter().enumerate() {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.1} {:.1}] \
+                 /Resources << /Font << {} >> >> /Contents {} 0 R >>",
+                page_width,
+                page_height,
+                font_resources,
+                first_page + index * 2 + 1
+            )
+            .into_bytes(),
+        );
+        objects.push(
+            format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                content.len(),
+                content
+            )
+            .into_bytes(),
+        );
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+
+    let xref_offset = pdf.len();
+    pdf.extend_from_slice(
+        format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+    );
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_offset
+        )
+        .as_bytes(),
+    );
+
+    (pdf, substituted)
+}
+
+/// receipt_<number>.pdf with anything but letters, digits, '-' and '_' replaced,
+/// plus _2, _3, ... when an earlier receipt in `used` already took that name
+pub(crate) fn unique_receipt_file_name(
+    invoice_id: &str,
+    used: &mut std::collections::HashSet<String>,
+) -> String {
+    let safe_name: String = invoice_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let file_name = (1..)
+        .map(|n| match n {
+            1 => format!("receipt_{}.pdf", safe_name),
+            n => format!("receipt_{}_{}.pdf", safe_name, n),
+        })
+        .find(|name| !used.contains(name))
+        .expect("an unused name is always found");
+    used.insert(file_name.clone());
+    file_name
+}
+
+/// Exports every receipt from a local date (YYYY-MM-DD) as its own PDF, named by
+/// receipt number (with _2, _3, ... when two numbers map to the same file name).
+/// Receipts use the saved "receipt" template or the built-in one, with the
+/// receipt header and footer. One bad receipt doesn't stop the batch; failures
+/// are reported per invoice. Non-Latin text such as ₹ and Tamil uses an embedded
+/// system font (or the receipt_pdf_font setting), drawn without shaping (see
+/// PdfFont); `warning` says when no font had the glyphs.
+#[tauri::command]
+pub(crate) fn export_receipts_pdf(
+    app: AppHandle,
+    date: String,
+    destination_dir: String,
+) -> Result<ReceiptExportResult, String> {
+    chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date: {}. Expected YYYY-MM-DD", date))?;
+
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+
+    let template = get_setting(&app, &template_setting_key(RECEIPT_PDF_TEMPLATE_NAME)?)
+        .unwrap_or_else(|| DEFAULT_RECEIPT_PDF_TEMPLATE.to_string());
+    let tokens = parse_template(&template)
+        .map_err(|errors| format!("Receipt template is invalid: {}", errors.join("; ")))?;
+
+    let destination = normalize_user_path(&app, &destination_dir)?;
+    fs::create_dir_all(&destination)
+        .map_err(|e| format!("Failed to create destination folder: {}", e))?;
+
+    let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id FROM invoices WHERE date(created_at, 'localtime') = date(?1) ORDER BY created_at",
+        )
+        .map_err(|e| format!("Failed to query invoices: {}", e))?;
+    let invoice_ids: Vec<String> = stmt
+        .query_map(params![date], |row| row.get(0))
+        .and_then(|rows| rows.collect::<Result<_, _>>())
+        .map_err(|e| format!("Failed to query invoices: {}", e))?;
+
+    let font = load_pdf_font(&app);
+    let mut exported = 0;
+    let mut failures = Vec::new();
+    let mut substituted = false;
+    let mut used_names = std::collections::HashSet::new();
+
+    for invoice_id in invoice_ids {
+        let file_name = unique_receipt_file_name(&invoice_id, &mut used_names);
+
+        let result = invoice_receipt_context(&conn, &invoice_id).and_then(|context| {
+            let mut rendered = String::new();
+            render_template_tokens(&tokens, &mut vec![&context], &mut rendered, &mut Vec::new());
+            let (pdf, replaced) =
+                render_text_pdf(&decorate_receipt(&app, &rendered), font.as_ref());
+            substituted |= replaced;
+            fs::write(destination.join(&file_name), pdf)
+                .map_err(|e| format!("Failed to write PDF: {}", e))
+        });
+
+        match result {
+            Ok(()) => exported += 1,
+            Err(error) => failures.push(ReceiptExportFailure { invoice_id, error }),
+        }
+    }
+
+    log_operation(
+        &app,
+        OperationRecord::new(
+            "export_receipts_pdf",
+            failures.is_empty(),
+            format!(
+                "Exported {} receipt(s) for {} to {} ({} failed)",
+                exported,
+                date,
+                destination.display(),
+                failures.len()
+            ),
+        ),
+    );
+
+    Ok(ReceiptExportResult {
+        exported,
+        failures,
+        destination_dir: destination.to_string_lossy().to_string(),
+        warning: substituted.then(|| {
+            format!(
+                "Some characters had no glyph and were replaced. Set {} to a font that has them.",
+                RECEIPT_PDF_FONT_KEY
+            )
+        }),
+    })
+}
+
+// ============================================
+// RECEIPT IMAGE PREVIEW
+// ============================================
+
+pub(crate) const RECEIPT_GLYPH_WIDTH: u32 = 8;
+pub(crate) const RECEIPT_GLYPH_HEIGHT: u32 = 16;
+pub(crate) const RECEIPT_IMAGE_MAX_WIDTH: usize = 80;
+pub(crate) const RECEIPT_IMAGE_MAX_LINES: usize = 1000;
+
+/// 8x16 bitmaps for ASCII 0x20-0x7E, one byte per row, most significant bit on the
+/// left. Rasterized from DejaVu Sans Mono; other characters draw as an outlined box.
+pub(crate) const RECEIPT_GLYPHS: [&[u8; 16]; 95] = [
+    b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00", // space
+    b"\x00\x00\x00\x10\x10\x10\x10\x10\x10\x00\x10\x10\x00\x00\x00\x00", // !
+    b"\x00\x00\x00\x28\x28\x28\x28\x00\x00\x00\x00\x00\x00\x00\x00\x00", // "
+    b"\x00\x00\x12\x12\x16\x7f\x24\x24\xfe\x28\x48\x48\x00\x00\x00\x00", // #
+    b"\x00\x00\x00\x08\x3e\x49\x48\x38\x0e\x09\x49\x3e\x08\x08\x00\x00", // $
+    b"\x00\x00\x00\x60\x90\x90\x62\x1c\x66\x09\x09\x06\x00\x00\x00\x00", // %
+    b"\x00\x00\x00\x1c\x20\x20\x30\x49\x4d\x45\x62\x3d\x00\x00\x00\x00", // &
+    b"\x00\x00\x00\x10\x10\x10\x10\x00\x00\x00\x00\x00\x00\x00\x00\x00", // '
+    b"\x00\x0c\x08\x08\x10\x10\x10\x10\x10\x10\x08\x08\x04\x00\x00\x00", // (
+    b"\x00\x30\x10\x10\x08\x08\x08\x08\x08\x08\x10\x10\x30\x00\x00\x00", // )
+    b"\x00\x00\x00\x08\x49\x3e\x1c\x6b\x08\x00\x00\x00\x00\x00\x00\x00", // *
+    b"\x00\x00\x00\x00\x10\x10\x10\xfe\x10\x10\x10\x00\x00\x00\x00\x00", // +
+    b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x18\x18\x10\x20\x00\x00", // ,
+    b"\x00\x00\x00\x00\x00\x00\x00\x00\x38\x00\x00\x00\x00\x00\x00\x00", // -
+    b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x18\x18\x00\x00\x00\x00", // .
+    b"\x00\x00\x00\x02\x04\x04\x08\x08\x18\x10\x10\x20\x20\x40\x00\x00", // /
+    b"\x00\x00\x00\x1c\x22\x41\x41\x49\x41\x41\x22\x1c\x00\x00\x00\x00", // 0
+    b"\x00\x00\x00\x38\x08\x08\x08\x08\x08\x08\x08\x3e\x00\x00\x00\x00", // 1
+    b"\x00\x00\x00\x3e\x43\x01\x01\x02\x0c\x18\x20\x7f\x00\x00\x00\x00", // 2
+    b"\x00\x00\x00\x3e\x41\x01\x03\x1c\x03\x01\x43\x3e\x00\x00\x00\x00", // 3
+    b"\x00\x00\x00\x06\x0a\x1a\x12\x22\x42\x7f\x02\x02\x00\x00\x00\x00", // 4
+    b"\x00\x00\x00\x7e\x40\x40\x7c\x03\x01\x01\x43\x3c\x00\x00\x00\x00", // 5
+    b"\x00\x00\x00\x1e\x21\x40\x5e\x63\x41\x41\x23\x1e\x00\x00\x00\x00", // 6
+    b"\x00\x00\x00\x7f\x02\x02\x04\x04\x08\x18\x10\x20\x00\x00\x00\x00", // 7
+    b"\x00\x00\x00\x3e\x41\x41\x41\x3e\x63\x41\x61\x3e\x00\x00\x00\x00", // 8
+    b"\x00\x00\x00\x3c\x62\x41\x41\x63\x3d\x01\x42\x3c\x00\x00\x00\x00", // 9
+    b"\x00\x00\x00\x00\x00\x18\x18\x00\x00\x00\x18\x18\x00\x00\x00\x00", // :
+    b"\x00\x00\x00\x00\x00\x18\x18\x00\x00\x00\x18\x18\x10\x20\x00\x00", // ;
+    b"\x00\x00\x00\x00\x00\x01\x0e\x70\x70\x0e\x01\x00\x00\x00\x00\x00", // <
+    b"\x00\x00\x00\x00\x00\x00\x7f\x00\x00\x7f\x00\x00\x00\x00\x00\x00", // =
+    b"\x00\x00\x00\x00\x00\x40\x38\x07\x07\x38\x40\x00\x00\x00\x00\x00", // >
+    b"\x00\x00\x00\x38\x44\x04\x08\x10\x10\x00\x10\x10\x00\x00\x00\x00", // ?
+    b"\x00\x00\x00\x1e\x33\x21\x47\x49\x49\x49\x47\x20\x30\x1e\x00\x00", // @
+    b"\x00\x00\x00\x08\x14\x14\x14\x22\x22\x3e\x63\x41\x00\x00\x00\x00", // A
+    b"\x00\x00\x00\x7e\x41\x41\x41\x7e\x41\x41\x41\x7e\x00\x00\x00\x00", // B
+    b"\x00\x00\x00\x1e\x21\x40\x40\x40\x40\x40\x21\x1e\x00\x00\x00\x00", // C
+    b"\x00\x00\x00\x7c\x42\x41\x41\x41\x41\x41\x42\x7c\x00\x00\x00\x00", // D
+    b"\x00\x00\x00\x7f\x40\x40\x40\x7f\x40\x40\x40\x7f\x00\x00\x00\x00", // E
+    b"\x00\x00\x00\x7f\x40\x40\x40\x7f\x40\x40\x40\x40\x00\x00\x00\x00", // F
+    b"\x00\x00\x00\x1e\x21\x40\x40\x43\x41\x41\x21\x1e\x00\x00\x00\x00", // G
+    b"\x00\x00\x00\x41\x41\x41\x41\x7f\x41\x41\x41\x41\x00\x00\x00\x00", // H
+    b"\x00\x00\x00\x7c\x10\x10\x10\x10\x10\x10\x10\x7c\x00\x00\x00\x00", // I
+    b"\x00\x00\x00\x1c\x04\x04\x04\x04\x04\x04\x44\x38\x00\x00\x00\x00", // J
+    b"\x00\x00\x00\x42\x44\x48\x50\x70\x48\x44\x44\x42\x00\x00\x00\x00", // K
+    b"\x00\x00\x00\x40\x40\x40\x40\x40\x40\x40\x40\x7f\x00\x00\x00\x00", // L
+    b"\x00\x00\x00\x63\x63\x55\x55\x55\x49\x41\x41\x41\x00\x00\x00\x00", // M
+    b"\x00\x00\x00\x61\x61\x51\x51\x49\x45\x45\x43\x43\x00\x00\x00\x00", // N
+    b"\x00\x00\x00\x1c\x22\x41\x41\x41\x41\x41\x22\x1c\x00\x00\x00\x00", // O
+    b"\x00\x00\x00\x7e\x43\x41\x41\x43\x7e\x40\x40\x40\x00\x00\x00\x00", // P
+    b"\x00\x00\x00\x1c\x22\x41\x41\x41\x41\x41\x23\x1e\x06\x02\x00\x00", // Q
+    b"\x00\x00\x00\x7e\x43\x41\x41\x7e\x42\x41\x41\x40\x00\x00\x00\x00", // R
+    b"\x00\x00\x00\x3e\x61\x40\x60\x3e\x03\x01\x43\x3e\x00\x00\x00\x00", // S
+    b"\x00\x00\x00\xfe\x10\x10\x10\x10\x10\x10\x10\x10\x00\x00\x00\x00", // T
+    b"\x00\x00\x00\x41\x41\x41\x41\x41\x41\x41\x41\x3e\x00\x00\x00\x00", // U
+    b"\x00\x00\x00\x41\x63\x22\x22\x22\x14\x14\x14\x08\x00\x00\x00\x00", // V
+    b"\x00\x00\x00\x81\x81\x81\x5a\x5a\x5a\x66\x66\x66\x00\x00\x00\x00", // W
+    b"\x00\x00\x00\x63\x22\x14\x1c\x08\x14\x36\x22\x41\x00\x00\x00\x00", // X
+    b"\x00\x00\x00\x82\x44\x28\x28\x10\x10\x10\x10\x10\x00\x00\x00\x00", // Y
+    b"\x00\x00\x00\x7f\x03\x06\x04\x08\x10\x30\x60\x7f\x00\x00\x00\x00", // Z
+    b"\x00\x1c\x10\x10\x10\x10\x10\x10\x10\x10\x10\x10\x1c\x00\x00\x00", // [
+    b"\x00\x00\x00\x40\x20\x20\x10\x10\x18\x08\x08\x04\x04\x02\x00\x00", // \
+    b"\x00\x38\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x38\x00\x00\x00", // ]
+    b"\x00\x00\x00\x10\x28\x44\xc6\x00\x00\x00\x00\x00\x00\x00\x00\x00", // ^
+    b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\xff\x00", // _
+    b"\x00\x00\x10\x08\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00", // `
+    b"\x00\x00\x00\x00\x00\x1c\x22\x02\x3e\x42\x46\x3a\x00\x00\x00\x00", // a
+    b"\x00\x40\x40\x40\x40\x7c\x66\x42\x42\x42\x66\x7c\x00\x00\x00\x00", // b
+    b"\x00\x00\x00\x00\x00\x1c\x22\x40\x40\x40\x22\x1c\x00\x00\x00\x00", // c
+    b"\x00\x02\x02\x02\x02\x3e\x66\x42\x42\x42\x66\x3e\x00\x00\x00\x00", // d
+    b"\x00\x00\x00\x00\x00\x3c\x66\x42\x7e\x40\x62\x3c\x00\x00\x00\x00", // e
+    b"\x00\x0c\x10\x10\x10\x7c\x10\x10\x10\x10\x10\x10\x00\x00\x00\x00", // f
+    b"\x00\x00\x00\x00\x00\x3e\x66\x42\x42\x42\x66\x3a\x02\x22\x1c\x00", // g
+    b"\x00\x40\x40\x40\x40\x5c\x62\x42\x42\x42\x42\x42\x00\x00\x00\x00", // h
+    b"\x00\x10\x00\x00\x00\x70\x10\x10\x10\x10\x10\x7c\x00\x00\x00\x00", // i
+    b"\x00\x08\x00\x00\x00\x38\x08\x08\x08\x08\x08\x08\x08\x08\x70\x00", // j
+    b"\x00\x40\x40\x40\x40\x44\x48\x50\x70\x48\x44\x42\x00\x00\x00\x00", // k
+    b"\x00\x70\x10\x10\x10\x10\x10\x10\x10\x10\x10\x0e\x00\x00\x00\x00", // l
+    b"\x00\x00\x00\x00\x00\x7f\x49\x49\x49\x49\x49\x49\x00\x00\x00\x00", // m
+    b"\x00\x00\x00\x00\x00\x5c\x62\x42\x42\x42\x42\x42\x00\x00\x00\x00", // n
+    b"\x00\x00\x00\x00\x00\x3c\x66\x42\x42\x42\x66\x3c\x00\x00\x00\x00", // o
+    b"\x00\x00\x00\x00\x00\x7c\x66\x42\x42\x42\x66\x7c\x40\x40\x40\x00", // p
+    b"\x00\x00\x00\x00\x00\x3e\x66\x42\x42\x42\x66\x3a\x02\x02\x02\x00", // q
+    b"\x00\x00\x00\x00\x00\x3c\x32\x20\x20\x20\x20\x20\x00\x00\x00\x00", // r
+    b"\x00\x00\x00\x00\x00\x3c\x42\x40\x3c\x02\x42\x3c\x00\x00\x00\x00", // s
+    b"\x00\x00\x00\x10\x10\x7e\x10\x10\x10\x10\x10\x0e\x00\x00\x00\x00", // t
+    b"\x00\x00\x00\x00\x00\x42\x42\x42\x42\x42\x46\x3a\x00\x00\x00\x00", // u
+    b"\x00\x00\x00\x00\x00\x42\x66\x24\x24\x3c\x18\x18\x00\x00\x00\x00", // v
+    b"\x00\x00\x00\x00\x00\x81\x81\x5a\x5a\x5a\x24\x24\x00\x00\x00\x00", // w
+    b"\x00\x00\x00\x00\x00\x66\x24\x18\x18\x18\x24\x66\x00\x00\x00\x00", // x
+    b"\x00\x00\x00\x00\x00\x42\x22\x24\x24\x14\x18\x08\x08\x10\x30\x00", // y
+    b"\x00\x00\x00\x00\x00\x7e\x02\x04\x18\x20\x40\x7e\x00\x00\x00\x00", // z
+    b"\x00\x1c\x10\x10\x10\x10\x60\x10\x10\x10\x10\x10\x0c\x00\x00\x00", // {
+    b"\x00\x10\x10\x10\x10\x10\x10\x10\x10\x10\x10\x10\x10\x10\x00\x00", // |
+    b"\x00\x70\x10\x10\x10\x10\x0c\x10\x10\x10\x10\x10\x60\x00\x00\x00", // }
+    b"\x00\x00\x00\x00\x00\x00\x00\x39\x46\x00\x00\x00\x00\x00\x00\x00", // ~
+];
+pub(crate) const RECEIPT_MISSING_GLYPH: &[u8; 16] =
+    b"\x00\x00\x7e\x42\x42\x42\x42\x42\x42\x42\x42\x42\x7e\x00\x00\x00";
+
+/// The characters as a printer with this codepage shows them: encodes, then
+/// decodes again, so unmappable characters come back as the substitute
+pub(crate) fn receipt_text_as_printed(
+    text: &str,
+    encoding: &str,
+    substitute: &str,
+) -> Result<String, String> {
+    let bytes = encode_receipt_for_codepage(text, encoding, substitute)?;
+    // Skip the ESC t n prefix
+    let body = &bytes[3..];
+    match resolve_receipt_codepage(encoding)?.1 {
+        None => Ok(body
+            .iter()
+            .map(|b| match b {
+                0x00..=0x7f => *b as char,
+                _ => CP437_HIGH.chars().nth((b - 0x80) as usize).unwrap_or('?'),
+            })
+            .collect()),
+        Some(codec) => Ok(codec.decode_without_bom_handling(body).0.into_owned()),
+    }
+}
+
+/// Rasterizes a receipt to PNG bytes on the printer's character grid: `width`
+/// columns of 8x16 pixel cells, one row per printed line. The header, footer and
+/// codepage are applied as print_receipt would, so the image shows what prints.
+#[tauri::command]
+pub(crate) fn render_receipt_image(
+    app: AppHandle,
+    text: String,
+    width: usize,
+    options: Option<PrintOptions>,
+) -> Result<Vec<u8>, String> {
+    if !(1..=RECEIPT_IMAGE_MAX_WIDTH).contains(&width) {
+        return Err(format!(
+            "Receipt width must be between 1 and {} characters",
+            RECEIPT_IMAGE_MAX_WIDTH
+        ));
+    }
+    let options = options.unwrap_or_default();
+    let text = if options.raw {
+        text
+    } else {
+        decorate_receipt(&app, &text)
+    };
+    let text = match receipt_codepage(&app, options.encoding, options.substitute) {
+        Some((encoding, substitute)) => receipt_text_as_printed(&text, &encoding, &substitute)?,
+        None => text,
+    };
+    // Printers wrap anything wider than the paper onto the next line
+    let wrapped = wrap_receipt_text(text.trim_end(), width);
+    let lines: Vec<&str> = wrapped.lines().collect();
+    if lines.len() > RECEIPT_IMAGE_MAX_LINES {
+        return Err(format!(
+            "Receipt is {} lines, over the {} line preview limit",
+            lines.len(),
+            RECEIPT_IMAGE_MAX_LINES
+        ));
+    }
+
+    let mut image = image::GrayImage::from_pixel(
+        width as u32 * RECEIPT_GLYPH_WIDTH,
+        lines.len().max(1) as u32 * RECEIPT_GLYPH_HEIGHT,
+        image::Luma([255]),
+    );
+    for (row, line) in lines.iter().enumerate() {
+        for (column, c) in line.chars().take(width).enumerate() {
+            let glyph = match c {
+                ' '..='~' => RECEIPT_GLYPHS[c as usize - 0x20],
+                // Non-breaking spaces and other blanks print as nothing
+                c if c.is_whitespace() => continue,
+                _ => RECEIPT_MISSING_GLYPH,
+            };
+            let origin_x = column as u32 * RECEIPT_GLYPH_WIDTH;
+            let origin_y = row as u32 * RECEIPT_GLYPH_HEIGHT;
+            for (y, bits) in glyph.iter().enumerate() {
+                for x in 0..RECEIPT_GLYPH_WIDTH {
+                    if bits & (0x80 >> x) != 0 {
+                        image.put_pixel(origin_x + x, origin_y + y as u32, image::Luma([0]));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode receipt image: {}", e))?;
+    Ok(png)
+}
+
+// ============================================
+// QR CODES
+// ============================================
+
+pub(crate) fn validate_qr_data(data: &str) -> Result<(), String> {
+    if data.is_empty() {
+        return Err("QR code data cannot be empty".to_string());
+    }
+    if data.len() > QR_MAX_DATA_LEN {
+        return Err(format!(
+            "QR code data is too long ({} bytes, max {})",
+            data.len(),
+            QR_MAX_DATA_LEN
+        ));
+    }
+    Ok(())
+}
+
+/// Generates a QR code as PNG bytes, at least `size` x `size` pixels
+#[tauri::command]
+pub(crate) fn generate_qr(data: String, size: u32) -> Result<Vec<u8>, String> {
+    validate_qr_data(&data)?;
+    if !(QR_MIN_SIZE..=QR_MAX_SIZE).contains(&size) {
+        return Err(format!(
+            "QR code size must be between {} and {} pixels",
+            QR_MIN_SIZE, QR_MAX_SIZE
+        ));
+    }
+
+    let code = qrcode::QrCode::new(data.as_bytes())
+        .map_err(|e| format!("Failed to encode QR code: {}", e))?;
+    let image = code
+        .render::<image::Luma<u8>>()
+        .min_dimensions(size, size)
+        .build();
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode QR image: {}", e))?;
+
+    Ok(png)
+}
+
+/// Builds the ESC/POS command sequence (GS ( k) that makes a thermal printer
+/// render `data` as a QR code. `module_size` is the dot size of each module (1-16).
+#[tauri::command]
+pub(crate) fn generate_qr_escpos(data: String, module_size: Option<u8>) -> Result<Vec<u8>, String> {
+    validate_qr_data(&data)?;
+
+    let module_size = module_size.unwrap_or(6);
+    if module_size == 0 || module_size > QR_MAX_MODULE_SIZE {
+        return Err(format!(
+            "QR module size must be between 1 and {}",
+            QR_MAX_MODULE_SIZE
+        ));
+    }
+
+    let mut bytes = Vec::with_capacity(data.len() + 32);
+    // Select model 2
+    bytes.extend_from_slice(&[0x1d, 0x28, 0x6b, 0x04, 0x00, 0x31, 0x41, 0x32, 0x00]);
+    // Module size
+    bytes.extend_from_slice(&[0x1d, 0x28, 0x6b, 0x03, 0x00, 0x31, 0x43, module_size]);
+    // Error correction level M
+    bytes.extend_from_slice(&[0x1d, 0x28, 0x6b, 0x03, 0x00, 0x31, 0x45, 0x31]);
+    // Store data in the symbol storage area
+    let store_len = (data.len() + 3) as u16;
+    bytes.extend_from_slice(&[0x1d, 0x28, 0x6b]);
+    bytes.extend_from_slice(&store_len.to_le_bytes());
+    bytes.extend_from_slice(&[0x31, 0x50, 0x30]);
+    bytes.extend_from_slice(data.as_bytes());
+    // Print the stored symbol
+    bytes.extend_from_slice(&[0x1d, 0x28, 0x6b, 0x03, 0x00, 0x31, 0x51, 0x30]);
+
+    Ok(bytes)
+}
+
+// ============================================
+// BARCODE LABELS
+// ============================================
+
+/// Encodes `value` as bar modules (1 = bar, 0 = space) after checking it against
+/// the symbology's rules. Code128 uses character set B (printable ASCII);
+/// EAN-13 takes 12 digits, or 13 with a valid check digit.
+pub(crate) fn encode_barcode(value: &str, symbology: &str) -> Result<Vec<u8>, String> {
+    match symbology.trim().to_ascii_lowercase().as_str() {
+        "code128" => {
+            if value.is_empty() || value.len() > CODE128_MAX_LEN {
+                return Err(format!(
+                    "Code128 value must be 1 to {} characters",
+                    CODE128_MAX_LEN
+                ));
+            }
+            if let Some(bad) = value.chars().find(|c| !(' '..='~').contains(c)) {
+                return Err(format!(
+                    "Code128 supports printable ASCII only; found {:?}",
+                    bad
+                ));
+            }
+            // 'Ɓ' selects character set B at the start of the symbol
+            barcoders::sym::code128::Code128::new(format!("Ɓ{}", value))
+                .map(|code| code.encode())
+                .map_err(|e| format!("Invalid Code128 value: {}", e))
+        }
+        "ean13" => {
+            if !value.chars().all(|c| c.is_ascii_digit()) || !(12..=13).contains(&value.len()) {
+                return Err(
+                    "EAN-13 value must be 12 digits, or 13 with the check digit".to_string()
+                );
+            }
+            barcoders::sym::ean13::EAN13::new(value)
+                .map(|code| code.encode())
+                .map_err(|_| format!("Invalid EAN-13 value: {} (check digit mismatch?)", value))
+        }
+        other => Err(format!(
+            "Unsupported symbology: {}. Use code128 or ean13",
+            other
+        )),
+    }
+}
+
+/// Builds an ESC/POS block that prints the modules as a centred raster image
+/// (GS v 0) with `label` underneath, then feeds and cuts
+pub(crate) fn barcode_escpos(modules: &[u8], label: &str) -> Result<Vec<u8>, String> {
+    let total_modules = modules.len() + 2 * BARCODE_QUIET_ZONE_MODULES;
+    let module_dots = (BARCODE_MAX_WIDTH_DOTS / total_modules).min(3);
+    if module_dots == 0 {
+        return Err("Barcode is too long to fit on the label".to_string());
+    }
+
+    let width_dots = total_modules * module_dots;
+    let width_bytes = width_dots.div_ceil(8);
+    let mut row = vec![0u8; width_bytes];
+    for (i, module) in modules.iter().enumerate() {
+        if *module == 0 {
+            continue;
+        }
+        let start = (BARCODE_QUIET_ZONE_MODULES + i) * module_dots;
+        for dot in start..start + module_dots {
+            row[dot / 8] |= 0x80 >> (dot % 8);
+        }
+    }
+
+    let mut bytes = Vec::with_capacity(width_bytes * BARCODE_HEIGHT_DOTS as usize + 64);
+    // Initialize, centre
+    bytes.extend_from_slice(&[0x1b, 0x40, 0x1b, 0x61, 0x01]);
+    bytes.extend_from_slice(&[0x1d, 0x76, 0x30, 0x00]);
+    bytes.extend_from_slice(&(width_bytes as u16).to_le_bytes());
+    bytes.extend_from_slice(&BARCODE_HEIGHT_DOTS.to_le_bytes());
+    for _ in 0..BARCODE_HEIGHT_DOTS {
+        bytes.extend_from_slice(&row);
+    }
+    bytes.push(b'\n');
+    bytes.extend_from_slice(label.as_bytes());
+    // Feed a few lines, then partial cut
+    bytes.extend_from_slice(b"\n\n\n\n");
+    bytes.extend_from_slice(&[0x1d, 0x56, 0x01]);
+    Ok(bytes)
+}
+
+/// Renders a Code128/EAN-13 barcode label and prints it. `printer_name` may be a
+/// CUPS printer (default printer when None) or "host:port" for a network label
+/// printer. Returns the spooler job id when the platform reports one.
+#[tauri::command]
+pub(crate) async fn print_barcode(
+    app: AppHandle,
+    value: String,
+    symbology: String,
+    printer_name: Option<String>,
+) -> Result<Option<String>, String> {
+    let modules = encode_barcode(value.trim(), &symbology)?;
+    let data = barcode_escpos(&modules, value.trim())?;
+    let timeout = get_print_timeout(&app);
+    let printer_name = printer_name.filter(|name| !name.trim().is_empty());
+
+    tauri::async_runtime::spawn_blocking(move || {
+        send_to_named_printer(&app, printer_name, &data, timeout)
+    })
+    .await
+    .map_err(|e| format!("Print task failed: {}", e))?
+}
+
+/// Sends raw bytes to "host:port" over the network, else to the named local
+/// printer (default printer when None)
+pub(crate) fn send_to_named_printer(
+    app: &AppHandle,
+    printer_name: Option<String>,
+    data: &[u8],
+    timeout: Duration,
+) -> Result<Option<String>, String> {
+    if let Some((host, port)) = printer_name.as_deref().and_then(network_printer_address) {
+        return send_to_network_printer(&host, port, data, timeout).map(|_| None);
+    }
+    send_raw_to_printer(app, printer_name, data, timeout)
+}
+
+/// Host and port when a printer name is a "host:port" network address
+pub(crate) fn network_printer_address(name: &str) -> Option<(String, u16)> {
+    let (host, port) = name.rsplit_once(':')?;
+    Some((host.to_string(), port.parse().ok()?))
+}
+
+/// Sends printer-ready bytes to a local printer without any filtering
+pub(crate) fn send_raw_to_printer(
+    app: &AppHandle,
+    printer_name: Option<String>,
+    data: &[u8],
+    timeout: Duration,
+) -> Result<Option<String>, String> {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        let printer = match printer_name {
+            Some(name) => name,
+            None => cached_default_printer(app, timeout)?.0,
+        };
+
+        let tmp_path = unique_temp_path("raw_print", "bin");
+        write_private_temp_file(&tmp_path, data)
+            .map_err(|e| format!("Failed to write print file: {e}"))?;
+        let lp = run_command_with_timeout(
+            Command::new("lp")
+                .args(["-d", &printer, "-o", "raw"])
+                .arg(tmp_path.to_string_lossy().to_string()),
+            timeout,
+        );
+        let _ = fs::remove_file(&tmp_path);
+        let lp = lp.map_err(|e| match e.kind() {
+            std::io::ErrorKind::TimedOut => {
+                "Print command timed out (lp did not respond)".to_string()
+            }
+            _ => format!("Printing not available (lp not found): {e}"),
+        })?;
+
+        if !lp.status.success() {
+            let stderr = String::from_utf8_lossy(&lp.stderr);
+            return Err(format!("Print failed: {stderr}"));
+        }
+        Ok(parse_lp_job_id(&String::from_utf8_lossy(&lp.stdout)))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let _ = (app, printer_name, data, timeout);
+        Err(
+            "Raw printer data (barcode labels, codepage receipts) can only be sent to a network printer on this platform."
+                .to_string(),
+        )
+    }
+}
+
+// ============================================
+// SILENT PDF PRINTING (Windows only, using SumatraPDF)
+// ============================================
+
+#[tauri::command]
+pub(crate) fn print_pdf_silent(
+    app: AppHandle,
+    pdf_path: String,
+    printer_name: Option<String>,
+) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        // Look for SumatraPDF.exe in the resources folder
+        let resource_dir = app.path().resource_dir().map_err(|e| e.to_string())?;
+
+        // Try multiple possible locations for SumatraPDF.exe
+        let possible_paths = [
+            resource_dir.join("SumatraPDF.exe"),
+            resource_dir.join("resources").join("SumatraPDF.exe"),
+        ];
+
+        let sumatra_exe = possible_paths.iter().find(|p| p.exists()).ok_or_else(|| {
+            "SumatraPDF.exe not found. Please place SumatraPDF.exe in src-tauri/resources/"
+                .to_string()
+        })?;
+
+        // Verify PDF file exists
+        let pdf_path = normalize_user_path(&app, &pdf_path)?;
+        let pdf_file = pdf_path.as_path();
+        if !pdf_file.exists() {
+            return Err(format!("PDF file not found: {}", pdf_path.display()));
+        }
+
+        // Build SumatraPDF command
+        let mut cmd = Command::new(sumatra_exe);
+
+        if let Some(printer) = printer_name {
+            cmd.args(["-print-to", &printer]);
+        } else {
+            cmd.arg("-print-to-default");
+        }
+
+        cmd.arg("-silent");
+        cmd.arg(&pdf_path);
+
+        let output = cmd
+            .output()
+            .map_err(|e| format!("Failed to execute SumatraPDF: {}", e))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.is_empty() {
+                // SumatraPDF often doesn't output errors, check if print started
+                return Ok(());
+            }
+            return Err(format!("Print failed: {}", stderr));
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (app, pdf_path, printer_name);
+        Err("Silent PDF printing is only available on Windows.".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn lpstat_output_is_parsed_into_printers() {
+        let stdout = "printer Receipt is idle.  enabled since Mon 01 Jan 2024 09:00:00\n\
+                      \tAlerts: offline-report\n\
+                      printer Office now printing Office-12.  enabled since Mon 01 Jan 2024\n\
+                      printer Old_Laser disabled since Tue 02 Jan 2024 -\n\
+                      \treason unknown\n\
+                      system default destination: Office\n";
+
+        let printers: Vec<(String, bool, String)> = parse_lpstat_printers(stdout)
+            .into_iter()
+            .map(|p| (p.name, p.is_default, p.state))
+            .collect();
+
+        assert_eq!(
+            printers,
+            vec![
+                ("Receipt".to_string(), false, "offline".to_string()),
+                ("Office".to_string(), true, "printing".to_string()),
+                ("Old_Laser".to_string(), false, "disabled".to_string()),
+            ]
+        );
+        assert!(parse_lpstat_printers("lpstat: No destinations added.\n").is_empty());
+    }
+
+    /// A five-glyph TrueType font: .notdef, simple glyphs 1, 2 and 4, and
+    /// glyph 3, a composite of glyph 4. 'A' and 'B' map to 1 and 2, ₹ to 3.
+    fn test_font() -> Vec<u8> {
+        let be16 = |v: u16| v.to_be_bytes().to_vec();
+        let simple = || {
+            let mut outline = [be16(1), be16(0), be16(0), be16(100), be16(100)].concat();
+            // endPtsOfContours, no instructions, one on-curve point
+            outline.extend([be16(0), be16(0), vec![0x01], be16(50), be16(50)].concat());
+            outline.push(0);
+            outline
+        };
+        let composite = [
+            be16(0xFFFF),
+            be16(0),
+            be16(0),
+            be16(100),
+            be16(100),
+            be16(0x0001),
+            be16(4),
+            be16(10),
+            be16(10),
+        ]
+        .concat();
+        let outlines = [Vec::new(), simple(), simple(), composite, simple()];
+
+        let mut glyf = Vec::new();
+        let mut loca = Vec::new();
+        for outline in &outlines {
+            loca.extend(be16((glyf.len() / 2) as u16));
+            glyf.extend(outline);
+        }
+        loca.extend(be16((glyf.len() / 2) as u16));
+
+        let mut head = vec![0u8; 54];
+        head[18..20].copy_from_slice(&2000u16.to_be_bytes());
+        head[40..42].copy_from_slice(&1000u16.to_be_bytes());
+        head[42..44].copy_from_slice(&1800u16.to_be_bytes());
+        let mut hhea = vec![0u8; 36];
+        hhea[4..6].copy_from_slice(&1800i16.to_be_bytes());
+        hhea[6..8].copy_from_slice(&(-400i16).to_be_bytes());
+        hhea[34..36].copy_from_slice(&3u16.to_be_bytes());
+        let hmtx = [be16(500), be16(0), be16(600), be16(0), be16(1200), be16(0)].concat();
+        let maxp = [0x0000_5000u32.to_be_bytes().to_vec(), be16(5)].concat();
+        // Format 4: 'A'..'B' -> 1..2, ₹ -> 3, then the closing 0xFFFF segment
+        let cmap = [
+            be16(0),
+            be16(1),
+            be16(3),
+            be16(1),
+            12u32.to_be_bytes().to_vec(),
+            be16(4),
+            be16(40),
+            be16(0),
+            be16(6),
+            be16(0),
+            be16(0),
+            be16(0),
+            [be16(0x42), be16(0x20B9), be16(0xFFFF)].concat(),
+            be16(0),
+            [be16(0x41), be16(0x20B9), be16(0xFFFF)].concat(),
+            [
+                be16(1u16.wrapping_sub(0x41)),
+                be16(3u16.wrapping_sub(0x20B9)),
+                be16(1),
+            ]
+            .concat(),
+            [be16(0), be16(0), be16(0)].concat(),
+        ]
+        .concat();
+
+        let tables: [(&[u8; 4], Vec<u8>); 7] = [
+            (b"cmap", cmap),
+            (b"glyf", glyf),
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"hmtx", hmtx),
+            (b"loca", loca),
+            (b"maxp", maxp),
+        ];
+        let mut font = [0x0001_0000u32.to_be_bytes().to_vec(), be16(7), vec![0; 6]].concat();
+        let mut offset = 12 + tables.len() * 16;
+        for (tag, data) in &tables {
+            font.extend_from_slice(*tag);
+            font.extend([0u8; 4]);
+            font.extend((offset as u32).to_be_bytes());
+            font.extend((data.len() as u32).to_be_bytes());
+            offset += data.len().div_ceil(4) * 4;
+        }
+        for (_, data) in &tables {
+            font.extend(data);
+            font.resize(font.len().div_ceil(4) * 4, 0);
+        }
+        font
+    }
+
+    #[test]
+    fn pdf_font_reads_glyph_ids_and_widths() {
+        let font = PdfFont::parse(&test_font()).unwrap();
+
+        assert_eq!(font.glyph('A'), Some(1));
+        assert_eq!(font.glyph('B'), Some(2));
+        assert_eq!(font.glyph('₹'), Some(3));
+        assert_eq!(font.glyph('C'), None);
+        assert_eq!((font.width(0), font.width(1)), (250, 300));
+        // Glyphs past numberOfHMetrics share the last advance
+        assert_eq!((font.width(3), font.width(4)), (600, 600));
+        assert_eq!((font.ascent, font.descent), (1800, -400));
+        assert_eq!(font.bbox, [0, 0, 1000, 1800]);
+    }
+
+    #[test]
+    fn pdf_font_rejects_cff_and_truncated_fonts() {
+        let mut cff = test_font();
+        cff[..4].copy_from_slice(b"OTTO");
+        assert!(PdfFont::parse(&cff).is_none());
+
+        let font = test_font();
+        for len in [0, 11, 100, font.len() - 8] {
+            assert!(PdfFont::parse(&font[..len]).is_none(), "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn pdf_font_subset_keeps_used_glyphs_and_their_components() {
+        let font = PdfFont::parse(&test_font()).unwrap();
+
+        let subset = font.subset(&std::collections::BTreeSet::from([3])).unwrap();
+
+        let tables = sfnt_tables(&subset).unwrap();
+        let table = |tag: &[u8; 4]| tables.iter().find(|(t, _)| t == tag).map(|(_, d)| *d);
+        let (head, loca) = (table(b"head").unwrap(), table(b"loca").unwrap());
+        assert_eq!(be_u16(head, 50), Some(1));
+        let lengths: Vec<u32> = (0..5)
+            .map(|g| be_u32(loca, g * 4 + 4).unwrap() - be_u32(loca, g * 4).unwrap())
+            .collect();
+        // Glyph 3 and its component glyph 4 keep their (padded) outlines
+        assert_eq!(lengths, vec![0, 0, 0, 20, 20]);
+        assert!(table(b"cmap").is_none());
+        let original = sfnt_tables(&font.data).unwrap();
+        let hmtx = original.iter().find(|(t, _)| t == b"hmtx").map(|(_, d)| *d);
+        assert_eq!(table(b"hmtx"), hmtx);
+    }
+
+    #[test]
+    fn pdf_line_runs_use_the_embedded_font_only_outside_winansi() {
+        let font = PdfFont::parse(&test_font()).unwrap();
+        let describe = |runs: Vec<PdfRun>| -> Vec<String> {
+            runs.into_iter()
+                .map(|run| match run {
+                    PdfRun::Courier(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+                    PdfRun::Glyphs(glyphs) => format!("{:?}", glyphs),
+                })
+                .collect()
+        };
+
+        let mut substituted = false;
+        let runs = pdf_line_runs("Total ₹5 AB", Some(&font), &mut substituted);
+        assert_eq!(describe(runs), vec!["Total ", "[3]", "5 AB"]);
+        assert!(!substituted);
+
+        let runs = pdf_line_runs("₹5 க", None, &mut substituted);
+        assert_eq!(describe(runs), vec!["Rs.5 ?"]);
+        assert!(substituted);
+    }
+
+    #[test]
+    fn text_pdf_embeds_the_font_only_when_its_glyphs_are_used() {
+        let font = PdfFont::parse(&test_font()).unwrap();
+        let contains =
+            |pdf: &[u8], needle: &str| pdf.windows(needle.len()).any(|w| w == needle.as_bytes());
+
+        let (pdf, substituted) = render_text_pdf("TOTAL ₹5\n", Some(&font));
+        assert!(!substituted);
+        assert!(contains(&pdf, "/FontFile2 7 0 R") && contains(&pdf, "<0003> <20B9>"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+
+        let (pdf, _) = render_text_pdf("TOTAL Rs.5\n", Some(&font));
+        assert!(!contains(&pdf, "/FontFile2"));
+    }
+
+    #[test]
+    fn receipt_file_names_stay_unique_within_a_batch() {
+        let mut used = std::collections::HashSet::new();
+
+        let names: Vec<String> = ["INV/1", "INV_1", "INV 1", "INV-2"]
+            .iter()
+            .map(|id| unique_receipt_file_name(id, &mut used))
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                "receipt_INV_1.pdf",
+                "receipt_INV_1_2.pdf",
+                "receipt_INV_1_3.pdf",
+                "receipt_INV-2.pdf",
+            ]
+        );
+    }
+
+    #[test]
+    fn default_receipt_template_renders_an_invoice() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE invoices (id TEXT, customer_name TEXT, customer_phone TEXT,
+                 discount_amount REAL, total_amount REAL, payment_mode TEXT, is_return INTEGER,
+                 created_at TEXT);
+             CREATE TABLE products (id TEXT, name TEXT);
+             CREATE TABLE invoice_items (id TEXT, invoice_id TEXT, product_id TEXT,
+                 quantity INTEGER, price REAL, created_at TEXT);
+             INSERT INTO invoices VALUES
+                 ('INV-1', 'Ravi', '', 10, 190, 'upi', 0, '2026-10-14 10:00:00');
+             INSERT INTO products VALUES ('p1', 'Brake pad');
+             INSERT INTO invoice_items VALUES
+                 ('i1', 'INV-1', 'p1', 2, 75, ''), ('i2', 'INV-1', 'p9', 1, 50, '');",
+        )
+        .unwrap();
+
+        let context = invoice_receipt_context(&conn, "INV-1").unwrap();
+        let mut rendered = String::new();
+        let mut warnings = Vec::new();
+        render_template_tokens(
+            &parse_template(DEFAULT_RECEIPT_PDF_TEMPLATE).unwrap(),
+            &mut vec![&context],
+            &mut rendered,
+            &mut warnings,
+        );
+
+        let divider = "-".repeat(RECEIPT_WIDTH);
+        let expected = [
+            "RECEIPT",
+            "Receipt #: INV-1",
+            "Date: 2026-10-14 10:00:00",
+            "Customer: Ravi",
+            &divider,
+            "Brake pad",
+            "  2 x 75.00 = 150.00",
+            "p9",
+            "  1 x 50.00 = 50.00",
+            &divider,
+            "Subtotal: 200.00",
+            "Discount: -10.00",
+            "TOTAL: 190.00",
+            "Payment: UPI",
+            &divider,
+        ]
+        .join("\n");
+        assert_eq!(rendered, expected);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert!(invoice_receipt_context(&conn, "missing").is_err());
+    }
+}
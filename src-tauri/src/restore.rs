@@ -0,0 +1,1979 @@
+use super::*;
+
+// ============================================
+// RESTORE TYPES
+// ============================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UndoRestoreResult {
+    /// The pre_restore_safety_ backup that is now live again
+    pub reverted_to: String,
+    /// The backup the undone restore had applied
+    pub undone_backup: Option<String>,
+    /// When the undone restore ran (RFC 3339)
+    pub undone_at: String,
+    pub message: String,
+}
+
+/// Identifies a backup on any storage backend
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupLocation {
+    /// "local" for the backups folders, or "url" when `filename` is an http(s) URL
+    pub source: String,
+    pub filename: String,
+    /// For "url" sources: checked against the download before it is restored
+    #[serde(default)]
+    pub expected_sha256: Option<String>,
+}
+
+/// user_version and application_id of a backup next to the running database's
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupVersionCheck {
+    pub backup_user_version: i64,
+    pub backup_application_id: i64,
+    pub expected_user_version: i64,
+    pub expected_application_id: i64,
+    /// The backup's schema is ahead of this app's; importing needs allow_newer
+    pub newer: bool,
+    /// application_id is set and differs from ours; importing needs force
+    pub foreign: bool,
+}
+
+/// Response from import_backup
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub message: String,
+    pub versions: BackupVersionCheck,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InteractiveImportResult {
+    /// True when the user dismissed the file dialog; nothing was imported
+    pub cancelled: bool,
+    pub source_path: Option<String>,
+    pub message: Option<String>,
+    /// Set when the picked file was part of a folder from export_portable_backup
+    pub portable: Option<PortableBackupInfo>,
+}
+
+pub(crate) struct TrialRestore {
+    pub(crate) backup_filename: String,
+    /// Database name to return to (and overwrite on commit)
+    pub(crate) original_name: String,
+}
+
+/// Payload of the "trial-restore" event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrialRestoreEvent {
+    /// "started", "committed" or "aborted"
+    pub status: String,
+    /// True while the app is running against the trial copy
+    pub active: bool,
+    pub backup_filename: String,
+    /// The database now in use; reconnect to this
+    pub database: DatabaseSwitched,
+    pub safety_backup: Option<String>,
+}
+
+/// Provenance stamped into the live database's app_meta table by restore_database
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoreInfo {
+    pub restored_from: String,
+    pub restored_at: String,
+}
+
+/// What a fresh connection reads from the live database after a restore
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoreProbe {
+    pub restored_from: Option<String>,
+    pub restored_at: Option<String>,
+    /// Exact row counts of the key tables as read now
+    pub table_counts: Vec<TableRowCount>,
+    /// The backup the caller expected to be live, if one was given
+    pub expected_backup: Option<String>,
+    /// False when the stamp or the row counts don't match the expected backup
+    pub matches_expected: bool,
+    pub warning: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableImportCount {
+    pub table: String,
+    pub inserted: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CsvBundleImportResult {
+    pub tables: Vec<TableImportCount>,
+    /// Archive entries ignored because `lenient` was set
+    pub skipped: Vec<String>,
+    pub safety_backup: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoreResult {
+    pub success: bool,
+    pub message: String,
+    pub records_imported: usize,
+    pub safety_backup: String,
+}
+
+// Source tag for a backup downloaded from an http(s) URL by restore_from_url
+pub(crate) const URL_BACKUP_SOURCE: &str = "url";
+
+// ============================================
+// RESTORE COMMANDS
+// ============================================
+
+/// Restores a backup from list_all_backups ("local"), or downloads one from a URL
+/// ("url") through restore_from_url, which checks the optional SHA-256 and that
+/// the file is restorable before the live database is touched
+#[tauri::command]
+pub(crate) async fn restore_from_any(
+    app: AppHandle,
+    location: BackupLocation,
+) -> Result<String, String> {
+    match location.source.as_str() {
+        LOCAL_BACKUP_SOURCE => {
+            tauri::async_runtime::spawn_blocking(move || restore_database(app, location.filename))
+                .await
+                .map_err(|e| format!("Restore task failed: {}", e))?
+        }
+        URL_BACKUP_SOURCE => {
+            restore_from_url(app, location.filename, location.expected_sha256).await
+        }
+        other => Err(format!(
+            "Unknown backup source '{}'. Use \"{}\" or \"{}\"",
+            other, LOCAL_BACKUP_SOURCE, URL_BACKUP_SOURCE
+        )),
+    }
+}
+
+/// Restores the database from a backup file
+#[tauri::command]
+pub(crate) fn restore_database(app: AppHandle, backup_filename: String) -> Result<String, String> {
+    let result = run_restore_database(&app, &backup_filename);
+    let backup_path = resolve_any_backup_file(&app, &backup_filename).ok();
+    let payload = match &result {
+        Ok(message) => WebhookPayload {
+            message: Some(message.clone()),
+            ..WebhookPayload::new("restore.completed", true)
+        },
+        Err(e) => WebhookPayload {
+            message: Some(e.clone()),
+            ..WebhookPayload::new("restore.failed", false)
+        },
+    };
+    fire_webhook(
+        &app,
+        WebhookPayload {
+            size: backup_path
+                .as_ref()
+                .and_then(|p| fs::metadata(p).ok())
+                .map(|m| m.len()),
+            checksum: backup_path.as_deref().and_then(read_checksum_sidecar),
+            filename: Some(backup_filename),
+            ..payload
+        },
+    );
+    result
+}
+
+/// Reads the restore stamp and key-table row counts through a new connection and,
+/// given the backup that should now be live, checks that they match it
+pub(crate) fn probe_restored_state(
+    app: &AppHandle,
+    expected_backup: Option<&str>,
+) -> Result<RestoreProbe, String> {
+    let info = get_last_restore_info(app.clone())?;
+    let db_path = get_db_path(app)?;
+    let live = {
+        let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        read_delta_snapshot(&conn)?
+    };
+
+    let mut problems = Vec::new();
+    if let Some(expected) = expected_backup {
+        let stamped = info.as_ref().map(|i| i.restored_from.as_str());
+        if stamped != Some(expected) {
+            problems.push(format!(
+                "restore stamp names {} instead of {}",
+                stamped.unwrap_or("no backup"),
+                expected
+            ));
+        }
+        if let Ok(backup_path) = resolve_any_backup_file(app, expected) {
+            let decompressed = if is_compressed_backup(expected) {
+                Some(decompress_backup_to_temp(&backup_path)?)
+            } else {
+                None
+            };
+            let backup_db = decompressed
+                .as_ref()
+                .map_or(backup_path.as_path(), |t| t.0.as_path());
+            let conn = open_database(backup_db, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+            let mismatched: Vec<String> = read_delta_snapshot(&conn)?
+                .counts
+                .into_iter()
+                .filter(|entry| !live.counts.contains(entry))
+                .map(|(table, count)| format!("{} ({} in backup)", table, count))
+                .collect();
+            if !mismatched.is_empty() {
+                problems.push(format!("row counts differ for {}", mismatched.join(", ")));
+            }
+        }
+    }
+
+    let warning = (!problems.is_empty()).then(|| {
+        format!(
+            "The database still looks like the old data ({}); reconnection may have failed",
+            problems.join("; ")
+        )
+    });
+    Ok(RestoreProbe {
+        restored_from: info.as_ref().map(|i| i.restored_from.clone()),
+        restored_at: info.map(|i| i.restored_at),
+        table_counts: live
+            .counts
+            .into_iter()
+            .map(|(table, count)| TableRowCount {
+                table,
+                count,
+                approximate: false,
+            })
+            .collect(),
+        expected_backup: expected_backup.map(str::to_string),
+        matches_expected: problems.is_empty(),
+        warning,
+    })
+}
+
+/// Probes the live database through a fresh connection after a restore and
+/// reconnect, so the frontend can confirm it is reading the restored data
+#[tauri::command]
+pub(crate) fn probe_restored_database(
+    app: AppHandle,
+    expected_backup: Option<String>,
+) -> Result<RestoreProbe, String> {
+    probe_restored_state(&app, expected_backup.as_deref())
+}
+
+/// Reverts the most recent restore by restoring the safety backup it took.
+/// The current state gets its own safety backup first, so this can be undone too.
+#[tauri::command]
+pub(crate) fn undo_last_restore(app: AppHandle) -> Result<UndoRestoreResult, String> {
+    let last = read_operation_history(&app, Some("restore"), None, None, None)?
+        .into_iter()
+        .find(|record| record.success)
+        .ok_or_else(|| "No restore has been recorded, so there is nothing to undo".to_string())?;
+
+    let backups_dir = get_backups_dir(&app)?;
+    let recorded = last
+        .details
+        .strip_prefix("Safety backup created: ")
+        .map(|name| name.trim().to_string());
+    // Fall back to the newest safety backup whose manifest names that restore
+    let safety_filename = recorded
+        .filter(|name| backups_dir.join(name).is_file())
+        .or_else(|| {
+            list_backups(app.clone(), None)
+                .ok()?
+                .into_iter()
+                .find_map(|b| {
+                    let manifest = read_backup_manifest(Path::new(&b.path));
+                    (b.filename.starts_with("pre_restore_safety_")
+                        && manifest.safety_for.as_deref() == Some("restore")
+                        && manifest.safety_target == last.filename)
+                        .then_some(b.filename)
+                })
+        })
+        .ok_or_else(|| {
+            format!(
+                "The safety backup taken before the restore at {} is missing",
+                last.timestamp
+            )
+        })?;
+
+    check_backup_restorable(&backups_dir.join(&safety_filename), &safety_filename)?;
+    let message = restore_database(app, safety_filename.clone())?;
+
+    Ok(UndoRestoreResult {
+        reverted_to: safety_filename,
+        undone_backup: last.filename,
+        undone_at: last.timestamp,
+        message,
+    })
+}
+
+/// Replaces the live database with a backup after taking a pre_restore_safety_ copy
+pub(crate) fn run_restore_database(
+    app: &AppHandle,
+    backup_filename: &str,
+) -> Result<String, String> {
+    require_primary_instance(app)?;
+    let _operation = begin_operation(app, "restore", false);
+    let db_path = get_db_path(app)?;
+    let backups_dir = get_backups_dir(app)?;
+    let backup_path = resolve_any_backup_file(app, backup_filename)?;
+
+    // A .db.gz is restored from a decompressed temp copy; the safety backup
+    // below still runs before anything overwrites the live database
+    let decompressed = if is_compressed_backup(backup_filename) {
+        Some(decompress_backup_to_temp(&backup_path)?)
+    } else {
+        None
+    };
+    let restore_source = decompressed
+        .as_ref()
+        .map_or(backup_path.as_path(), |t| t.0.as_path());
+
+    // Create a safety backup of current database before restore
+    let safety_filename = safety_backup_filename("restore");
+    let safety_path = backups_dir.join(&safety_filename);
+
+    if db_path.exists() {
+        snapshot_database(&db_path, &safety_path)
+            .map_err(|e| format!("Failed to create safety backup: {}", e))?;
+        record_safety_origin(&safety_path, "restore", Some(backup_filename));
+    }
+
+    // Leftover sidecars from a crash would be replayed over the restored file
+    if let Err(e) = remove_stale_sidecars(app, &db_path) {
+        log::warn!("{}", e);
+    }
+
+    // Perform the restore
+    copy_file_durable(restore_source, &db_path).map_err(|e| {
+        describe_copy_error("Failed to restore database", &e, restore_source, &db_path)
+    })?;
+
+    // The restore itself succeeded; a missing stamp only costs support context
+    if let Err(e) = stamp_restore_provenance(&db_path, backup_filename) {
+        log::warn!("{}", e);
+    }
+
+    log_operation(
+        app,
+        OperationRecord::new(
+            "restore",
+            true,
+            format!("Safety backup created: {}", safety_filename),
+        )
+        .with_filename(backup_filename),
+    );
+
+    let mut message = format!(
+        "Database restored from {}. Safety backup created: {}",
+        backup_filename, safety_filename
+    );
+    let excluded = read_backup_manifest(&backup_path).excluded_tables;
+    if !excluded.is_empty() {
+        message.push_str(&format!(
+            ". Warning: this backup was taken without the rows of {}; those tables are now empty",
+            excluded.join(", ")
+        ));
+    }
+    match probe_restored_state(app, Some(backup_filename)) {
+        Ok(probe) => {
+            if let Some(warning) = probe.warning {
+                log::warn!("{}", warning);
+                message.push_str(&format!(". Warning: {}", warning));
+            }
+        }
+        Err(e) => log::warn!("Post-restore probe failed: {}", e),
+    }
+    Ok(message)
+}
+
+/// Returns the backup the live database was last restored from, if any
+#[tauri::command]
+pub(crate) fn get_last_restore_info(app: AppHandle) -> Result<Option<RestoreInfo>, String> {
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Ok(None);
+    }
+
+    let conn = open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    if !table_exists(&conn, "app_meta") {
+        return Ok(None);
+    }
+
+    let read_meta = |key: &str| -> Result<Option<String>, String> {
+        conn.query_row(
+            "SELECT value FROM app_meta WHERE key = ?1",
+            params![key],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()
+        .map(Option::flatten)
+        .map_err(|e| format!("Failed to read restore info: {}", e))
+    };
+
+    match (
+        read_meta(LAST_RESTORE_FROM_META_KEY)?,
+        read_meta(LAST_RESTORE_AT_META_KEY)?,
+    ) {
+        (Some(restored_from), Some(restored_at)) => Ok(Some(RestoreInfo {
+            restored_from,
+            restored_at,
+        })),
+        _ => Ok(None),
+    }
+}
+
+/// Reads PRAGMA user_version and application_id
+pub(crate) fn read_schema_ids(conn: &Connection) -> Result<(i64, i64), String> {
+    let read = |pragma: &str| {
+        conn.query_row(&format!("PRAGMA {}", pragma), [], |row| {
+            row.get::<_, i64>(0)
+        })
+        .map_err(|e| format!("Failed to read {}: {}", pragma, e))
+    };
+    Ok((read("user_version")?, read("application_id")?))
+}
+
+/// Compares a backup's schema ids with the running database's. A backup with
+/// application_id 0 (unset) is never treated as foreign.
+pub(crate) fn check_backup_versions(
+    app: &AppHandle,
+    source: &Path,
+) -> Result<BackupVersionCheck, String> {
+    let (backup_user_version, backup_application_id) =
+        read_schema_ids(&open_database(source, OpenFlags::SQLITE_OPEN_READ_ONLY)?)?;
+    let db_path = get_db_path(app)?;
+    let (expected_user_version, expected_application_id) = if db_path.exists() {
+        read_schema_ids(&open_database(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?)?
+    } else {
+        (0, 0)
+    };
+
+    Ok(BackupVersionCheck {
+        backup_user_version,
+        backup_application_id,
+        expected_user_version,
+        expected_application_id,
+        newer: backup_user_version > expected_user_version,
+        foreign: backup_application_id != 0 && backup_application_id != expected_application_id,
+    })
+}
+
+/// Reports a backup file's schema version and application_id against this app's
+/// without importing it
+#[tauri::command]
+pub(crate) fn check_backup_version(
+    app: AppHandle,
+    source_path: String,
+) -> Result<BackupVersionCheck, String> {
+    let source = normalize_user_path(&app, &source_path)?;
+    if !source.is_file() || !has_sqlite_header(&source)? {
+        return Err("Invalid backup file. Expected a SQLite database".to_string());
+    }
+    check_backup_versions(&app, &source)
+}
+
+/// Restores from an external backup file path. A backup from a newer schema
+/// needs `allow_newer`; one with another application's id needs `force`.
+/// An encrypted backup (.db.enc, .db.gz.enc) needs its `passphrase`.
+#[tauri::command]
+pub(crate) fn import_backup(
+    app: AppHandle,
+    source_path: String,
+    allow_newer: Option<bool>,
+    force: Option<bool>,
+    passphrase: Option<String>,
+) -> Result<ImportResult, String> {
+    require_primary_instance(&app)?;
+    let _operation = begin_operation(&app, "import", false);
+    let db_path = get_db_path(&app)?;
+    let backups_dir = get_backups_dir(&app)?;
+    let source = normalize_user_path(&app, &source_path)?;
+    let source_path = source.to_string_lossy().to_string();
+
+    // Verify source exists and is a SQLite database, whatever its extension
+    if !source.is_file() {
+        return Err("Source backup file not found".to_string());
+    }
+
+    // A .db.gz or an encrypted backup is checked and imported from a plain temp copy
+    let decompressed = if is_encrypted_backup(&source)? {
+        let Some(passphrase) = passphrase.filter(|p| !p.is_empty()) else {
+            return Err("This backup is encrypted. Enter its passphrase to import it".to_string());
+        };
+        Some(decrypt_backup_to_temp(&source, &passphrase)?)
+    } else if is_compressed_backup(&source_path) {
+        Some(decompress_backup_to_temp(&source)?)
+    } else {
+        None
+    };
+    let data = decompressed
+        .as_ref()
+        .map_or(source.as_path(), |t| t.0.as_path());
+
+    if !has_sqlite_header(data)? {
+        return Err("Invalid backup file. Expected a SQLite database".to_string());
+    }
+
+    let versions = check_backup_versions(&app, data)?;
+    if versions.foreign && !force.unwrap_or(false) {
+        return Err(format!(
+            "Backup has application_id {}, not this app's ({}); it is probably not a MotorMods database. Pass force to import it anyway",
+            versions.backup_application_id, versions.expected_application_id
+        ));
+    }
+    if versions.newer && !allow_newer.unwrap_or(false) {
+        return Err(format!(
+            "Backup is from a newer app version (schema {}, this app uses {}), please update. Pass allow_newer to import it anyway",
+            versions.backup_user_version, versions.expected_user_version
+        ));
+    }
+
+    // Create a safety backup first
+    let safety_filename = safety_backup_filename("import");
+    let safety_path = backups_dir.join(&safety_filename);
+
+    if db_path.exists() {
+        snapshot_database(&db_path, &safety_path)
+            .map_err(|e| format!("Failed to create safety backup: {}", e))?;
+        record_safety_origin(&safety_path, "import", Some(&source_path));
+    }
+
+    if let Err(e) = remove_stale_sidecars(&app, &db_path) {
+        log::warn!("{}", e);
+    }
+
+    // Restore from external file
+    copy_file_durable(data, &db_path)
+        .map_err(|e| describe_copy_error("Failed to import backup", &e, data, &db_path))?;
+
+    // Keep the imported file among the backups, named .db like the rest
+    let already_kept = source.parent() == Some(backups_dir.as_path()) && is_listed_backup(&source);
+    let kept_filename = if already_kept {
+        source
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    } else {
+        imported_backup_filename(&backups_dir, &source)
+    };
+    if !already_kept {
+        let kept_path = backups_dir.join(&kept_filename);
+        if let Err(e) = copy_file_durable(data, &kept_path) {
+            log::warn!(
+                "{}",
+                describe_copy_error("Failed to keep imported backup", &e, data, &kept_path)
+            );
+        }
+    }
+
+    log_operation(
+        &app,
+        OperationRecord::new(
+            "import",
+            true,
+            format!(
+                "Imported {} (schema {}, application_id {}). Safety backup created: {}",
+                source_path,
+                versions.backup_user_version,
+                versions.backup_application_id,
+                safety_filename
+            ),
+        )
+        .with_filename(&kept_filename),
+    );
+
+    Ok(ImportResult {
+        message: format!(
+            "Database imported from external backup. Safety backup created: {}",
+            safety_filename
+        ),
+        versions,
+    })
+}
+
+/// <stem>.db for an imported file (e.g. shop.sqlite -> shop.db), with a
+/// timestamp added when that name is already taken in the backups directory
+pub(crate) fn imported_backup_filename(backups_dir: &Path, source: &Path) -> String {
+    let name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = name
+        .strip_suffix(ARCHIVAL_BACKUP_SUFFIX)
+        .or_else(|| name.strip_suffix(ENCRYPTED_BACKUP_SUFFIX))
+        .or_else(|| name.strip_suffix(COMPRESSED_BACKUP_SUFFIX))
+        .map(str::to_string)
+        .or_else(|| source.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "imported".to_string());
+    let filename = format!("{}.db", stem);
+    if !backups_dir.join(&filename).exists() {
+        return filename;
+    }
+    format!("{}_{}.db", stem, Local::now().format("%Y-%m-%d_%H-%M-%S"))
+}
+
+/// Opens a native file dialog and imports the chosen backup in one step.
+/// Picking a portable backup's marker or .db imports that folder's backup
+/// once its checksum has been verified. An encrypted pick is decrypted with
+/// `passphrase`, and refused when none was given.
+#[tauri::command]
+pub(crate) async fn import_backup_interactive(
+    app: AppHandle,
+    passphrase: Option<String>,
+) -> Result<InteractiveImportResult, String> {
+    // Runs off the main thread (async command), so the blocking dialog is safe here
+    let picked = app
+        .dialog()
+        .file()
+        .set_title("Import MotorMods backup")
+        .add_filter("MotorMods backup", SQLITE_FILE_EXTENSIONS)
+        .add_filter("Encrypted MotorMods backup", ENCRYPTED_FILE_EXTENSIONS)
+        .add_filter("MotorMods portable backup", &[MANIFEST_SIDECAR_EXT])
+        .blocking_pick_file();
+
+    let Some(picked) = picked else {
+        return Ok(InteractiveImportResult {
+            cancelled: true,
+            source_path: None,
+            message: None,
+            portable: None,
+        });
+    };
+
+    let mut source = picked
+        .into_path()
+        .map_err(|e| format!("Invalid file selection: {}", e))?;
+    let portable = find_portable_backup(&source)?;
+    if let Some(portable) = &portable {
+        if !portable.checksum_ok {
+            return Err(format!(
+                "{} no longer matches the checksum recorded when it was exported; the copy is damaged",
+                portable.marker.backup_filename
+            ));
+        }
+        source = PathBuf::from(&portable.backup_path);
+    }
+    let source_path = source.to_string_lossy().to_string();
+    let message = import_backup(app, source_path.clone(), None, None, passphrase)?.message;
+
+    Ok(InteractiveImportResult {
+        cancelled: false,
+        source_path: Some(source_path),
+        message: Some(message),
+        portable,
+    })
+}
+
+/// Reads the portable backup layout `path` belongs to: the folder itself, its
+/// marker file or its .db. None when there is no marker or `path` is some
+/// other file that happens to sit next to one.
+pub(crate) fn find_portable_backup(path: &Path) -> Result<Option<PortableBackupInfo>, String> {
+    let folder = if path.is_dir() {
+        path
+    } else {
+        match path.parent() {
+            Some(parent) => parent,
+            None => return Ok(None),
+        }
+    };
+    let marker_path = folder.join(PORTABLE_MARKER_FILE);
+    if !marker_path.is_file() {
+        return Ok(None);
+    }
+    let marker: PortableBackupMarker = fs::read_to_string(&marker_path)
+        .map_err(|e| format!("Failed to read {}: {}", PORTABLE_MARKER_FILE, e))
+        .and_then(|raw| {
+            serde_json::from_str(&raw)
+                .map_err(|e| format!("{} is not valid: {}", PORTABLE_MARKER_FILE, e))
+        })?;
+    if marker.format != PORTABLE_FORMAT {
+        return Err(format!(
+            "Unsupported portable backup format: {}",
+            marker.format
+        ));
+    }
+    if Path::new(&marker.backup_filename).file_name()
+        != Some(std::ffi::OsStr::new(&marker.backup_filename))
+    {
+        return Err(format!(
+            "{} names an invalid backup file",
+            PORTABLE_MARKER_FILE
+        ));
+    }
+    let backup_path = folder.join(&marker.backup_filename);
+    if path.is_file() && path != marker_path && path != backup_path {
+        return Ok(None);
+    }
+    if !backup_path.is_file() {
+        return Err(format!(
+            "Portable backup folder is missing {}",
+            marker.backup_filename
+        ));
+    }
+
+    Ok(Some(PortableBackupInfo {
+        folder: folder.to_string_lossy().to_string(),
+        backup_path: backup_path.to_string_lossy().to_string(),
+        checksum_ok: compute_file_sha256(&backup_path)? == marker.sha256.to_lowercase(),
+        marker,
+    }))
+}
+
+/// Checks whether a path is (or is inside) a folder written by
+/// export_portable_backup, so the import dialog can show what it holds first
+#[tauri::command]
+pub(crate) fn detect_portable_backup(
+    app: AppHandle,
+    path: String,
+) -> Result<Option<PortableBackupInfo>, String> {
+    find_portable_backup(&normalize_user_path(&app, &path)?)
+}
+
+/// Restores a backup into a separate trial database and switches the app to it.
+/// The real database is untouched until commit_trial; abort_trial switches back.
+/// As with switch_database, the frontend should close its SQL connection first.
+#[tauri::command]
+pub(crate) fn begin_trial_restore(
+    app: AppHandle,
+    backup_filename: String,
+) -> Result<TrialRestoreEvent, String> {
+    require_primary_instance(&app)?;
+    let backup_path = resolve_backup_file(&app, &backup_filename)?;
+    let db_state = app.state::<DatabaseState>();
+    let _busy = db_state.busy.try_lock().map_err(|_| {
+        "A database operation is in progress. Try again when it finishes.".to_string()
+    })?;
+    let mut trial = db_state
+        .trial
+        .lock()
+        .map_err(|_| "Database state is unavailable".to_string())?;
+    if trial.is_some() {
+        return Err("A trial restore is already active. Commit or abort it first.".to_string());
+    }
+
+    check_backup_restorable(&backup_path, &backup_filename)?;
+
+    let trial_path = get_app_dir(&app)?.join(format!("{}.db", TRIAL_DB_NAME));
+    remove_trial_files(&trial_path);
+    copy_file_durable(&backup_path, &trial_path).map_err(|e| {
+        describe_copy_error(
+            "Failed to create trial database",
+            &e,
+            &backup_path,
+            &trial_path,
+        )
+    })?;
+
+    let original_name = db_state
+        .active_name
+        .lock()
+        .map_err(|_| "Database state is unavailable".to_string())?
+        .clone();
+    let database = activate_database(&app, &db_state, TRIAL_DB_NAME, &trial_path)?;
+    *trial = Some(TrialRestore {
+        backup_filename: backup_filename.clone(),
+        original_name,
+    });
+
+    log_operation(
+        &app,
+        OperationRecord::new("trial_restore", true, "Trial restore started")
+            .with_filename(&backup_filename),
+    );
+    Ok(emit_trial_event(
+        &app,
+        "started",
+        true,
+        backup_filename,
+        database,
+        None,
+    ))
+}
+
+/// Promotes the trial database to the real one (after a safety backup of the
+/// current data) and switches the app back to it
+#[tauri::command]
+pub(crate) fn commit_trial(app: AppHandle) -> Result<TrialRestoreEvent, String> {
+    require_primary_instance(&app)?;
+    let db_state = app.state::<DatabaseState>();
+    let _busy = db_state.busy.try_lock().map_err(|_| {
+        "A database operation is in progress. Try again when it finishes.".to_string()
+    })?;
+    let mut trial = db_state
+        .trial
+        .lock()
+        .map_err(|_| "Database state is unavailable".to_string())?;
+    let Some(active_trial) = trial.as_ref() else {
+        return Err("No trial restore is active".to_string());
+    };
+
+    let app_dir = get_app_dir(&app)?;
+    let trial_path = app_dir.join(format!("{}.db", TRIAL_DB_NAME));
+    let original_path = app_dir.join(format!("{}.db", active_trial.original_name));
+    let backups_dir = get_backups_dir(&app)?;
+
+    let safety_filename = safety_backup_filename("restore");
+    if original_path.exists() {
+        let safety_path = backups_dir.join(&safety_filename);
+        snapshot_database(&original_path, &safety_path)
+            .map_err(|e| format!("Failed to create safety backup: {}", e))?;
+        record_safety_origin(
+            &safety_path,
+            "commit_trial",
+            Some(&active_trial.backup_filename),
+        );
+    }
+
+    if let Err(e) = remove_stale_sidecars(&app, &original_path) {
+        log::warn!("{}", e);
+    }
+
+    // Snapshot first so anything the trial session left in its WAL is included
+    let staging_path = app_dir.join(format!("{}.db.commit", TRIAL_DB_NAME));
+    // Left by an interrupted commit; it is only ever a copy of the trial
+    let _ = fs::remove_file(&staging_path);
+    let promoted = snapshot_database(&trial_path, &staging_path).and_then(|_| {
+        copy_file_durable(&staging_path, &original_path).map_err(|e| {
+            describe_copy_error(
+                "Failed to promote trial database",
+                &e,
+                &staging_path,
+                &original_path,
+            )
+        })
+    });
+    let _ = fs::remove_file(&staging_path);
+    promoted?;
+
+    let Some(finished) = trial.take() else {
+        return Err("No trial restore is active".to_string());
+    };
+    if let Err(e) = stamp_restore_provenance(&original_path, &finished.backup_filename) {
+        log::warn!("{}", e);
+    }
+    let database = activate_database(&app, &db_state, &finished.original_name, &original_path)?;
+    remove_trial_files(&trial_path);
+
+    log_operation(
+        &app,
+        OperationRecord::new(
+            "trial_restore",
+            true,
+            format!(
+                "Trial committed. Safety backup created: {}",
+                safety_filename
+            ),
+        )
+        .with_filename(&finished.backup_filename),
+    );
+    Ok(emit_trial_event(
+        &app,
+        "committed",
+        false,
+        finished.backup_filename,
+        database,
+        Some(safety_filename),
+    ))
+}
+
+/// Discards the trial database and switches the app back to the real one
+#[tauri::command]
+pub(crate) fn abort_trial(app: AppHandle) -> Result<TrialRestoreEvent, String> {
+    let db_state = app.state::<DatabaseState>();
+    let _busy = db_state.busy.try_lock().map_err(|_| {
+        "A database operation is in progress. Try again when it finishes.".to_string()
+    })?;
+    let finished = db_state
+        .trial
+        .lock()
+        .map_err(|_| "Database state is unavailable".to_string())?
+        .take()
+        .ok_or_else(|| "No trial restore is active".to_string())?;
+
+    let app_dir = get_app_dir(&app)?;
+    let original_path = app_dir.join(format!("{}.db", finished.original_name));
+    let database = activate_database(&app, &db_state, &finished.original_name, &original_path)?;
+    remove_trial_files(&app_dir.join(format!("{}.db", TRIAL_DB_NAME)));
+
+    log_operation(
+        &app,
+        OperationRecord::new("trial_restore", true, "Trial restore discarded")
+            .with_filename(&finished.backup_filename),
+    );
+    Ok(emit_trial_event(
+        &app,
+        "aborted",
+        false,
+        finished.backup_filename,
+        database,
+        None,
+    ))
+}
+
+pub(crate) fn emit_trial_event(
+    app: &AppHandle,
+    status: &str,
+    active: bool,
+    backup_filename: String,
+    database: DatabaseSwitched,
+    safety_backup: Option<String>,
+) -> TrialRestoreEvent {
+    let event = TrialRestoreEvent {
+        status: status.to_string(),
+        active,
+        backup_filename,
+        database,
+        safety_backup,
+    };
+    let _ = app.emit("trial-restore", event.clone());
+    event
+}
+
+/// Deletes the trial database and any -wal/-shm it left behind
+pub(crate) fn remove_trial_files(trial_path: &Path) {
+    let _ = fs::remove_file(trial_path);
+    for sidecar in sqlite_sidecar_paths(trial_path) {
+        let _ = fs::remove_file(sidecar);
+    }
+}
+
+/// Fails unless the backup is a SQLite database that passes integrity_check
+pub(crate) fn check_backup_restorable(
+    backup_path: &Path,
+    backup_filename: &str,
+) -> Result<(), String> {
+    if !has_sqlite_header(backup_path)? {
+        return Err(format!("{} is not a SQLite database", backup_filename));
+    }
+    let conn = open_database(backup_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let integrity = run_integrity_check(&conn);
+    if integrity != "ok" {
+        return Err(format!("Backup failed integrity check: {}", integrity));
+    }
+    Ok(())
+}
+
+/// Copies a backup to app_config_dir/<new_db_name>.db for side-by-side inspection,
+/// leaving the active database untouched. The returned connection string is meant
+/// for a read-only tauri-plugin-sql connection; the active database is not switched.
+#[tauri::command]
+pub(crate) fn restore_to_new(
+    app: AppHandle,
+    backup_filename: String,
+    new_db_name: String,
+) -> Result<DatabaseSwitched, String> {
+    let backup_path = resolve_backup_file(&app, &backup_filename)?;
+    let name = validate_db_name(&new_db_name)?;
+
+    let active_name = app
+        .state::<DatabaseState>()
+        .active_name
+        .lock()
+        .map_err(|_| "Database state is unavailable".to_string())?
+        .clone();
+    let reserved = [active_name.as_str(), DEFAULT_DB_NAME, TRIAL_DB_NAME];
+    if reserved.iter().any(|r| name.eq_ignore_ascii_case(r)) {
+        return Err(format!(
+            "{}.db is an application database; choose a different name",
+            name
+        ));
+    }
+
+    let target = get_app_dir(&app)?.join(format!("{}.db", name));
+    if target.exists() {
+        return Err(format!(
+            "Database already exists: {}.db. Choose another name",
+            name
+        ));
+    }
+
+    check_backup_restorable(&backup_path, &backup_filename)?;
+
+    copy_file_durable(&backup_path, &target).map_err(|e| {
+        describe_copy_error("Failed to restore backup copy", &e, &backup_path, &target)
+    })?;
+
+    log_operation(
+        &app,
+        OperationRecord::new(
+            "restore_to_new",
+            true,
+            format!("Restored copy as {}.db", name),
+        )
+        .with_filename(&backup_filename),
+    );
+
+    Ok(DatabaseSwitched {
+        connection: format!("sqlite:{}.db", name),
+        name,
+        path: target.to_string_lossy().to_string(),
+    })
+}
+
+/// Restores database by importing data from a backup file
+/// This uses rusqlite directly to handle the data import properly
+/// Much more robust than file replacement - works without app restart
+#[tauri::command]
+pub(crate) fn restore_data_from_backup(
+    app: AppHandle,
+    backup_path: String,
+) -> Result<RestoreResult, String> {
+    require_primary_instance(&app)?;
+    let db_state = app.state::<DatabaseState>();
+    let _busy = db_state
+        .busy
+        .lock()
+        .map_err(|_| "Database state is unavailable".to_string())?;
+
+    let db_path = get_db_path(&app)?;
+    let backups_dir = get_backups_dir(&app)?;
+    let backup_file = normalize_user_path(&app, &backup_path)?;
+
+    // Verify backup exists
+    if !backup_file.exists() {
+        return Err(format!("Backup file not found: {}", backup_path));
+    }
+
+    // Create a safety backup first
+    let safety_filename = safety_backup_filename("restore");
+    let safety_path = backups_dir.join(&safety_filename);
+
+    if db_path.exists() {
+        snapshot_database(&db_path, &safety_path)
+            .map_err(|e| format!("Failed to create safety backup: {}", e))?;
+        record_safety_origin(
+            &safety_path,
+            "restore_data",
+            Some(&backup_file.to_string_lossy()),
+        );
+    }
+
+    // Open both databases
+    let backup_conn = Connection::open(&backup_file)
+        .map_err(|e| format!("Failed to open backup database: {}", e))?;
+    
+    let main_conn = Connection::open(&db_path)
+        .map_err(|e| format!("Failed to open main database: {}", e))?;
+
+    // Disable foreign keys for the import
+    main_conn
+        .execute("PRAGMA foreign_keys = OFF", [])
+        .map_err(|e| format!("Failed to disable foreign keys: {}", e))?;
+
+    // Start a transaction
+    main_conn
+        .execute("BEGIN TRANSACTION", [])
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let mut total_imported = 0;
+
+    // Clear and import each table
+    for table in DATA_TABLES.iter().rev() {
+        // Clear table first (reverse order for foreign keys)
+        if let Err(e) = main_conn.execute(&format!("DELETE FROM {}", table), []) {
+            eprintln!("Warning: Could not clear table {}: {}", table, e);
+        }
+    }
+
+    // Import data in forward order
+    for table in DATA_TABLES.iter() {
+        match copy_table_data(&backup_conn, &main_conn, table) {
+            Ok(count) => {
+                println!("[Restore] Imported {} rows into {}", count, table);
+                total_imported += count;
+            }
+            Err(e) => {
+                eprintln!("Warning: Error importing {}: {}", table, e);
+                // Continue with other tables
+            }
+        }
+    }
+
+    // Commit the transaction
+    if let Err(e) = main_conn.execute("COMMIT", []) {
+        // Try to rollback
+        let _ = main_conn.execute("ROLLBACK", []);
+        return Err(format!("Failed to commit transaction: {}", e));
+    }
+
+    // Re-enable foreign keys
+    let _ = main_conn.execute("PRAGMA foreign_keys = ON", []);
+
+    Ok(RestoreResult {
+        success: true,
+        message: format!("Successfully restored {} records from backup", total_imported),
+        records_imported: total_imported,
+        safety_backup: safety_filename,
+    })
+}
+
+/// Restores database by importing data from a backup file in the backups directory
+#[tauri::command]
+pub(crate) fn restore_data_from_backup_file(
+    app: AppHandle,
+    backup_filename: String,
+) -> Result<RestoreResult, String> {
+    let backup_path = resolve_backup_file(&app, &backup_filename)?;
+    
+    restore_data_from_backup(app, backup_path.to_string_lossy().to_string())
+}
+
+// ============================================
+// RESTORE FROM URL (disaster recovery)
+// ============================================
+
+pub(crate) const URL_RESTORE_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Payload of "url-restore-progress"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlRestoreProgress {
+    /// "downloading", "verifying" or "restoring"
+    pub stage: String,
+    pub downloaded_bytes: u64,
+    /// From Content-Length; None when the server didn't send one
+    pub total_bytes: Option<u64>,
+}
+
+/// Downloads a backup over HTTP(S) and restores it. The download streams to a temp
+/// file and is checked (optional SHA-256, then integrity_check) before the usual
+/// import runs, so a failed or partial download never touches the live database.
+/// Emits "url-restore-progress"; cancellable with cancel_backup_operation.
+#[tauri::command]
+pub(crate) async fn restore_from_url(
+    app: AppHandle,
+    url: String,
+    expected_sha256: Option<String>,
+) -> Result<String, String> {
+    let temp_path = unique_temp_path("url_restore", "db");
+    let result = run_restore_from_url(&app, &url, expected_sha256.as_deref(), &temp_path).await;
+    let _ = fs::remove_file(&temp_path);
+
+    let record = match &result {
+        Ok(message) => OperationRecord::new("restore_from_url", true, message.clone()),
+        Err(e) => OperationRecord::new("restore_from_url", false, format!("{}: {}", url, e)),
+    };
+    log_operation(&app, record);
+    result
+}
+
+pub(crate) async fn run_restore_from_url(
+    app: &AppHandle,
+    url: &str,
+    expected_sha256: Option<&str>,
+    temp_path: &Path,
+) -> Result<String, String> {
+    require_primary_instance(app)?;
+    use sha2::Digest;
+    use std::io::Write;
+
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Only http and https URLs are supported".to_string());
+    }
+    let expected_sha256 = expected_sha256
+        .map(|v| v.trim().to_ascii_lowercase())
+        .filter(|v| !v.is_empty());
+    if let Some(expected) = &expected_sha256 {
+        if expected.len() != 64 || !expected.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("expected_sha256 must be 64 hex characters".to_string());
+        }
+    }
+
+    let operation = begin_operation(app, "restore_from_url", true);
+    let emit = |stage: &str, downloaded_bytes: u64, total_bytes: Option<u64>| {
+        let _ = app.emit(
+            "url-restore-progress",
+            UrlRestoreProgress {
+                stage: stage.to_string(),
+                downloaded_bytes,
+                total_bytes,
+            },
+        );
+    };
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(URL_RESTORE_CONNECT_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to set up download client: {}", e))?;
+    let mut response = client
+        .get(parsed.clone())
+        .send()
+        .await
+        .map_err(|e| format!("Download failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Download failed: HTTP {}", response.status()));
+    }
+    let total_bytes = response.content_length();
+
+    let mut file = fs::File::create(temp_path)
+        .map_err(|e| format!("Failed to create download file: {}", e))?;
+    let mut hasher = sha2::Sha256::new();
+    let mut downloaded: u64 = 0;
+    emit("downloading", 0, total_bytes);
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Download interrupted after {} bytes: {}", downloaded, e))?
+    {
+        if operation.is_cancelled() {
+            return Err("Download cancelled".to_string());
+        }
+        file.write_all(&chunk)
+            .map_err(|e| format!("Failed to write download file: {}", e))?;
+        hasher.update(&chunk);
+        downloaded += chunk.len() as u64;
+        if let Some(total) = total_bytes.filter(|t| *t > 0) {
+            operation.set_progress(downloaded as f64 * 100.0 / total as f64);
+        }
+        emit("downloading", downloaded, total_bytes);
+    }
+    file.sync_all()
+        .map_err(|e| format!("Failed to write download file: {}", e))?;
+    drop(file);
+    if let Some(total) = total_bytes {
+        if downloaded != total {
+            return Err(format!(
+                "Download incomplete: received {} of {} bytes",
+                downloaded, total
+            ));
+        }
+    }
+
+    emit("verifying", downloaded, total_bytes);
+    let checksum = format!("{:x}", hasher.finalize());
+    if let Some(expected) = &expected_sha256 {
+        if &checksum != expected {
+            return Err(format!(
+                "Checksum mismatch: expected {}, downloaded file is {}",
+                expected, checksum
+            ));
+        }
+    }
+
+    let remote_name = parsed
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .map(|name| {
+            name.chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || "-_.".contains(c) {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect::<String>()
+        })
+        .filter(|name| !name.trim_matches('.').is_empty())
+        .unwrap_or_else(|| "downloaded.db".to_string());
+    let worker_app = app.clone();
+    let temp_path = temp_path.to_path_buf();
+    let message = tauri::async_runtime::spawn_blocking(move || {
+        check_backup_restorable(&temp_path, &remote_name)?;
+
+        // Keep the verified download among the backups, then restore from that copy
+        let backups_dir = get_backups_dir(&worker_app)?;
+        let kept_filename = imported_backup_filename(&backups_dir, Path::new(&remote_name));
+        let kept_path = backups_dir.join(&kept_filename);
+        copy_file_durable(&temp_path, &kept_path).map_err(|e| {
+            describe_copy_error(
+                "Failed to save downloaded backup",
+                &e,
+                &temp_path,
+                &kept_path,
+            )
+        })?;
+        write_checksum_sidecar(&kept_path, &checksum)?;
+
+        let _ = worker_app.emit(
+            "url-restore-progress",
+            UrlRestoreProgress {
+                stage: "restoring".to_string(),
+                downloaded_bytes: downloaded,
+                total_bytes,
+            },
+        );
+        import_backup(
+            worker_app,
+            kept_path.to_string_lossy().to_string(),
+            None,
+            None,
+            None,
+        )
+        .map(|result| result.message)
+    })
+    .await
+    .map_err(|e| format!("Restore task failed: {}", e))??;
+
+    Ok(format!(
+        "Downloaded {} bytes from {}. {}",
+        downloaded, parsed, message
+    ))
+}
+
+// ============================================
+// CSV BUNDLE IMPORT
+// ============================================
+
+/// Imports a zip of per-table CSVs (<table>.csv, header row first) in one
+/// transaction after a safety backup. `mode` is "append" or "replace" (clear each
+/// imported table first). Entries that escape the archive or don't match a table
+/// fail the import unless `lenient` is set, in which case they are skipped.
+/// Empty fields are imported as NULL.
+#[tauri::command]
+pub(crate) fn import_csv_bundle(
+    app: AppHandle,
+    source_path: String,
+    mode: String,
+    lenient: Option<bool>,
+) -> Result<CsvBundleImportResult, String> {
+    require_primary_instance(&app)?;
+    let replace = match mode.trim() {
+        "append" => false,
+        "replace" => true,
+        other => {
+            return Err(format!(
+                "Invalid import mode: {}. Use append or replace",
+                other
+            ))
+        }
+    };
+    let lenient = lenient.unwrap_or(false);
+
+    let source_path = normalize_user_path(&app, &source_path)?;
+    let file = fs::File::open(&source_path)
+        .map_err(|e| format!("Failed to open {}: {}", source_path.display(), e))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Not a valid zip archive: {}", e))?;
+
+    let db_state = app.state::<DatabaseState>();
+    let _busy = db_state.busy.try_lock().map_err(|_| {
+        "A database operation is in progress. Try again when it finishes.".to_string()
+    })?;
+
+    let db_path = get_db_path(&app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+    let mut conn = open_database(&db_path, OpenFlags::default())?;
+    let schema = read_live_schema(&conn)?;
+
+    // Work out which entries map to which tables before touching anything
+    let BundleEntries { entries, skipped } = match_bundle_entries(&mut archive, &schema, lenient)?;
+    if entries.is_empty() {
+        return Err("The archive contains no table CSVs to import".to_string());
+    }
+
+    let safety_filename = safety_backup_filename("import");
+    let safety_path = get_backups_dir(&app)?.join(&safety_filename);
+    snapshot_database(&db_path, &safety_path)
+        .map_err(|e| format!("Failed to create safety backup: {}", e))?;
+    record_safety_origin(
+        &safety_path,
+        "import_csv_bundle",
+        Some(&source_path.to_string_lossy()),
+    );
+
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    // Tables can arrive in any order; check references once everything is in
+    tx.execute_batch("PRAGMA defer_foreign_keys = ON")
+        .map_err(|e| format!("Failed to configure import: {}", e))?;
+
+    let mut tables = Vec::with_capacity(entries.len());
+    for (index, table) in entries {
+        let entry = archive
+            .by_index(index)
+            .map_err(|e| format!("Failed to read archive: {}", e))?;
+        let definition = schema
+            .tables
+            .iter()
+            .find(|t| t.name == table)
+            .ok_or_else(|| format!("Unknown table: {}", table))?;
+        let inserted = import_csv_table(&tx, definition, entry, replace)
+            .map_err(|e| format!("Import of {} failed, nothing was changed: {}", table, e))?;
+        tables.push(TableImportCount { table, inserted });
+    }
+
+    tx.commit()
+        .map_err(|e| format!("Import failed, nothing was changed: {}", e))?;
+
+    let total: usize = tables.iter().map(|t| t.inserted).sum();
+    log_operation(
+        &app,
+        OperationRecord::new(
+            "import",
+            true,
+            format!(
+                "Imported {} row(s) into {} table(s) from {} ({}). Safety backup created: {}",
+                total,
+                tables.len(),
+                source_path.display(),
+                mode.trim(),
+                safety_filename
+            ),
+        ),
+    );
+
+    Ok(CsvBundleImportResult {
+        tables,
+        skipped,
+        safety_backup: safety_filename,
+    })
+}
+
+/// The tables a CSV bundle's entries map to, from match_bundle_entries
+#[derive(Debug)]
+pub(crate) struct BundleEntries {
+    /// (archive entry index, table name)
+    pub(crate) entries: Vec<(usize, String)>,
+    pub(crate) skipped: Vec<String>,
+}
+
+/// Maps each <table>.csv entry of a bundle to its table. Entries that escape
+/// the archive, aren't CSVs, name no table or repeat one are an error, or with
+/// `lenient` are returned as skipped instead.
+pub(crate) fn match_bundle_entries<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    schema: &SchemaDefinition,
+    lenient: bool,
+) -> Result<BundleEntries, String> {
+    let mut entries: Vec<(usize, String)> = Vec::new();
+    let mut skipped = Vec::new();
+    for index in 0..archive.len() {
+        let entry = archive
+            .by_index(index)
+            .map_err(|e| format!("Failed to read archive: {}", e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+
+        let problem = match entry.enclosed_name() {
+            None => Some(format!("{}: path escapes the archive", name)),
+            Some(path) if path.extension().is_none_or(|ext| ext != "csv") => {
+                Some(format!("{}: not a CSV file", name))
+            }
+            Some(path) => {
+                let table = path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                if schema.tables.iter().any(|t| t.name == table) {
+                    if entries.iter().any(|(_, t)| *t == table) {
+                        Some(format!("{}: table {} appears twice", name, table))
+                    } else {
+                        entries.push((index, table));
+                        None
+                    }
+                } else {
+                    Some(format!("{}: no table named {}", name, table))
+                }
+            }
+        };
+
+        if let Some(problem) = problem {
+            if !lenient {
+                return Err(format!("Rejected archive entry {}", problem));
+            }
+            skipped.push(problem);
+        }
+    }
+    Ok(BundleEntries { entries, skipped })
+}
+
+/// Inserts one CSV's rows into `table`; the header must only name existing columns
+pub(crate) fn import_csv_table(
+    conn: &Connection,
+    table: &TableDefinition,
+    reader: impl Read,
+    replace: bool,
+) -> Result<usize, String> {
+    let mut csv_reader = csv::Reader::from_reader(reader);
+    let headers: Vec<String> = csv_reader
+        .headers()
+        .map_err(|e| format!("Failed to read header: {}", e))?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+    if headers.is_empty() {
+        return Err("Missing header row".to_string());
+    }
+    let unknown: Vec<&str> = headers
+        .iter()
+        .filter(|h| !table.columns.iter().any(|c| &c.name == *h))
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!("Unknown column(s): {}", unknown.join(", ")));
+    }
+
+    let quote = |name: &str| format!("\"{}\"", name.replace('"', "\"\""));
+    if replace {
+        conn.execute(&format!("DELETE FROM {}", quote(&table.name)), [])
+            .map_err(|e| format!("Failed to clear table: {}", e))?;
+    }
+
+    let sql = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        quote(&table.name),
+        headers
+            .iter()
+            .map(|h| quote(h))
+            .collect::<Vec<_>>()
+            .join(", "),
+        (1..=headers.len())
+            .map(|i| format!("?{}", i))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to prepare insert: {}", e))?;
+
+    let mut inserted = 0;
+    for (line, record) in csv_reader.records().enumerate() {
+        let record = record.map_err(|e| format!("Row {}: {}", line + 1, e))?;
+        // Column affinity turns numeric text into INTEGER/REAL values
+        let values: Vec<Option<&str>> = record
+            .iter()
+            .map(|v| if v.is_empty() { None } else { Some(v) })
+            .collect();
+        stmt.execute(rusqlite::params_from_iter(values))
+            .map_err(|e| format!("Row {}: {}", line + 1, e))?;
+        inserted += 1;
+    }
+    Ok(inserted)
+}
+
+// ============================================
+// ONE-WAY SYNC (pull new rows from another database)
+// ============================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableSyncCount {
+    pub table: String,
+    pub inserted: usize,
+    /// Source rows whose key already exists here
+    pub skipped: usize,
+}
+
+/// Response from sync_from
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncResult {
+    pub source_path: String,
+    pub tables: Vec<TableSyncCount>,
+    pub safety_backup: String,
+}
+
+/// Copies rows from another database into this one, per table, when their key
+/// isn't here yet; existing rows are never changed. Keys default to each
+/// table's primary key; `key_columns` maps a table to the columns to match on
+/// instead. Both schemas must agree on the tables' columns. Runs in one
+/// transaction after a pre_sync_safety_ backup.
+#[tauri::command]
+pub(crate) fn sync_from(
+    app: AppHandle,
+    source_path: String,
+    tables: Vec<String>,
+    key_columns: Option<HashMap<String, Vec<String>>>,
+) -> Result<SyncResult, String> {
+    let result = run_sync_from(&app, &source_path, &tables, key_columns.unwrap_or_default());
+    let record = match &result {
+        Ok(sync) => OperationRecord::new(
+            "sync_from",
+            true,
+            format!(
+                "Synced from {}: {}. Safety backup created: {}",
+                sync.source_path,
+                sync.tables
+                    .iter()
+                    .map(|t| format!("{} +{} ({} skipped)", t.table, t.inserted, t.skipped))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                sync.safety_backup
+            ),
+        ),
+        Err(e) => OperationRecord::new("sync_from", false, format!("{}: {}", source_path, e)),
+    };
+    log_operation(&app, record);
+    result
+}
+
+pub(crate) fn run_sync_from(
+    app: &AppHandle,
+    source_path: &str,
+    tables: &[String],
+    key_columns: HashMap<String, Vec<String>>,
+) -> Result<SyncResult, String> {
+    require_primary_instance(app)?;
+    if tables.is_empty() {
+        return Err("Choose at least one table to sync".to_string());
+    }
+    let source = normalize_user_path(app, source_path)?;
+    if !source.is_file() || !has_sqlite_header(&source)? {
+        return Err("Sync source must be a SQLite database".to_string());
+    }
+    let db_path = get_db_path(app)?;
+    if !db_path.exists() {
+        return Err("Database file not found".to_string());
+    }
+    if fs::canonicalize(&source).ok() == fs::canonicalize(&db_path).ok() {
+        return Err("Sync source is the active database".to_string());
+    }
+
+    let db_state = app.state::<DatabaseState>();
+    let _busy = db_state.busy.try_lock().map_err(|_| {
+        "A database operation is in progress. Try again when it finishes.".to_string()
+    })?;
+
+    // Schema check and key resolution before anything is written
+    let source_schema =
+        read_live_schema(&open_database(&source, OpenFlags::SQLITE_OPEN_READ_ONLY)?)?;
+    let mut conn = open_database(&db_path, OpenFlags::default())?;
+    let plans = plan_sync_tables(&conn, &source_schema, tables, key_columns)?;
+
+    let backups_dir = get_backups_dir(app)?;
+    let safety_filename = safety_backup_filename("sync");
+    let safety_path = backups_dir.join(&safety_filename);
+    snapshot_database(&db_path, &safety_path)
+        .map_err(|e| format!("Failed to create safety backup: {}", e))?;
+    let source_path = source.to_string_lossy().to_string();
+    record_safety_origin(&safety_path, "sync_from", Some(&source_path));
+
+    let synced = sync_planned_tables(&mut conn, &source_path, &plans);
+
+    Ok(SyncResult {
+        source_path,
+        tables: synced?,
+        safety_backup: safety_filename,
+    })
+}
+
+/// One table sync_from copies: its columns and the key rows are matched on
+#[derive(Debug)]
+pub(crate) struct SyncTablePlan {
+    pub(crate) table: String,
+    pub(crate) columns: Vec<String>,
+    pub(crate) keys: Vec<String>,
+}
+
+/// Checks that each table exists on both sides with the same columns and
+/// resolves its key: `key_columns` when given, else the primary key
+pub(crate) fn plan_sync_tables(
+    conn: &Connection,
+    source_schema: &SchemaDefinition,
+    tables: &[String],
+    mut key_columns: HashMap<String, Vec<String>>,
+) -> Result<Vec<SyncTablePlan>, String> {
+    let live_schema = read_live_schema(conn)?;
+    let mut plans = Vec::with_capacity(tables.len());
+    for table in tables {
+        let find =
+            |schema: &SchemaDefinition| schema.tables.iter().find(|t| &t.name == table).cloned();
+        let live = find(&live_schema).ok_or_else(|| format!("Unknown table: {}", table))?;
+        let theirs = find(source_schema).ok_or_else(|| format!("Source has no {} table", table))?;
+        let mut problems = Vec::new();
+        for column in &live.columns {
+            match theirs.columns.iter().find(|c| c.name == column.name) {
+                None => problems.push(format!("missing column {}", column.name)),
+                Some(c) if !c.column_type.eq_ignore_ascii_case(&column.column_type) => problems
+                    .push(format!(
+                        "{} is {} there, {} here",
+                        column.name, c.column_type, column.column_type
+                    )),
+                Some(_) => {}
+            }
+        }
+        for column in &theirs.columns {
+            if !live.columns.iter().any(|c| c.name == column.name) {
+                problems.push(format!("extra column {}", column.name));
+            }
+        }
+        if !problems.is_empty() {
+            return Err(format!(
+                "Source schema for {} doesn't match: {}",
+                table,
+                problems.join(", ")
+            ));
+        }
+
+        let columns: Vec<String> = live.columns.iter().map(|c| c.name.clone()).collect();
+        let keys = match key_columns.remove(table) {
+            Some(keys) => keys,
+            None => {
+                let mut stmt = conn
+                    .prepare("SELECT name FROM pragma_table_info(?1) WHERE pk > 0 ORDER BY pk")
+                    .map_err(|e| format!("Failed to read key of {}: {}", table, e))?;
+                let keys = stmt
+                    .query_map(params![table], |row| row.get(0))
+                    .map_err(|e| format!("Failed to read key of {}: {}", table, e))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                keys
+            }
+        };
+        if keys.is_empty() {
+            return Err(format!(
+                "{} has no primary key; pass key_columns for it",
+                table
+            ));
+        }
+        if let Some(unknown) = keys.iter().find(|k| !columns.contains(k)) {
+            return Err(format!("Unknown key column {} in table {}", unknown, table));
+        }
+        plans.push(SyncTablePlan {
+            table: table.clone(),
+            columns,
+            keys,
+        });
+    }
+    if let Some(table) = key_columns.keys().next() {
+        return Err(format!(
+            "key_columns names {}, which is not being synced",
+            table
+        ));
+    }
+    Ok(plans)
+}
+
+/// Inserts the source rows whose key isn't in `conn` yet for every planned
+/// table, all in one transaction, and returns the per-table counts
+pub(crate) fn sync_planned_tables(
+    conn: &mut Connection,
+    source_path: &str,
+    plans: &[SyncTablePlan],
+) -> Result<Vec<TableSyncCount>, String> {
+    conn.execute("ATTACH DATABASE ?1 AS src", params![source_path])
+        .map_err(|e| format!("Failed to open sync source: {}", e))?;
+    let quote = |name: &str| format!("\"{}\"", name.replace('"', "\"\""));
+    let synced = (|| -> Result<Vec<TableSyncCount>, String> {
+        // Dropping the transaction on any error rolls every table back
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let mut counts = Vec::with_capacity(plans.len());
+        for SyncTablePlan {
+            table,
+            columns,
+            keys,
+        } in plans
+        {
+            let sync_err = |e: rusqlite::Error| format!("Sync of {} failed: {}", table, e);
+            let total: i64 = tx
+                .query_row(
+                    &format!("SELECT COUNT(*) FROM src.{}", quote(table)),
+                    [],
+                    |row| row.get(0),
+                )
+                .map_err(sync_err)?;
+            let column_list = columns
+                .iter()
+                .map(|c| quote(c))
+                .collect::<Vec<_>>()
+                .join(", ");
+            // IS rather than = so NULL keys match each other
+            let key_match = keys
+                .iter()
+                .map(|k| format!("d.{0} IS s.{0}", quote(k)))
+                .collect::<Vec<_>>()
+                .join(" AND ");
+            let inserted = tx
+                .execute(
+                    &format!(
+                        "INSERT INTO main.{0} ({1}) SELECT {1} FROM src.{0} s
+                         WHERE NOT EXISTS (SELECT 1 FROM main.{0} d WHERE {2})",
+                        quote(table),
+                        column_list,
+                        key_match
+                    ),
+                    [],
+                )
+                .map_err(sync_err)?;
+            counts.push(TableSyncCount {
+                table: table.clone(),
+                inserted,
+                skipped: (total.max(0) as usize).saturating_sub(inserted),
+            });
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit sync: {}", e))?;
+        Ok(counts)
+    })();
+    let _ = conn.execute_batch("DETACH DATABASE src");
+    synced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::sample_database;
+
+    fn zip_bundle(entries: &[(&str, &str)]) -> zip::ZipArchive<std::io::Cursor<Vec<u8>>> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, contents) in entries {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        zip::ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn bundle_entries_reject_traversal_and_unknown_tables_unless_lenient() {
+        let db = sample_database();
+        let schema = read_live_schema(&Connection::open(&db.0).unwrap()).unwrap();
+        let bundle = || {
+            zip_bundle(&[
+                ("products.csv", "id,name\n"),
+                ("../products.csv", "id,name\n"),
+                ("customers.csv", "id\n"),
+                ("notes.txt", "hello"),
+            ])
+        };
+
+        let error = match_bundle_entries(&mut bundle(), &schema, false).unwrap_err();
+        assert_eq!(
+            error,
+            "Rejected archive entry ../products.csv: path escapes the archive"
+        );
+
+        let matched = match_bundle_entries(&mut bundle(), &schema, true).unwrap();
+        assert_eq!(matched.entries, [(0, "products".to_string())]);
+        assert_eq!(matched.skipped.len(), 3);
+    }
+
+    #[test]
+    fn csv_table_import_appends_replaces_and_checks_the_header() {
+        let db = sample_database();
+        let conn = Connection::open(&db.0).unwrap();
+        let schema = read_live_schema(&conn).unwrap();
+        let products = &schema.tables[0];
+        let count = || -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM products", [], |row| row.get(0))
+                .unwrap()
+        };
+
+        let csv = "name,id\nClutch plate,c\nBolt,\n";
+        assert_eq!(
+            import_csv_table(&conn, products, csv.as_bytes(), false),
+            Ok(2)
+        );
+        assert_eq!(count(), 4);
+        let null_ids: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM products WHERE id IS NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(null_ids, 1);
+
+        let csv = "id,name\nd,Disc\n";
+        assert_eq!(
+            import_csv_table(&conn, products, csv.as_bytes(), true),
+            Ok(1)
+        );
+        assert_eq!(count(), 1);
+
+        let csv = "id,colour\ne,Red\n";
+        assert_eq!(
+            import_csv_table(&conn, products, csv.as_bytes(), false),
+            Err("Unknown column(s): colour".to_string())
+        );
+        assert_eq!(count(), 1);
+    }
+
+    fn database_with(sql: &str) -> TempFileGuard {
+        let path = TempFileGuard(unique_temp_path("test_sync", "db"));
+        Connection::open(&path.0)
+            .unwrap()
+            .execute_batch(sql)
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn sync_inserts_only_rows_whose_key_is_new() {
+        let db = sample_database();
+        let source = database_with(
+            "CREATE TABLE products (id TEXT PRIMARY KEY, name TEXT NOT NULL);
+             INSERT INTO products VALUES ('a', 'Renamed'), ('c', 'Clutch plate');",
+        );
+        let source_schema = read_live_schema(&Connection::open(&source.0).unwrap()).unwrap();
+        let mut conn = Connection::open(&db.0).unwrap();
+
+        let plans = plan_sync_tables(
+            &conn,
+            &source_schema,
+            &["products".to_string()],
+            HashMap::new(),
+        )
+        .unwrap();
+        assert_eq!(plans[0].keys, ["id"]);
+        let counts = sync_planned_tables(&mut conn, &source.0.to_string_lossy(), &plans).unwrap();
+
+        assert_eq!((counts[0].inserted, counts[0].skipped), (1, 1));
+        let name: String = conn
+            .query_row("SELECT name FROM products WHERE id = 'a'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(name, "Brake pad");
+    }
+
+    #[test]
+    fn sync_rejects_mismatched_schemas_and_unused_keys() {
+        let db = sample_database();
+        let conn = Connection::open(&db.0).unwrap();
+        let products = vec!["products".to_string()];
+        let schema_of = |sql: &str| {
+            let source = database_with(sql);
+            read_live_schema(&Connection::open(&source.0).unwrap()).unwrap()
+        };
+
+        let extra = schema_of("CREATE TABLE products (id TEXT PRIMARY KEY, name TEXT, price REAL)");
+        let error = plan_sync_tables(&conn, &extra, &products, HashMap::new()).unwrap_err();
+        assert_eq!(
+            error,
+            "Source schema for products doesn't match: extra column price"
+        );
+
+        let same = schema_of("CREATE TABLE products (id TEXT PRIMARY KEY, name TEXT NOT NULL)");
+        let keys = HashMap::from([("invoices".to_string(), vec!["id".to_string()])]);
+        let error = plan_sync_tables(&conn, &same, &products, keys).unwrap_err();
+        assert_eq!(
+            error,
+            "key_columns names invoices, which is not being synced"
+        );
+    }
+
+    #[test]
+    fn sync_rolls_every_table_back_when_one_fails() {
+        let db = database_with(
+            "CREATE TABLE products (id TEXT PRIMARY KEY, name TEXT NOT NULL);
+             CREATE TABLE stock (id TEXT PRIMARY KEY, qty INTEGER CHECK (qty > 0));",
+        );
+        let source = database_with(
+            "CREATE TABLE products (id TEXT PRIMARY KEY, name TEXT NOT NULL);
+             CREATE TABLE stock (id TEXT PRIMARY KEY, qty INTEGER);
+             INSERT INTO products VALUES ('a', 'Brake pad');
+             INSERT INTO stock VALUES ('a', 0);",
+        );
+        let source_schema = read_live_schema(&Connection::open(&source.0).unwrap()).unwrap();
+        let mut conn = Connection::open(&db.0).unwrap();
+        let tables = ["products".to_string(), "stock".to_string()];
+
+        let plans = plan_sync_tables(&conn, &source_schema, &tables, HashMap::new()).unwrap();
+        let error =
+            sync_planned_tables(&mut conn, &source.0.to_string_lossy(), &plans).unwrap_err();
+
+        assert!(error.starts_with("Sync of stock failed"), "{}", error);
+        let products: i64 = conn
+            .query_row("SELECT COUNT(*) FROM products", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(products, 0);
+    }
+}
@@ -42,6 +42,10 @@ export const BackupRestore: React.FC = () => {
         backup: null,
     });
 
+    // Encrypted import waiting for its passphrase
+    const [encryptedImport, setEncryptedImport] = useState<string | null>(null);
+    const [importPassphrase, setImportPassphrase] = useState("");
+
     // Delete confirmation state
     const [deleteConfirm, setDeleteConfirm] = useState<{ open: boolean; backup: BackupFileInfo | null }>({
         open: false,
@@ -77,7 +81,11 @@ export const BackupRestore: React.FC = () => {
     const handleManualBackup = async () => {
         setIsBackingUp(true);
         try {
-            await backupService.triggerBackup('manual');
+            const filename = await backupService.triggerBackup('manual');
+            if (!filename) {
+                toast.warning("Nothing to Back Up", "The database has no data yet, so no backup was created");
+                return;
+            }
             toast.success("Backup Complete", "Database has been backed up successfully");
             loadData();
         } catch (error) {
@@ -132,14 +140,27 @@ export const BackupRestore: React.FC = () => {
                 return; // User cancelled
             }
 
-            const result = await backupService.importBackup(filePath);
-            toast.success("Import Complete", result);
-            
-            // Give user a moment to see success message, then reload to refresh all data
-            toast.info("Refreshing", "Reloading application to apply imported data...");
-            setTimeout(() => {
-                window.location.reload();
-            }, 1500);
+            // A portable folder points at its own .db; show what it holds first
+            const portable = await backupService.detectPortableBackup(filePath);
+            if (portable && !portable.checksum_ok) {
+                throw new Error(`${portable.backup_filename} does not match its recorded checksum`);
+            }
+            if (portable) {
+                const capturedAt = portable.captured_at
+                    ? new Date(portable.captured_at).toLocaleString()
+                    : "an unknown date";
+                toast.info("Portable Backup", `${portable.backup_filename} with data from ${capturedAt}`);
+            }
+
+            // Encrypted backups are decrypted by import_backup once the passphrase is known
+            if (!portable && filePath.endsWith(".enc")) {
+                setImportPassphrase("");
+                setEncryptedImport(filePath);
+                setIsImporting(false);
+                return;
+            }
+
+            await runImport(portable ? portable.backup_path : filePath);
         } catch (error) {
             console.error(error);
             toast.error("Import Failed", error instanceof Error ? error.message : "Could not import backup");
@@ -147,6 +168,36 @@ export const BackupRestore: React.FC = () => {
         }
     };
 
+    const runImport = async (sourcePath: string, passphrase?: string) => {
+        const result = await backupService.importBackup(sourcePath, passphrase);
+        toast.success("Import Complete", result);
+
+        // Give user a moment to see success message, then reload to refresh all data
+        toast.info("Refreshing", "Reloading application to apply imported data...");
+        setTimeout(() => {
+            window.location.reload();
+        }, 1500);
+    };
+
+    const handleEncryptedImport = async () => {
+        if (!encryptedImport) return;
+        if (!importPassphrase) {
+            toast.error("Error", "Please enter the backup's passphrase");
+            return;
+        }
+        setIsImporting(true);
+        try {
+            await runImport(encryptedImport, importPassphrase);
+            setEncryptedImport(null);
+        } catch (error) {
+            console.error(error);
+            toast.error("Import Failed", error instanceof Error ? error.message : "Could not import backup");
+            setIsImporting(false);
+        } finally {
+            setImportPassphrase("");
+        }
+    };
+
 
 
     const handleDeleteBackup = async () => {
@@ -468,7 +519,7 @@ export const BackupRestore: React.FC = () => {
                             Restore from File
                         </h3>
                         <p className="text-sm text-slate-500 mb-4">
-                            Have a backup file (.db) saved externally? Select it to restore your data.
+                            Have a backup file (.db, or an encrypted .enc) saved externally? Select it to restore your data.
                         </p>
                         <Button
                             variant="secondary"
@@ -494,6 +545,43 @@ export const BackupRestore: React.FC = () => {
                 variant="danger"
             />
 
+            {/* Encrypted Import Passphrase Modal */}
+            {encryptedImport && (
+                <div className="fixed inset-0 z-50 flex items-center justify-center bg-black/50">
+                    <div className="bg-white rounded-2xl shadow-2xl w-full max-w-md p-6 m-4">
+                        <h3 className="text-lg font-bold text-slate-800 mb-2">Encrypted Backup</h3>
+                        <p className="text-sm text-slate-500 mb-4">
+                            Enter the passphrase this backup was created with. A safety backup will be created automatically before importing.
+                        </p>
+                        <div>
+                            <label className="block text-sm font-medium text-slate-700 mb-1.5">Passphrase</label>
+                            <Input
+                                type="password"
+                                value={importPassphrase}
+                                onChange={(e) => setImportPassphrase(e.target.value)}
+                                placeholder="Enter backup passphrase"
+                            />
+                        </div>
+                        <div className="flex gap-3 mt-6">
+                            <Button
+                                variant="secondary"
+                                className="flex-1"
+                                disabled={isImporting}
+                                onClick={() => {
+                                    setEncryptedImport(null);
+                                    setImportPassphrase("");
+                                }}
+                            >
+                                Cancel
+                            </Button>
+                            <Button className="flex-1" isLoading={isImporting} onClick={handleEncryptedImport}>
+                                Decrypt and Import
+                            </Button>
+                        </div>
+                    </div>
+                </div>
+            )}
+
             {/* Delete Confirmation Modal */}
             <ConfirmModal
                 isOpen={deleteConfirm.open}
@@ -1,6 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
 import { open, save } from "@tauri-apps/plugin-dialog";
-import { BackupFileInfo, BackupLog, BackupResult } from "../types";
+import { BackupFileInfo, BackupLog, BackupResult, DatabaseSwitched, ImportResult, PortableBackupInfo, RepairResult, RestoreProbe, RetentionResult } from "../types";
 import { getDb } from "./index";
 import { isTauriRuntime } from "./runtime";
 import { settingsService } from "./settingsService";
@@ -32,9 +32,11 @@ const saveBackupLog = (logs: BackupLog[]) => {
 
 export const backupService = {
   /**
-   * Triggers a database backup and logs the result
+   * Triggers a database backup and logs the result.
+   * Resolves to an empty string when the database has nothing to back up yet.
+   * `compress` writes a gzipped .db.gz instead of a plain .db.
    */
-  async triggerBackup(type: 'auto' | 'manual' = 'manual'): Promise<string> {
+  async triggerBackup(type: 'auto' | 'manual' = 'manual', compress = false): Promise<string> {
     const backupDate = new Date().toISOString();
     let backupFile = `motormods_backup_${backupDate.replace(/[:.]/g, '-')}.db`;
     let fileSize: number | null = null;
@@ -43,7 +45,11 @@ export const backupService = {
     try {
       if (isTauriRuntime()) {
         // The Rust command returns a BackupResult object with file info
-        const result = await invoke<BackupResult>("backup_database");
+        const result = await invoke<BackupResult>("backup_database", { compress });
+        if (result.skipped_reason) {
+          // A backup was just taken; nothing new to log
+          return result.filename;
+        }
         backupFile = result.filename;
         fileSize = result.file_size;
       }
@@ -95,7 +101,20 @@ export const backupService = {
 
     // Step 3: Reopen the database connection by calling getDb
     // This will create a fresh connection to the restored database
-    await getDb();
+    const database = await getDb();
+
+    // Step 4: Confirm the reopened connection sees the restored data
+    const probe = await invoke<RestoreProbe>("probe_restored_database", {
+      expectedBackup: backupFilename,
+    });
+    const stamp = await database.select<{ value: string }[]>(
+      "SELECT value FROM app_meta WHERE key = 'last_restore_from'"
+    );
+    if (stamp[0]?.value !== backupFilename) {
+      console.warn("[Restore] Reconnected database does not show the restore stamp; reconnection may have failed");
+    } else if (probe.warning) {
+      console.warn(`[Restore] ${probe.warning}`);
+    }
 
     return result;
   },
@@ -103,8 +122,9 @@ export const backupService = {
   /**
    * Imports and restores from an external backup file path.
    * Closes DB connection, replaces file, reopens connection.
+   * Encrypted backups (.db.enc, .db.gz.enc) need their passphrase.
    */
-  async importBackup(sourcePath: string): Promise<string> {
+  async importBackup(sourcePath: string, passphrase?: string): Promise<string> {
     if (!isTauriRuntime()) {
       throw new Error("Import is only available in the desktop application");
     }
@@ -116,12 +136,60 @@ export const backupService = {
     await closeDatabase();
 
     // Step 2: Have Rust replace the database file with the external backup
-    const result = await invoke<string>("import_backup", { sourcePath });
+    const result = await invoke<ImportResult>("import_backup", { sourcePath, passphrase });
 
     // Step 3: Reopen the database connection
     await getDb();
 
-    return result;
+    return result.message;
+  },
+
+  /**
+   * Rebuilds a database that fails its integrity check.
+   * Closes DB connection, repairs the file, reopens connection.
+   */
+  async repairDatabase(): Promise<RepairResult> {
+    if (!isTauriRuntime()) {
+      throw new Error("Repair is only available in the desktop application");
+    }
+
+    const { closeDatabase } = await import("./index");
+
+    // Step 1: Close the frontend database connection so the file can be swapped
+    await closeDatabase();
+
+    // Step 2: Have Rust rebuild and swap the file; reconnect either way
+    try {
+      return await invoke<RepairResult>("repair_database");
+    } finally {
+      await getDb();
+    }
+  },
+
+  /**
+   * Switches the app to another database file (e.g. a training copy).
+   * Closes DB connection, switches, reopens connection on the new file.
+   */
+  async switchDatabase(dbName: string, createIfMissing = false): Promise<DatabaseSwitched> {
+    if (!isTauriRuntime()) {
+      throw new Error("Switching databases is only available in the desktop application");
+    }
+
+    const { closeDatabase } = await import("./index");
+
+    // Step 1: Close the frontend database connection
+    await closeDatabase();
+
+    // Step 2: Point the Rust commands at the new file; reopen the old one if refused
+    let switched: DatabaseSwitched;
+    try {
+      switched = await invoke<DatabaseSwitched>("switch_database", { dbName, createIfMissing });
+    } finally {
+      // Step 3: Reconnect; getDb asks Rust for the active file
+      await getDb();
+    }
+
+    return switched;
   },
 
   /**
@@ -137,7 +205,13 @@ export const backupService = {
       directory: false,
       filters: [{
         name: "Database Backup",
-        extensions: ["db"]
+        extensions: ["db", "sqlite", "sqlite3", "db3"]
+      }, {
+        name: "Portable Backup",
+        extensions: ["json"]
+      }, {
+        name: "Encrypted Backup",
+        extensions: ["enc"]
       }],
       title: "Select Backup File to Import"
     });
@@ -149,6 +223,17 @@ export const backupService = {
     return null;
   },
 
+  /**
+   * Returns the portable backup folder a picked path belongs to, or null
+   */
+  async detectPortableBackup(path: string): Promise<PortableBackupInfo | null> {
+    if (!isTauriRuntime()) {
+      return null;
+    }
+
+    return await invoke<PortableBackupInfo | null>("detect_portable_backup", { path });
+  },
+
   /**
    * Exports a backup to a user-selected location
    */
@@ -179,14 +264,15 @@ export const backupService = {
   },
 
   /**
-   * Lists all backup files from the filesystem
+   * Lists all backup files from the filesystem, newest first.
+   * 'data_time' orders by when the data was captured rather than the file mtime.
    */
-  async listBackupFiles(): Promise<BackupFileInfo[]> {
+  async listBackupFiles(sort: 'mtime' | 'data_time' = 'mtime'): Promise<BackupFileInfo[]> {
     if (!isTauriRuntime()) {
       return [];
     }
 
-    return await invoke<BackupFileInfo[]>("list_backups");
+    return await invoke<BackupFileInfo[]>("list_backups", { sort });
   },
 
   /**
@@ -263,6 +349,12 @@ export const backupService = {
       return;
     }
 
+    // Paused keeps the schedule configured but skips runs until resumed
+    const autoBackupPaused = await settingsService.get('auto_backup_paused');
+    if (autoBackupPaused) {
+      return;
+    }
+
     if (lastBackup !== today) {
       try {
         await this.triggerBackup('auto');
@@ -301,17 +393,15 @@ export const backupService = {
         [cutoffIso]
       );
 
-      // Also clean up actual backup files
-      const backupFiles = await this.listBackupFiles();
-      for (const file of backupFiles) {
-        if (file.modified_at < cutoffIso) {
-          try {
-            await this.deleteBackup(file.filename);
-            console.log(`Deleted old backup: ${file.filename}`);
-          } catch (e) {
-            console.error(`Failed to delete backup ${file.filename}:`, e);
-          }
-        }
+      // Rust skips protected and safety backups and keeps the last good one
+      const result = await invoke<RetentionResult>("apply_retention_policy", {
+        retentionDays,
+      });
+      for (const filename of result.deleted) {
+        console.log(`Deleted old backup: ${filename}`);
+      }
+      if (result.warning) {
+        console.warn(result.warning);
       }
 
       console.log(`Cleaned up backups older than ${retentionDays} days`);
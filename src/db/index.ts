@@ -1,4 +1,5 @@
 import Database from "@tauri-apps/plugin-sql";
+import { invoke } from "@tauri-apps/api/core";
 
 let db: Database | null = null;
 
@@ -346,13 +347,58 @@ const migrateProductsSkuNullable = async (database: Database) => {
   `);
 };
 
+/**
+ * Compares the migrated schema against the definition shipped with the app
+ * and warns about drift. Never blocks or fails startup.
+ */
+const warnOnSchemaDrift = async () => {
+  try {
+    const diff = await invoke<{ matches: boolean }>("validate_schema");
+    if (!diff.matches) {
+      console.warn("[DB] Schema does not match this app version:", diff);
+    }
+  } catch (error) {
+    console.error("[DB] Schema self-check failed:", error);
+  }
+};
+
+// synchronous is per-connection, so the profile chosen in Rust is reapplied here
+const applyDurabilityProfile = async (database: Database) => {
+  try {
+    const profile = await invoke<{ profile: string | null; synchronous: string }>(
+      "get_durability_profile"
+    );
+    if (profile.profile) {
+      await database.execute(`PRAGMA synchronous = ${profile.synchronous}`);
+    }
+  } catch (error) {
+    console.error("[DB] Failed to apply durability profile:", error);
+  }
+};
+
+// busy_timeout is per-connection too; set_busy_timeout saves it in Rust
+const applyBusyTimeout = async (database: Database) => {
+  try {
+    const diagnostics = await invoke<{ busy_timeout_ms: number }>("get_lock_diagnostics", {
+      limit: 0,
+    });
+    await database.execute(`PRAGMA busy_timeout = ${Math.floor(diagnostics.busy_timeout_ms)}`);
+  } catch (error) {
+    console.error("[DB] Failed to apply busy timeout:", error);
+  }
+};
+
 export const getDb = async () => {
   if (db) return db;
 
-  // Use the plugin-managed SQLite path (app data dir) consistently.
-  // This also matches the connection name used by the Tauri plugin.
-  db = await Database.load("sqlite:motormods.db");
+  // Ask the backend for the database file so an override directory or a
+  // fallback location is used by both sides, not just the Rust commands.
+  const url = await invoke<string>("get_database_url");
+  db = await Database.load(url);
   await ensureSchema(db);
   await migrateProductsSkuNullable(db);
+  await applyDurabilityProfile(db);
+  await applyBusyTimeout(db);
+  void warnOnSchemaDrift();
   return db;
 };
@@ -10,6 +10,7 @@ const defaultSettings: AppSettings = {
     low_stock_days_supply: 15,
     non_moving_threshold_days: 120,
     auto_backup_enabled: true,
+    auto_backup_paused: false,
     auto_backup_time: '23:00',
     backup_retention_days: 30,
     // Store Details
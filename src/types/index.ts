@@ -159,6 +159,13 @@ export interface BackupResult {
   path: string;
   file_size: number;
   created_at: string;
+  // Set when a backup was skipped inside the cooldown; fields describe the earlier backup
+  skipped_reason: string | null;
+  // Set with skipped_reason when the database is still empty; no file was written
+  nothing_to_back_up: boolean;
+  // The new file had a SQLite header and passed integrity_check
+  verified: boolean;
+  integrity_error: string | null;
 }
 
 // Backup file info from filesystem
@@ -167,6 +174,83 @@ export interface BackupFileInfo {
   path: string;
   file_size: number;
   modified_at: string;
+  // Milliseconds since epoch for sorting; null when the file time is unknown
+  modified_epoch_ms: number | null;
+  protected: boolean;
+  redacted: boolean;
+  // When the data was captured, from the manifest; survives copies unlike the mtime
+  captured_at: string | null;
+}
+
+// Response from Rust apply_retention_policy command
+export interface RetentionResult {
+  retention_days: number;
+  deleted: string[];
+  preserved_last_good: string | null;
+  warning: string | null;
+  // Rotation folders that could not be read and were left untouched
+  unavailable_folders: string[];
+}
+
+// Schema ids of a backup next to the running database's
+export interface BackupVersionCheck {
+  backup_user_version: number;
+  backup_application_id: number;
+  expected_user_version: number;
+  expected_application_id: number;
+  // Importing needs allow_newer
+  newer: boolean;
+  // Importing needs force; probably not a MotorMods database
+  foreign: boolean;
+}
+
+// Response from Rust import_backup command
+export interface ImportResult {
+  message: string;
+  versions: BackupVersionCheck;
+}
+
+// Response from Rust repair_database command
+export interface RepairResult {
+  repaired: boolean;
+  integrity_before: string;
+  safety_backup: string | null;
+  rows_recovered: number;
+  dropped: { object: string; detail: string }[];
+  message: string;
+}
+
+// A folder written by export_portable_backup, from detect_portable_backup
+export interface PortableBackupInfo {
+  folder: string;
+  backup_path: string;
+  // The .db still matches the checksum recorded at export
+  checksum_ok: boolean;
+  format: string;
+  backup_filename: string;
+  sha256: string;
+  file_size: number;
+  schema_version: number;
+  captured_at: string | null;
+  exported_at: string;
+  app_version: string;
+}
+
+// Database the app is pointed at, from switch_database / get_active_database
+export interface DatabaseSwitched {
+  name: string;
+  path: string;
+  connection: string;
+}
+
+// Response from Rust probe_restored_database command
+export interface RestoreProbe {
+  restored_from: string | null;
+  restored_at: string | null;
+  table_counts: { table: string; count: number; approximate: boolean }[];
+  expected_backup: string | null;
+  matches_expected: boolean;
+  warning: string | null;
 }
 
 // ============================================
@@ -183,6 +267,7 @@ export interface AppSettings {
   non_moving_threshold_days: number;
   // Backup Settings
   auto_backup_enabled: boolean;
+  auto_backup_paused: boolean;
   auto_backup_time: string;
   backup_retention_days: number;
   // Store Details (for invoice branding)